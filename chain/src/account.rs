@@ -9,25 +9,31 @@ use utils::crypto::to_address;
 use crate::helpers::{deserialize, serialize};
 use crate::{
     error::{ChainError, Result},
-    storage::Storage,
+    storage::{Storage, TrieDb},
 };
 
 /// AccountStorage 结构体用于存储账户的相关信息。
 /// 它使用 EthTrie 来管理存储数据，确保数据的高效检索和组织。
 ///
 /// 字段:
-/// - trie: 一个使用 Storage 作为底层数据结构的 EthTrie 实例。
+/// - trie: 一个使用 TrieDb 作为底层数据结构的 EthTrie 实例。
 ///         它负责实际的数据存储和检索操作。
+/// - db: trie使用的数据库，也用于打开每个合约账户自己的存储trie。
+///   快照使用当前数据库之上的覆盖层，对快照的修改不会写入共享的存储。
 #[derive(Debug)]
 pub(crate) struct AccountStorage {
-    pub(crate) trie: EthTrie<Storage>,
+    pub(crate) trie: EthTrie<TrieDb>,
+    db: Arc<TrieDb>,
 }
 
 impl AccountStorage {
     /// 创建一个新的AccountStorage实例
     pub(crate) fn new(storage: Arc<Storage>) -> Self {
+        let db = Arc::new(TrieDb::new(storage));
+
         Self {
-            trie: EthTrie::new(Arc::clone(&storage)),
+            trie: EthTrie::new(Arc::clone(&db)),
+            db,
         }
     }
 
@@ -54,6 +60,20 @@ impl AccountStorage {
         Ok(account)
     }
 
+    /// 替换一个合约账户的代码
+    ///
+    /// 账户的存储、余额和nonce保持不变，非合约账户返回`NotAContractAccount`
+    pub(crate) fn set_code(&mut self, contract: &Account, new_code: Bytes) -> Result<()> {
+        let mut account_data = self.get_account(contract)?;
+
+        if account_data.code_hash.is_none() {
+            return Err(ChainError::NotAContractAccount(contract.to_string()));
+        }
+
+        account_data.code_hash = Some(new_code);
+        self.upsert(contract, &account_data)
+    }
+
     /// 获取一个账户的数据
     pub(crate) fn get_account(&self, key: &Account) -> Result<AccountData> {
         let account = &self
@@ -77,22 +97,27 @@ impl AccountStorage {
         Ok(accounts)
     }
 
-    /// 增加一个账户的余额
+    /// 增加一个账户的余额，账户不存在时先创建一个空账户
     pub(crate) fn add_account_balance(&mut self, key: &Account, amount: U256) -> Result<()> {
-        let mut account_data = self.get_account(key)?;
+        let mut account_data = match self.get_account(key) {
+            Err(ChainError::StorageNotFound(_)) => AccountData::empty(),
+            account_data => account_data?,
+        };
         account_data.balance += amount;
         self.upsert(key, &account_data)
     }
 
-    /// 减少一个账户的余额
+    /// 减少一个账户的余额，余额不足时返回`InsufficientFunds`
     pub(crate) fn subtract_account_balance(&mut self, key: &Account, amount: U256) -> Result<()> {
         let mut account_data = self.get_account(key)?;
-        let balance = account_data.balance - amount;
-        account_data.balance = std::cmp::max(U256::zero(), balance);
+        account_data.balance = account_data
+            .balance
+            .checked_sub(amount)
+            .ok_or_else(|| ChainError::InsufficientFunds(key.to_string(), amount.to_string()))?;
         self.upsert(key, &account_data)
     }
 
-    /// 在账户之间转移余额
+    /// 在账户之间转移余额，接收方不存在时会被创建
     pub(crate) fn transfer(&mut self, from: &Account, to: &Account, amount: U256) -> Result<()> {
         self.subtract_account_balance(from, amount)?;
         self.add_account_balance(to, amount)?;
@@ -100,9 +125,9 @@ impl AccountStorage {
         Ok(())
     }
 
-    /// 更新账户的nonce值
-    pub(crate) fn update_nonce(&mut self, key: &Account, nonce: U256) -> Result<U256> {
-        let mut account_data = self.get_account(key)?;
+    /// 检查nonce是否为账户的下一个nonce，不修改账户
+    pub(crate) fn check_nonce(&self, key: &Account, nonce: U256) -> Result<()> {
+        let account_data = self.get_account(key)?;
 
         if nonce < account_data.nonce + 1 {
             return Err(ChainError::NonceTooLow(nonce.to_string(), key.to_string()));
@@ -112,14 +137,74 @@ impl AccountStorage {
             return Err(ChainError::NonceTooHigh(nonce.to_string(), key.to_string()));
         }
 
+        Ok(())
+    }
+
+    /// 更新账户的nonce值
+    pub(crate) fn update_nonce(&mut self, key: &Account, nonce: U256) -> Result<U256> {
+        self.check_nonce(key, nonce)?;
+
+        let mut account_data = self.get_account(key)?;
         account_data.nonce = nonce;
         self.upsert(key, &account_data)?;
 
         Ok(account_data.nonce)
     }
 
-    /// 获取账户存储的根哈希值
-    pub(crate) fn root_hash(&mut self) -> Result<H256> {
+    /// 创建当前账户状态的快照
+    ///
+    /// 先将当前的trie提交到内存中的覆盖层得到根哈希，再基于该根哈希在一个新的覆盖层上打开trie，
+    /// 对快照的修改（包括快照内的提交）只保存在快照自己的覆盖层中，不会影响原有的账户存储，
+    /// 也不会写入共享的存储。快照需要通过`merge`才会被采用，否则直接丢弃即可
+    pub(crate) fn snapshot(&mut self) -> Result<Self> {
+        let root_hash = self.checkpoint()?;
+
+        self.overlay_at(root_hash)
+    }
+
+    /// 复制当前账户状态，包括还没有提交的修改
+    ///
+    /// 将所有账户逐个写入一个新覆盖层上的trie，不需要提交当前的trie，因此只需要`&self`，
+    /// 对副本的修改不会影响原有的账户存储，也不会写入共享的存储
+    pub(crate) fn fork(&self) -> Result<Self> {
+        let db = Arc::new(TrieDb::overlay(&self.db));
+        let mut trie = EthTrie::new(Arc::clone(&db));
+        for (key, value) in self.trie.iter() {
+            trie.insert(&key, &value)
+                .map_err(|_| ChainError::StoragePutError(Storage::key_string(&key)))?;
+        }
+
+        Ok(Self { trie, db })
+    }
+
+    /// 打开指定状态根哈希对应的账户状态，例如某个历史区块之后的状态
+    ///
+    /// 返回的账户状态位于一个新的覆盖层上，对它的修改不会写入共享的存储
+    pub(crate) fn at_root(&self, state_root: H256) -> Result<Self> {
+        self.overlay_at(state_root)
+    }
+
+    /// 采用一个基于当前账户存储创建的快照，快照覆盖层中的节点合并到当前的数据库
+    ///
+    /// 快照不是由当前账户存储创建时返回`ChainError::StorageError`
+    pub(crate) fn merge(&mut self, mut snapshot: Self) -> Result<()> {
+        if !snapshot.db.is_overlay_of(&self.db) {
+            return Err(ChainError::StorageError(
+                "cannot merge a snapshot of another account storage".into(),
+            ));
+        }
+
+        let root_hash = snapshot.checkpoint()?;
+        snapshot.db.merge_into_base()?;
+        self.trie = open_trie(&self.db, root_hash)?;
+
+        Ok(())
+    }
+
+    /// 将当前的trie提交到内存中的覆盖层，返回提交后的根哈希，不会写入共享的存储
+    ///
+    /// 之后可以通过`revert`回到该根哈希对应的状态
+    pub(crate) fn checkpoint(&mut self) -> Result<H256> {
         let root_hash = self
             .trie
             .root_hash()
@@ -127,6 +212,88 @@ impl AccountStorage {
 
         Ok(H256::from_slice(root_hash.as_bytes()))
     }
+
+    /// 丢弃之后的修改，回到`checkpoint`返回的根哈希对应的状态
+    pub(crate) fn revert(&mut self, root_hash: H256) -> Result<()> {
+        self.trie = open_trie(&self.db, root_hash)?;
+
+        Ok(())
+    }
+
+    /// 在当前数据库之上的新覆盖层中打开指定根哈希对应的trie
+    fn overlay_at(&self, root_hash: H256) -> Result<Self> {
+        let db = Arc::new(TrieDb::overlay(&self.db));
+
+        Ok(Self {
+            trie: open_trie(&db, root_hash)?,
+            db,
+        })
+    }
+
+    /// 打开账户的存储trie，账户还没有写入过存储时返回一个空的trie
+    fn storage_trie(&self, data: &AccountData) -> Result<EthTrie<TrieDb>> {
+        match data.storage_root {
+            Some(storage_root) => open_trie(&self.db, storage_root),
+            None => Ok(EthTrie::new(Arc::clone(&self.db))),
+        }
+    }
+
+    /// 读取合约账户存储中指定键的值
+    pub(crate) fn get_storage(&self, key: &Account, storage_key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let account_data = self.get_account(key)?;
+
+        self.storage_trie(&account_data)?
+            .get(storage_key)
+            .map_err(|_| ChainError::StorageNotFound(Storage::key_string(storage_key)))
+    }
+
+    /// 写入合约账户存储中指定键的值，并更新账户的存储根哈希
+    ///
+    /// 计算存储根哈希时会将合约的存储trie提交到内存中的覆盖层，
+    /// 节点在账户存储计算根哈希时才会写入数据库
+    pub(crate) fn set_storage(
+        &mut self,
+        key: &Account,
+        storage_key: &[u8],
+        value: &[u8],
+    ) -> Result<()> {
+        let mut account_data = self.get_account(key)?;
+        let mut trie = self.storage_trie(&account_data)?;
+
+        trie.insert(storage_key, value)
+            .map_err(|_| ChainError::StoragePutError(Storage::key_string(storage_key)))?;
+        let storage_root = trie
+            .root_hash()
+            .map_err(|e| ChainError::CannotCreateRootHash(format!("storage_trie: {}", e)))?;
+
+        account_data.storage_root = Some(H256::from_slice(storage_root.as_bytes()));
+        self.upsert(key, &account_data)
+    }
+
+    /// 压缩底层数据库，回收删除数据占用的空间
+    pub(crate) fn compact(&self) {
+        self.db.storage().compact();
+    }
+
+    /// 获取账户存储的根哈希值，并将提交的节点写入数据库
+    ///
+    /// `upsert`、`transfer`、`update_nonce`等修改只会累积在内存中的trie里，
+    /// 快照和`set_storage`的提交也只写入内存中的覆盖层，
+    /// 计算根哈希时才将覆盖层中的所有节点通过一个RocksDB WriteBatch写入数据库，
+    /// 因此每个区块只写入一次。被替换的旧节点不会被删除，历史区块的状态根仍然可以打开。
+    /// 快照的下层不是共享的存储，在快照上计算根哈希不会写入数据库
+    pub(crate) fn root_hash(&mut self) -> Result<H256> {
+        let root_hash = self.checkpoint()?;
+        self.db.persist()?;
+
+        Ok(root_hash)
+    }
+}
+
+/// 打开数据库中指定根哈希对应的trie，根节点不存在时返回`ChainError::CannotOpenTrie`
+fn open_trie(db: &Arc<TrieDb>, root: H256) -> Result<EthTrie<TrieDb>> {
+    EthTrie::from(Arc::clone(db), root.to_fixed_bytes().into())
+        .map_err(|e| ChainError::CannotOpenTrie(format!("{:?}: {}", root, e)))
 }
 
 #[cfg(test)]
@@ -196,4 +363,201 @@ mod tests {
 
         assert_ne!(root_hash_1, root_hash_2);
     }
+
+    /// 测试批量提交的状态与逐笔提交的状态一致
+    ///
+    /// 此测试验证了只在最后计算一次根哈希时，得到的状态与每笔转账后都提交一次相同
+    #[test]
+    fn it_commits_a_batch_of_transfers() {
+        let accounts = (0..5).map(|_| Account::random()).collect::<Vec<_>>();
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(1_000);
+
+        let mut batched = new_account_storage();
+        let mut per_transaction = new_account_storage();
+
+        for account in accounts.iter() {
+            batched.add_account(account, &account_data).unwrap();
+            per_transaction.add_account(account, &account_data).unwrap();
+        }
+
+        for (index, from) in accounts.iter().enumerate() {
+            let to = &accounts[(index + 1) % accounts.len()];
+            let amount = U256::from(10 * (index + 1));
+
+            batched.transfer(from, to, amount).unwrap();
+            per_transaction.transfer(from, to, amount).unwrap();
+            per_transaction.root_hash().unwrap();
+        }
+
+        assert_eq!(
+            batched.root_hash().unwrap(),
+            per_transaction.root_hash().unwrap()
+        );
+
+        for account in accounts.iter() {
+            assert_eq!(
+                batched.get_account(account).unwrap(),
+                per_transaction.get_account(account).unwrap()
+            );
+        }
+    }
+
+    /// 测试账户修改只在计算根哈希时写入数据库
+    ///
+    /// 此测试统计数据库的写入次数：普通转账和合约存储的写入在提交前都不写入，
+    /// 每次计算根哈希只写入一次
+    #[test]
+    fn it_counts_database_writes_per_commit() {
+        let storage = Arc::new(Storage::new(Some("test_write_count")).unwrap());
+        let mut account_storage = AccountStorage::new(Arc::clone(&storage));
+        let accounts = (0..5).map(|_| Account::random()).collect::<Vec<_>>();
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(1_000);
+
+        for account in accounts.iter() {
+            account_storage.add_account(account, &account_data).unwrap();
+        }
+
+        for (index, from) in accounts.iter().enumerate() {
+            let to = &accounts[(index + 1) % accounts.len()];
+            account_storage.transfer(from, to, U256::from(10)).unwrap();
+        }
+
+        // 修改只累积在内存中
+        assert_eq!(storage.write_count(), 0);
+
+        // 首次提交没有需要删除的旧节点，只有一次写入
+        account_storage.root_hash().unwrap();
+        assert_eq!(storage.write_count(), 1);
+
+        // 合约存储的提交只写入内存中的覆盖层
+        let contract = account_storage
+            .add_contract_account(&accounts[0], Bytes::from_static(b"code"))
+            .unwrap();
+        account_storage
+            .set_storage(&contract, b"key", b"value")
+            .unwrap();
+        assert_eq!(storage.write_count(), 1);
+
+        account_storage.root_hash().unwrap();
+        assert_eq!(storage.write_count(), 2);
+    }
+
+    /// 测试复制的账户状态包含未提交的修改，并且不会写入数据库
+    #[test]
+    fn it_forks_uncommitted_accounts() {
+        let storage = Arc::new(Storage::new(Some("test_fork")).unwrap());
+        let mut account_storage = AccountStorage::new(Arc::clone(&storage));
+        let (from, to) = (Account::random(), Account::random());
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(1_000);
+        account_storage.add_account(&from, &account_data).unwrap();
+
+        let mut fork = account_storage.fork().unwrap();
+        assert_eq!(storage.write_count(), 0);
+        assert_eq!(fork.get_account(&from).unwrap(), account_data);
+
+        fork.transfer(&from, &to, U256::from(10)).unwrap();
+        assert_eq!(fork.get_account(&to).unwrap().balance, U256::from(10));
+        assert_eq!(
+            account_storage.get_account(&from).unwrap().balance,
+            U256::from(1_000)
+        );
+        assert!(account_storage.get_account(&to).is_err());
+    }
+
+    /// 测试向从未出现过的地址转账
+    ///
+    /// 此测试验证了接收方账户会被自动创建，并且余额为转账的金额
+    #[test]
+    fn it_creates_the_recipient_of_a_transfer() {
+        let mut account_storage = new_account_storage();
+        let (mut account_data, from) = add_account(&mut account_storage);
+        account_data.balance = U256::from(100);
+        account_storage.upsert(&from, &account_data).unwrap();
+        let to = Account::random();
+        assert!(account_storage.get_account(&to).is_err());
+
+        account_storage
+            .transfer(&from, &to, U256::from(10))
+            .unwrap();
+
+        let recipient = account_storage.get_account(&to).unwrap();
+        assert_eq!(recipient.balance, U256::from(10));
+        assert_eq!(recipient.nonce, U256::zero());
+        assert!(!recipient.is_contract());
+        assert_eq!(
+            account_storage.get_account(&from).unwrap().balance,
+            U256::from(90)
+        );
+    }
+
+    /// 测试转账金额超过余额时返回错误
+    ///
+    /// 此测试验证了余额不足时返回`InsufficientFunds`，并且双方的余额都不发生变化
+    #[test]
+    fn it_rejects_a_transfer_exceeding_the_balance() {
+        let mut account_storage = new_account_storage();
+        let (mut account_data, from) = add_account(&mut account_storage);
+        account_data.balance = U256::from(5);
+        account_storage.upsert(&from, &account_data).unwrap();
+        let to = Account::random();
+
+        let result = account_storage.transfer(&from, &to, U256::from(10));
+
+        assert!(matches!(result, Err(ChainError::InsufficientFunds(_, _))));
+        assert_eq!(
+            account_storage.get_account(&from).unwrap().balance,
+            U256::from(5)
+        );
+        assert!(account_storage.get_account(&to).is_err());
+    }
+
+    /// 测试替换合约代码
+    ///
+    /// 此测试验证了合约账户的代码可以被替换，而普通账户不能设置代码
+    #[test]
+    fn it_sets_the_code_of_a_contract() {
+        let mut account_storage = new_account_storage();
+        let (_, id) = add_account(&mut account_storage);
+        let contract = account_storage
+            .add_contract_account(&id, Bytes::from_static(b"old"))
+            .unwrap();
+
+        account_storage
+            .set_code(&contract, Bytes::from_static(b"new"))
+            .unwrap();
+
+        assert_eq!(
+            account_storage.get_account(&contract).unwrap().code_hash,
+            Some(Bytes::from_static(b"new"))
+        );
+        assert_eq!(
+            account_storage.set_code(&id, Bytes::from_static(b"new")),
+            Err(ChainError::NotAContractAccount(id.to_string()))
+        );
+    }
+
+    /// 测试合约账户存储的读写
+    ///
+    /// 此测试验证了写入的值可以从账户的存储trie中读回，并且写入会更新账户的存储根哈希
+    #[test]
+    fn it_sets_and_gets_account_storage() {
+        let mut account_storage = new_account_storage();
+        let (_, id) = add_account(&mut account_storage);
+        assert_eq!(account_storage.get_storage(&id, b"key").unwrap(), None);
+
+        account_storage.set_storage(&id, b"key", b"value").unwrap();
+
+        assert_eq!(
+            account_storage.get_storage(&id, b"key").unwrap(),
+            Some(b"value".to_vec())
+        );
+        assert!(account_storage
+            .get_account(&id)
+            .unwrap()
+            .storage_root
+            .is_some());
+    }
 }
@@ -1,21 +1,128 @@
-use std::collections::VecDeque;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::account::AccountStorage;
+use crate::config::NodeConfig;
+use crate::contract::ContractHost;
 use crate::error::{ChainError, Result};
-use crate::helpers::tests::STORAGE;
+use crate::helpers::serialize;
+use crate::keys::{ADDRESS, PRIVATE_KEY};
 use crate::storage::Storage;
 use crate::transaction::TransactionStorage;
 use crate::world_state::WorldState;
 use eth_trie::DB;
-use ethereum_types::{H256, U64};
-use tokio::sync::Mutex;
-use types::account::Account;
-use types::block::{Block, BlockNumber};
-use types::transaction::{Transaction, TransactionKind, TransactionReceipt, TransactionRequest};
+use ethereum_types::{Bloom, H256, H520, U256, U64};
+use runtime::error::RuntimeError;
+use runtime::host::{CallDepth, Context, Environment, TraceStep};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{broadcast, Mutex};
+use types::account::{Account, AccountData};
+use types::block::{Block, BlockTag};
+use types::bytes::Bytes;
+use types::transaction::{
+    logs_bloom, AccountDiff, Log, LogFilter, SignedTransaction, SimulationResult, Transaction,
+    TransactionKind, TransactionReceipt, TransactionRequest,
+};
+use utils::crypto::{keypair, public_key_address, sign_typed_data};
+use utils::SecretKey;
+
+// 已打包交易通知的缓冲区大小，订阅者落后超过该数量时会丢失最早的通知
+const MINED_TRANSACTIONS_CAPACITY: usize = 1024;
+
+// 新区块通知的缓冲区大小
+const NEW_HEADS_CAPACITY: usize = 64;
+
+// 开发模式下每个测试账户的初始余额
+const DEV_ACCOUNT_BALANCE: u128 = 1_000_000_000_000_000_000_000_000;
+
+/// 节点的健康状态，供负载均衡器等探活使用
+///
+/// 字段:
+/// - healthy: 节点是否可以正常提供服务
+/// - block_number: 当前区块号
+/// - peers: 连接的节点数量，目前节点不连接其他节点，始终为0
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct Health {
+    pub(crate) healthy: bool,
+    pub(crate) block_number: U64,
+    pub(crate) peers: u64,
+}
+
+/// 重新执行交易得到的执行跟踪
+///
+/// 字段:
+/// - transaction_hash: 被跟踪的交易哈希
+/// - steps: 合约执行过程中记录的每一步，非合约调用交易为空
+/// - revert_reason: 合约执行回滚的原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TransactionTrace {
+    pub(crate) transaction_hash: H256,
+    pub(crate) steps: Vec<TraceStep>,
+    pub(crate) revert_reason: Option<String>,
+}
+
+/// 提交到交易池的交易
+#[derive(Debug)]
+pub(crate) enum PendingTransaction {
+    /// 未签名的交易请求
+    Request(TransactionRequest),
+    /// 已签名的交易，进入交易池之前需要验证签名
+    Signed(SignedTransaction),
+}
+
+impl From<TransactionRequest> for PendingTransaction {
+    fn from(transaction_request: TransactionRequest) -> Self {
+        PendingTransaction::Request(transaction_request)
+    }
+}
+
+impl From<Transaction> for PendingTransaction {
+    fn from(transaction: Transaction) -> Self {
+        PendingTransaction::Request(transaction.into())
+    }
+}
+
+impl From<SignedTransaction> for PendingTransaction {
+    fn from(signed_transaction: SignedTransaction) -> Self {
+        PendingTransaction::Signed(signed_transaction)
+    }
+}
+
+/// 新区块打包完成后调用的回调
+pub(crate) type BlockCallback = Box<dyn Fn(&Block) + Send + Sync>;
+
+/// 注册到区块链上的区块回调，按注册顺序调用
+#[derive(Default)]
+pub(crate) struct BlockCallbacks(Vec<BlockCallback>);
+
+impl BlockCallbacks {
+    /// 依次调用所有回调
+    fn notify(&self, block: &Block) {
+        for callback in self.0.iter() {
+            callback(block);
+        }
+    }
+}
+
+// 回调本身无法打印，只输出回调的数量
+impl fmt::Debug for BlockCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BlockCallbacks")
+            .field(&self.0.len())
+            .finish()
+    }
+}
 
 #[derive(Debug)]
 pub(crate) struct BlockChain {
+    // 底层存储，区块在打包和裁剪时写入其中
+    pub(crate) storage: Arc<Storage>,
     // AccountStorage用于存储区块链中的所有账户信息
     pub(crate) accounts: AccountStorage,
     // 存储区块链中的所有区块，Block类型代表区块链中的一个区块
@@ -24,15 +131,99 @@ pub(crate) struct BlockChain {
     pub(crate) transactions: Arc<Mutex<TransactionStorage>>,
     // WorldState代表系统的当前状态，存储了区块链中所有账户的状态信息
     pub(crate) world_state: WorldState,
+    // 允许发送交易的账户，为None时允许所有账户发送交易
+    pub(crate) allowed_senders: Option<HashSet<Account>>,
+    // 节点配置
+    pub(crate) config: NodeConfig,
+    // 每个账户部署的合约地址，按部署顺序排列
+    pub(crate) deployed_contracts: HashMap<Account, Vec<Account>>,
+    // 每个账户作为发送方或接收方参与的交易哈希，按打包顺序排列
+    pub(crate) transaction_history: HashMap<Account, Vec<H256>>,
+    // 已解锁的账户及其私钥，这些账户通过eth_sendTransaction发送的交易由节点代为签名
+    pub(crate) unlocked_accounts: HashMap<Account, SecretKey>,
+    // 交易打包并写入收据后广播其交易哈希，供订阅者接收
+    pub(crate) mined_transactions: broadcast::Sender<H256>,
+    // 新区块的收据写入后广播该区块，供订阅者接收
+    pub(crate) new_heads: broadcast::Sender<Block>,
+    // 开发方法`evm_increaseTime`累加的时间偏移，单位为毫秒，应用于之后所有区块的时间戳
+    pub(crate) time_offset: u64,
+    // 开发方法`evm_setNextBlockTimestamp`指定的下一个区块的时间戳，单位为毫秒
+    pub(crate) next_block_timestamp: Option<u64>,
+    // 每个新区块的收据写入后调用的回调，例如将区块同步到外部的索引服务
+    pub(crate) block_callbacks: BlockCallbacks,
 }
 
 impl BlockChain {
-    pub(crate) fn new(storage: Arc<Storage>) -> Result<Self> {
+    #[cfg(test)]
+    pub(crate) fn new(storage: Arc<Storage>, config: &NodeConfig) -> Result<Self> {
+        let transactions = TransactionStorage::from_config(config, &storage);
+
         Ok(Self {
+            storage: storage.clone(),
             accounts: AccountStorage::new(storage),
-            blocks: vec![Block::genesis()?],
-            transactions: Arc::new(Mutex::new(TransactionStorage::new())),
+            blocks: vec![Block::genesis_with_difficulty(
+                H256::zero(),
+                U256::from(config.genesis_difficulty),
+            )?],
+            transactions: Arc::new(Mutex::new(transactions)),
             world_state: WorldState::new(),
+            allowed_senders: None,
+            config: config.clone(),
+            deployed_contracts: HashMap::new(),
+            transaction_history: HashMap::new(),
+            unlocked_accounts: HashMap::new(),
+            mined_transactions: broadcast::channel(MINED_TRANSACTIONS_CAPACITY).0,
+            new_heads: broadcast::channel(NEW_HEADS_CAPACITY).0,
+            time_offset: 0,
+            next_block_timestamp: None,
+            block_callbacks: BlockCallbacks::default(),
+        })
+    }
+
+    /// 根据创世分配创建区块链
+    ///
+    /// 先将预分配的账户写入账户状态树，再使用状态树的根哈希创建创世块，
+    /// 因此拥有相同创世分配的节点会得到相同的创世块
+    ///
+    /// 参数:
+    /// - `storage`: 底层存储
+    /// - `allocations`: 创世时预先分配的账户及其数据
+    /// - `config`: 节点配置
+    pub(crate) fn from_genesis(
+        storage: Arc<Storage>,
+        allocations: &[(Account, AccountData)],
+        config: &NodeConfig,
+    ) -> Result<Self> {
+        let transactions = TransactionStorage::from_config(config, &storage);
+        let mut accounts = AccountStorage::new(storage.clone());
+
+        for (account, account_data) in allocations.iter() {
+            accounts.add_account(account, account_data)?;
+        }
+
+        let state_root = accounts.root_hash()?;
+        let mut world_state = WorldState::new();
+        world_state.update_state_trie(state_root);
+
+        Ok(Self {
+            storage,
+            accounts,
+            blocks: vec![Block::genesis_with_difficulty(
+                state_root,
+                U256::from(config.genesis_difficulty),
+            )?],
+            transactions: Arc::new(Mutex::new(transactions)),
+            world_state,
+            allowed_senders: None,
+            config: config.clone(),
+            deployed_contracts: HashMap::new(),
+            transaction_history: HashMap::new(),
+            unlocked_accounts: HashMap::new(),
+            mined_transactions: broadcast::channel(MINED_TRANSACTIONS_CAPACITY).0,
+            new_heads: broadcast::channel(NEW_HEADS_CAPACITY).0,
+            time_offset: 0,
+            next_block_timestamp: None,
+            block_callbacks: BlockCallbacks::default(),
         })
     }
 
@@ -55,60 +246,562 @@ impl BlockChain {
         Ok(block.to_owned())
     }
 
+    /// 根据区块哈希获取区块
+    pub(crate) fn get_block_by_hash(&self, block_hash: H256) -> Result<Block> {
+        let block = self
+            .blocks
+            .iter()
+            .find(|block| block.hash == Some(block_hash))
+            .ok_or_else(|| ChainError::BlockNotFound(block_hash.to_string()))?;
+
+        Ok(block.to_owned())
+    }
+
+    /// 根据区块号和交易在区块中的位置获取交易
+    ///
+    /// 区块不存在时返回错误，索引超出区块交易数量时返回 `None`
+    pub(crate) fn transaction_by_block_and_index(
+        &self,
+        block_number: U64,
+        index: usize,
+    ) -> Result<Option<Transaction>> {
+        let block = self.get_block_body(block_number)?;
+
+        Ok(block.transactions.get(index).cloned())
+    }
+
+    /// 根据区块号获取包含完整区块体的区块
+    ///
+    /// 与`get_block_by_number`不同，区块体已被裁剪时返回`BlockPruned`，
+    /// 而不是返回交易列表为空的区块头
+    pub(crate) fn get_block_body(&self, block_number: U64) -> Result<Block> {
+        if self.is_pruned(block_number)? {
+            return Err(ChainError::BlockPruned(block_number.to_string()));
+        }
+
+        self.get_block_by_number(block_number)
+    }
+
     pub(crate) fn new_block(
         &mut self,
         transactions: Vec<Transaction>,
         state_trie: H256,
+        logs_bloom: Bloom,
     ) -> Result<Block> {
         let current_block = self.get_current_block()?;
-        let number = current_block.number + 1_u64;
+        let number = current_block
+            .number
+            .checked_add(U64::one())
+            .ok_or_else(|| ChainError::BlockNumberOverflow(current_block.number.to_string()))?;
         let parent_hash = current_block.block_hash()?;
-        let block = Block::new(number, parent_hash, transactions, state_trie)?;
+
+        // 根据父区块到当前的出块间隔调整难度
+        let timestamp = self.pending_block_timestamp()?;
+        if self.next_block_timestamp.take().is_some() {
+            // 之后的区块从指定的时间戳继续计时
+            self.time_offset = timestamp.saturating_sub(now_millis()?);
+        }
+        let difficulty = Block::next_difficulty(
+            current_block.difficulty,
+            current_block.timestamp,
+            timestamp,
+            self.config.block_time,
+        );
+        let block = Block::new_with_difficulty(
+            number,
+            parent_hash,
+            transactions,
+            state_trie,
+            logs_bloom,
+            timestamp,
+            difficulty,
+        )?;
 
         // 持久化存储到数据库中
-        STORAGE.insert(block.hash.as_slice(), block.into());
+        self.storage
+            .insert(block.block_hash()?.as_bytes(), serialize(&block)?)?;
         self.blocks.push(block);
+        self.prune_blocks()?;
 
         self.get_block_by_number(number)
     }
 
+    /// 下一个区块的时间戳，单位为毫秒
+    ///
+    /// 优先使用`evm_setNextBlockTimestamp`指定的时间戳，否则为当前系统时间加上时间偏移
+    pub(crate) fn pending_block_timestamp(&self) -> Result<u64> {
+        match self.next_block_timestamp {
+            Some(timestamp) => Ok(timestamp),
+            None => Ok(now_millis()?.saturating_add(self.time_offset)),
+        }
+    }
+
+    /// 指定下一个区块的时间戳，单位为秒，必须晚于当前区块的时间戳
+    pub(crate) fn set_next_block_timestamp(&mut self, timestamp: u64) -> Result<()> {
+        let timestamp = timestamp.saturating_mul(1000);
+        let current_timestamp = self.get_current_block()?.timestamp;
+
+        if timestamp <= current_timestamp {
+            return Err(ChainError::InvalidBlockTimestamp(
+                (timestamp / 1000).to_string(),
+                (current_timestamp / 1000).to_string(),
+            ));
+        }
+
+        self.next_block_timestamp = Some(timestamp);
+
+        Ok(())
+    }
+
+    /// 将之后区块的时间戳向后推移`seconds`秒，返回累计推移的秒数
+    pub(crate) fn increase_time(&mut self, seconds: u64) -> u64 {
+        self.time_offset = self
+            .time_offset
+            .saturating_add(seconds.saturating_mul(1000));
+
+        self.time_offset / 1000
+    }
+
+    /// 判断区块的区块体是否已经被裁剪
+    ///
+    /// 配置了裁剪深度时，区块号小于`当前区块号 - prune_depth`的区块只保留区块头
+    pub(crate) fn is_pruned(&self, block_number: U64) -> Result<bool> {
+        let Some(prune_depth) = self.config.prune_depth else {
+            return Ok(false);
+        };
+        let current_block_number = self.get_current_block()?.number.as_u64();
+
+        Ok(block_number.as_u64().saturating_add(prune_depth) < current_block_number)
+    }
+
+    /// 清空已经超出裁剪深度的区块的交易列表，保留区块头和各个根哈希，并更新存储中的区块
+    fn prune_blocks(&mut self) -> Result<()> {
+        let Some(prune_depth) = self.config.prune_depth else {
+            return Ok(());
+        };
+        let current_block_number = self.get_current_block()?.number.as_u64();
+        let Some(pruned_before) = current_block_number.checked_sub(prune_depth) else {
+            return Ok(());
+        };
+
+        for block in self.blocks.iter_mut().take(pruned_before as usize) {
+            if block.transactions.is_empty() {
+                continue;
+            }
+
+            tracing::info!("Pruning the body of block {}", block.number);
+
+            block.transactions.clear();
+            self.storage
+                .insert(block.block_hash()?.as_bytes(), serialize(block)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// 构造合约执行时的环境
+    ///
+    /// 交易会被打包进下一个区块，因此区块号为当前区块号加一，时间戳取下一个区块的时间戳，单位为秒
+    ///
+    /// # 参数
+    ///
+    /// * `caller`: 发起调用的账户地址
+    /// * `address`: 被调用的合约地址
+    /// * `value`: 随调用转入合约的金额，超出u64范围时返回错误
+    pub(crate) fn environment(
+        &self,
+        caller: &Account,
+        address: &Account,
+        value: U256,
+    ) -> Result<Environment> {
+        let current_block_number = self.get_current_block()?.number.as_u64();
+        let block_number = current_block_number
+            .checked_add(1)
+            .ok_or_else(|| ChainError::BlockNumberOverflow(current_block_number.to_string()))?;
+        let block_timestamp = self.pending_block_timestamp()? / 1000;
+
+        Self::environment_at(block_number, block_timestamp, caller, address, value)
+    }
+
+    /// 构造合约在指定区块中执行时的环境，例如重新执行已打包的交易
+    ///
+    /// # 参数
+    ///
+    /// * `block_number`: 合约执行时所在区块的区块号
+    /// * `block_timestamp`: 合约执行时所在区块的时间戳，单位为秒
+    /// * `caller`: 发起调用的账户地址
+    /// * `address`: 被调用的合约地址
+    /// * `value`: 随调用转入合约的金额，超出u64范围时返回错误
+    pub(crate) fn environment_at(
+        block_number: u64,
+        block_timestamp: u64,
+        caller: &Account,
+        address: &Account,
+        value: U256,
+    ) -> Result<Environment> {
+        Ok(Environment {
+            block_number,
+            block_timestamp,
+            caller: format!("{:?}", caller),
+            address: format!("{:?}", address),
+            value: u64::try_from(value)
+                .map_err(|_| ChainError::ValueTooLarge(value.to_string()))?,
+        })
+    }
+
+    /// 复制出一条只用于执行交易的链，例如模拟执行交易
+    ///
+    /// 副本包含当前账户状态（包括未提交的修改）、当前区块和出块时间的设置，
+    /// 不包含历史区块、交易记录和回调，在副本上执行交易不会影响原有的链
+    fn fork(&self) -> Result<Self> {
+        Ok(Self {
+            storage: Arc::clone(&self.storage),
+            accounts: self.accounts.fork()?,
+            blocks: vec![self.get_current_block()?],
+            transactions: Arc::clone(&self.transactions),
+            world_state: WorldState::new(),
+            allowed_senders: self.allowed_senders.clone(),
+            config: self.config.clone(),
+            deployed_contracts: HashMap::new(),
+            transaction_history: HashMap::new(),
+            unlocked_accounts: HashMap::new(),
+            mined_transactions: broadcast::channel(MINED_TRANSACTIONS_CAPACITY).0,
+            new_heads: broadcast::channel(NEW_HEADS_CAPACITY).0,
+            time_offset: self.time_offset,
+            next_block_timestamp: self.next_block_timestamp,
+            block_callbacks: BlockCallbacks::default(),
+        })
+    }
+
+    /// 升级一个合约，用新的代码替换合约账户中的代码
+    ///
+    /// 只有合约的部署者可以升级合约，合约的存储和余额会被保留
+    pub(crate) fn upgrade_contract(
+        &mut self,
+        deployer: &Account,
+        contract: &Account,
+        code: Bytes,
+    ) -> Result<()> {
+        if !self.contracts_deployed_by(deployer).contains(contract) {
+            return Err(ChainError::NotContractDeployer(
+                deployer.to_string(),
+                contract.to_string(),
+            ));
+        }
+
+        self.accounts.set_code(contract, code)
+    }
+
+    /// 获取可以代表账户签名的私钥
+    ///
+    /// 已解锁账户使用其私钥，节点自己的账户使用节点密钥，其余账户返回`SignerNotFound`
+    pub(crate) fn signing_key(&self, account: &Account) -> Result<SecretKey> {
+        if let Some(key) = self.unlocked_accounts.get(account) {
+            return Ok(*key);
+        }
+
+        if *account == *ADDRESS {
+            return Ok(*PRIVATE_KEY);
+        }
+
+        Err(ChainError::SignerNotFound(account.to_string()))
+    }
+
+    /// 按EIP-712对结构化数据签名，签名者必须是节点可以代为签名的账户
+    ///
+    /// 返回65字节的签名`r || s || v`，其中`v`为27或28
+    pub(crate) fn sign_typed_data(&self, account: &Account, typed_data: &Value) -> Result<H520> {
+        let field = |name: &str| {
+            typed_data
+                .get(name)
+                .ok_or_else(|| ChainError::InvalidTypedData(format!("missing {}", name)))
+        };
+        let key = self.signing_key(account)?;
+        let signature = sign_typed_data(field("domain")?, field("types")?, field("message")?, &key)
+            .map_err(|e| ChainError::InvalidTypedData(e.to_string()))?;
+
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(signature.r.as_bytes());
+        bytes[32..64].copy_from_slice(signature.s.as_bytes());
+        bytes[64] = signature.v as u8 + 27;
+
+        Ok(H520::from(bytes))
+    }
+
+    /// 生成开发模式的测试账户
+    ///
+    /// 每个账户都有较大的初始余额，并被注册为已解锁账户
+    ///
+    /// 参数:
+    /// - `count`: 生成的账户数量
+    ///
+    /// 返回值:
+    /// 生成的账户地址及其私钥
+    pub(crate) fn add_dev_accounts(&mut self, count: usize) -> Result<Vec<(Account, SecretKey)>> {
+        let mut dev_accounts = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let (secret_key, public_key) = keypair();
+            let account = public_key_address(&public_key);
+            let mut account_data = AccountData::new(None);
+            account_data.balance = U256::from(DEV_ACCOUNT_BALANCE);
+
+            self.accounts.add_account(&account, &account_data)?;
+            self.unlocked_accounts.insert(account, secret_key);
+            dev_accounts.push((account, secret_key));
+        }
+
+        Ok(dev_accounts)
+    }
+
+    /// 注册新区块打包完成后调用的回调
+    ///
+    /// 每打包一个区块并写入其收据后，回调会按注册顺序以该区块为参数被调用一次
+    #[allow(dead_code)]
+    pub(crate) fn add_block_callbacks(&mut self, callbacks: Vec<BlockCallback>) {
+        self.block_callbacks.0.extend(callbacks);
+    }
+
+    /// 获取一个账户部署的所有合约地址，按部署顺序排列
+    pub(crate) fn contracts_deployed_by(&self, deployer: &Account) -> Vec<Account> {
+        self.deployed_contracts
+            .get(deployer)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 获取一个账户作为发送方或接收方参与的所有交易哈希，按打包顺序排列
+    pub(crate) fn transactions_for(&self, address: &Account) -> Vec<H256> {
+        self.transaction_history
+            .get(address)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 判断节点是否健康，目前只要存在当前区块即认为健康，之后可以加入同步状态的判断
+    pub(crate) fn is_healthy(&self) -> bool {
+        !self.blocks.is_empty()
+    }
+
+    /// 获取节点的健康状态
+    pub(crate) fn health(&self) -> Result<Health> {
+        Ok(Health {
+            healthy: self.is_healthy(),
+            block_number: self.get_current_block()?.number,
+            peers: 0,
+        })
+    }
+
+    /// 判断账户是否允许发送交易，未配置允许列表时所有账户都被允许
+    pub(crate) fn is_sender_allowed(&self, sender: &Account) -> bool {
+        self.allowed_senders
+            .as_ref()
+            .map_or(true, |allowed_senders| allowed_senders.contains(sender))
+    }
+
+    /// 将交易放入交易池
+    ///
+    /// 已签名的交易在进入交易池之前验证签名，签名无效或缺少nonce的交易直接被拒绝，不会占用区块的位置，
+    /// 只有交易请求会由节点自动分配nonce
+    ///
+    /// # 参数
+    ///
+    /// * `pending_transaction`: 交易请求或已签名的交易
     pub(crate) async fn send_transaction(
         &mut self,
-        transaction_request: TransactionRequest,
+        pending_transaction: PendingTransaction,
     ) -> Result<H256> {
-        let mut transaction: Transaction = transaction_request.try_into()?;
+        let (mut transaction, signed) = match pending_transaction {
+            PendingTransaction::Request(transaction_request) => {
+                (transaction_request.try_into()?, false)
+            }
+            PendingTransaction::Signed(signed_transaction) => {
+                let transaction: Transaction = signed_transaction.clone().into();
+
+                if !Transaction::verify(signed_transaction, transaction.from)? {
+                    return Err(ChainError::TransactionNotVerified(
+                        transaction.transaction_hash()?.to_string(),
+                    ));
+                }
+
+                // nonce在签名的范围内，节点无法替已签名的交易分配nonce，
+                // 缺少nonce的已签名交易可以被重复提交，因此直接拒绝
+                if transaction.nonce.is_none() {
+                    return Err(ChainError::MissingTransactionNonce(
+                        transaction.transaction_hash()?.to_string(),
+                    ));
+                }
+
+                // 按EIP-155签名的交易只能在签名时绑定的链上执行
+                if let Some(chain_id) = transaction.chain_id {
+                    if chain_id != self.config.chain_id {
+                        return Err(ChainError::ChainIdMismatch(
+                            chain_id.to_string(),
+                            self.config.chain_id.to_string(),
+                        ));
+                    }
+                }
+
+                (transaction, true)
+            }
+        };
+
+        if !self.is_sender_allowed(&transaction.from) {
+            return Err(ChainError::SenderNotAllowed(transaction.from.to_string()));
+        }
+
+        // 拒绝低于最低gas价格的交易，避免零手续费的交易占满交易池
+        let min_gas_price = U256::from(self.config.min_gas_price);
+        if transaction.gas_price < min_gas_price {
+            return Err(ChainError::GasPriceTooLow(
+                transaction.gas_price.to_string(),
+                min_gas_price.to_string(),
+            ));
+        }
+
+        check_intrinsic_gas(&transaction)?;
+
+        // 超过区块gas上限的交易无法被打包进任何区块
+        let gas_limit = U256::from(self.config.gas_limit);
+        if transaction.gas_used() > gas_limit {
+            return Err(ChainError::BlockGasLimitExceeded(
+                transaction.gas_used().to_string(),
+                gas_limit.to_string(),
+            ));
+        }
+
         let account = self.accounts.get_account(&transaction.from)?;
-        let nonce = transaction.nonce.unwrap_or_else(|| account.nonce + 1_u64);
+        let mut transaction_storage = self.transactions.lock().await;
 
-        transaction.nonce = Some(nonce);
+        // 未指定nonce时，基于已打包的nonce和交易池中的最高nonce中较大者自动分配，
+        // 避免连续提交的交易得到相同的nonce
+        let nonce = match transaction.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let pending_nonce = transaction_storage
+                    .highest_pending_nonce(&transaction.from)
+                    .unwrap_or_default();
 
-        let transaction_hash = transaction.hash()?;
+                account
+                    .nonce
+                    .max(pending_nonce)
+                    .checked_add(U256::one())
+                    .ok_or_else(|| ChainError::NonceOverflow(transaction.from.to_string()))?
+            }
+        };
+
+        // 已签名的交易保持签名和交易哈希不变，
+        // 交易请求在确定nonce后重新计算哈希，由节点为已解锁的账户签名
+        if !signed {
+            transaction.nonce = Some(nonce);
+            transaction.signature = None;
+            transaction.hash()?;
+
+            // 已解锁账户的交易由节点使用其私钥签名
+            if let Some(key) = self.unlocked_accounts.get(&transaction.from) {
+                transaction = transaction.sign(*key)?.into();
+            }
+        }
+
+        let transaction_hash = transaction.transaction_hash()?;
 
-        self.transactions.lock().await.send_transaction(transaction);
+        transaction_storage.send_transaction(transaction);
 
         Ok(transaction_hash)
     }
 
+    /// 处理交易池中当前的所有交易
+    ///
+    /// 交易按照配置的区块最大交易数分批处理，每批打包成一个区块并立即写入收据
+    #[allow(dead_code)]
     pub(crate) async fn process_transactions(&mut self) -> Result<()> {
+        let chunk_size = self.config.max_block_transactions.max(1);
+        let mut remaining = self.transactions.lock().await.mempool.len();
+
+        if remaining == 0 && self.config.produce_empty_blocks {
+            self.produce_empty_block()?;
+        }
+
+        while remaining > 0 {
+            let taken = self
+                .process_transaction_chunk(remaining.min(chunk_size))
+                .await?;
+
+            if taken == 0 {
+                break;
+            }
+
+            remaining -= taken;
+        }
+
+        Ok(())
+    }
+
+    /// 打包一个不包含交易的区块，使区块高度在没有交易时也能持续增长
+    pub(crate) fn produce_empty_block(&mut self) -> Result<Block> {
+        let state_trie = self.accounts.root_hash()?;
+        let block = self.new_block(vec![], state_trie, Bloom::zero())?;
+
+        tracing::info!("Created empty block {}", block.number);
+
+        self.block_callbacks.notify(&block);
+        let _ = self.new_heads.send(block.clone());
+
+        Ok(block)
+    }
+
+    /// 从交易池中取出最多`chunk_size`笔交易处理，并打包成一个区块
+    ///
+    /// 取出的交易的gas消耗之和不超过配置的区块gas上限，放不下的交易留到下一个区块。
+    /// 收据在区块创建后立即写入，因此在分批处理时读取方可以逐步看到已经打包的交易，
+    /// 返回从交易池中取出的交易数量
+    pub(crate) async fn process_transaction_chunk(&mut self, chunk_size: usize) -> Result<usize> {
+        let gas_limit = U256::from(self.config.gas_limit);
         let transactions = self
             .transactions
             .lock()
             .await
-            .mempool
-            .drain(0..)
-            .collect::<VecDeque<_>>();
+            .take_transactions(chunk_size, gas_limit);
+        let num_transactions = transactions.len();
 
         if !transactions.is_empty() {
             let mut receipts: Vec<TransactionReceipt> = vec![];
             let mut processed: Vec<Transaction> = vec![];
+            let mut cumulative_gas_used = U256::zero();
 
             tracing::info!("Processing {} transactions", transactions.len());
 
             for mut transaction in transactions.into_iter() {
-                match self.process_transaction(&mut transaction) {
-                    Ok((transaction, transaction_receipt)) => {
+                // 单笔交易处理过程中的panic只会丢弃这笔交易，不会中断其余交易的处理，
+                // panic之前已经扣除的余额和gas费用回滚到处理这笔交易之前的状态
+                let checkpoint = self.accounts.checkpoint()?;
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    self.process_transaction(&mut transaction)
+                        .map(|(_, transaction_receipt)| transaction_receipt)
+                }));
+                let result = match result {
+                    Ok(result) => result,
+                    Err(payload) => {
+                        self.accounts.revert(checkpoint)?;
+                        Err(ChainError::InternalError(panic_message(&*payload)))
+                    }
+                };
+
+                match result {
+                    Ok(mut transaction_receipt) => {
+                        // 只在交易被打包时记录部署者和合约地址的对应关系，
+                        // 模拟执行、估算gas和计算待打包状态都不会留下部署记录
+                        if let Some(contract_address) = transaction_receipt.contract_address {
+                            self.deployed_contracts
+                                .entry(transaction.from)
+                                .or_default()
+                                .push(contract_address);
+                        }
+
+                        cumulative_gas_used += transaction.gas_used();
+                        transaction_receipt.cumulative_gas_used = cumulative_gas_used;
                         receipts.push(transaction_receipt);
-                        processed.push(transaction.to_owned());
+                        processed.push(transaction);
                     }
                     Err(error) => match error {
                         ChainError::NonceTooHigh(_, _) => {
@@ -117,11 +810,7 @@ impl BlockChain {
                                 transaction,
                                 error
                             );
-                            self.transactions
-                                .lock()
-                                .await
-                                .mempool
-                                .push_back(transaction);
+                            self.transactions.lock().await.send_transaction(transaction);
                         }
                         _ => tracing::error!(
                             "Could not process transaction {:?}: {}",
@@ -137,8 +826,23 @@ impl BlockChain {
 
             tracing::info!("World State: state_trie {:?}", state_trie);
 
+            // 记录每笔交易的发送方和接收方参与的交易
+            for transaction in processed.iter() {
+                let transaction_hash = transaction.transaction_hash()?;
+                let mut participants = vec![transaction.from];
+                participants.extend(transaction.to.filter(|to| *to != transaction.from));
+
+                for participant in participants {
+                    self.transaction_history
+                        .entry(participant)
+                        .or_default()
+                        .push(transaction_hash);
+                }
+            }
+
             let num_processed = processed.len();
-            let block = self.new_block(processed, state_trie)?;
+            let logs_bloom = logs_bloom(receipts.iter().flat_map(|receipt| receipt.logs.iter()));
+            let block = self.new_block(processed, state_trie, logs_bloom)?;
 
             tracing::info!(
                 "Created block {} with {} transactions",
@@ -146,18 +850,23 @@ impl BlockChain {
                 num_processed
             );
 
-            for mut receipt in receipts.into_iter() {
-                receipt.block_number = Some(BlockNumber(block.number));
-                receipt.block_hash = block.hash;
+            assign_receipts_to_block(&block, &mut receipts);
 
+            for receipt in receipts.into_iter() {
+                let transaction_hash = receipt.transaction_hash;
                 self.transactions
                     .clone()
                     .lock()
                     .await
-                    .receipts
-                    .insert(receipt.transaction_hash, receipt);
+                    .insert_receipt(receipt)?;
+
+                // 没有订阅者时发送会失败，可以忽略
+                let _ = self.mined_transactions.send(transaction_hash);
             }
 
+            self.block_callbacks.notify(&block);
+            let _ = self.new_heads.send(block);
+
             let storage = self.transactions.lock().await;
 
             tracing::info!(
@@ -167,7 +876,55 @@ impl BlockChain {
             );
         }
 
-        Ok(())
+        Ok(num_transactions)
+    }
+
+    /// 按确定的顺序执行一组交易，并返回执行后的状态根
+    ///
+    /// 交易按`(发送方, nonce)`排序，相同时按gas价格从高到低、再按交易哈希排序，
+    /// 因此无论交易以何种顺序提交，相同的交易集合总会得到相同的状态根，
+    /// 执行失败的交易会被跳过。交易在当前状态的快照上执行，不会修改链上的账户状态
+    ///
+    /// 参数:
+    /// - `transactions`: 需要执行的交易
+    ///
+    /// 返回值:
+    /// - `Result<H256>`: 执行全部交易后账户状态树的根哈希
+    #[allow(dead_code)]
+    pub(crate) fn apply_transactions_deterministic(
+        &mut self,
+        mut transactions: Vec<Transaction>,
+    ) -> Result<H256> {
+        let mut keyed = transactions
+            .iter_mut()
+            .map(|transaction| Ok((transaction.transaction_hash()?, transaction)))
+            .collect::<Result<Vec<_>>>()?;
+
+        keyed.sort_by(|(hash_a, a), (hash_b, b)| {
+            (a.from, a.nonce)
+                .cmp(&(b.from, b.nonce))
+                .then_with(|| b.gas_price.cmp(&a.gas_price))
+                .then_with(|| hash_a.cmp(hash_b))
+        });
+
+        // 在快照上执行交易，计算出状态根后无论成功与否都换回原有的账户存储
+        let snapshot = self.accounts.snapshot()?;
+        let accounts = std::mem::replace(&mut self.accounts, snapshot);
+
+        for (transaction_hash, transaction) in keyed.into_iter() {
+            if let Err(error) = self.process_transaction(transaction) {
+                tracing::error!(
+                    "Could not apply transaction {:?}: {}",
+                    transaction_hash,
+                    error
+                );
+            }
+        }
+
+        let state_root = self.accounts.root_hash();
+        self.accounts = accounts;
+
+        state_root
     }
 
     /// 处理交易函数
@@ -184,6 +941,17 @@ impl BlockChain {
     pub(crate) fn process_transaction<'a>(
         &mut self,
         transaction: &'a mut Transaction,
+    ) -> Result<(&'a mut Transaction, TransactionReceipt)> {
+        self.execute_transaction(transaction, None, None)
+    }
+
+    /// 处理交易，`block`不为空时交易在该区块中重新执行，否则在下一个区块中执行；
+    /// `trace`不为空时以跟踪模式执行合约，并将记录的执行步骤追加到其中
+    fn execute_transaction<'a>(
+        &mut self,
+        transaction: &'a mut Transaction,
+        block: Option<&Block>,
+        trace: Option<&mut Vec<TraceStep>>,
     ) -> Result<(&'a mut Transaction, TransactionReceipt)> {
         // 初始化合约地址为None，因为在处理交易时可能不会创建合约
         let mut contract_address: Option<Account> = None;
@@ -195,8 +963,11 @@ impl BlockChain {
             // 记录交易处理信息
             tracing::info!("Processing Transaction {:?}", transaction_hash);
 
-            // 判断目标账户是否存在，如果不存在返回错误
-            if let Some(to) = transaction.to {
+            // gas上限不足以支付固有gas的交易不会被执行
+            check_intrinsic_gas(transaction)?;
+
+            // 调用合约时目标账户必须存在，普通转账的接收方不存在时在转账时创建
+            if let (Some(to), Some(_)) = (transaction.to, &transaction.data) {
                 if self.accounts.get_account(&to).is_err() {
                     return Err(ChainError::AccountNotFound(to.to_string()));
                 }
@@ -205,34 +976,50 @@ impl BlockChain {
             // 获取交易类型
             let kind = transaction.to_owned().kind()?;
 
+            // 在修改任何状态之前检查nonce和余额，无效的交易不会留下部分执行的状态
+            self.accounts.check_nonce(&transaction.from, nonce)?;
+            let gas_cost = self.check_balance(transaction)?;
+
+            // 预先从发送方扣除gas上限对应的全部费用，执行后再退还未使用的部分
+            self.accounts
+                .subtract_account_balance(&transaction.from, gas_cost)?;
+
+            #[cfg(test)]
+            if PANICKING_TRANSACTION.with(|hash| hash.get() == Some(transaction_hash)) {
+                panic!("injected panic processing {:?}", transaction_hash);
+            }
+
             // 根据交易类型处理交易
-            match kind {
-                // 处理常规转账交易
-                TransactionKind::Regular(from, to, value) => {
-                    self.accounts.transfer(&from, &to, value)
-                }
-                // 处理合约部署交易
-                TransactionKind::ContractDeployment(from, data) => {
-                    // 部署合约，并尝试获取合约地址
-                    contract_address = self.accounts.add_contract_account(&from, data).ok();
-                    Ok(())
-                }
-                // 处理合约执行交易
-                TransactionKind::ContractExecution(_from, to, data) => {
-                    // 获取合约账户的代码哈希
-                    let code = self
-                        .accounts
-                        .get_account(&to)?
-                        .code_hash
-                        .ok_or_else(|| ChainError::NotAContractAccount(to.to_string()))?;
-                    // 反序列化合约数据以获取函数和参数
-                    let (function, params): (&str, Vec<&str>) = bincode::deserialize(&data)?;
-
-                    // 调用合约函数
-                    runtime::contract::call_function(&code, function, &params)
-                        .map_err(|e| ChainError::RuntimeError(to.to_string(), e.to_string()))
+            let mut logs = vec![];
+            let result = self.execute_kind(
+                transaction,
+                kind,
+                &mut contract_address,
+                &mut logs,
+                block,
+                trace,
+            );
+
+            // 合约执行回滚时交易仍然会被打包并消耗nonce，但不会产生任何状态变化；
+            // 其他错误会丢弃这笔交易，预先扣除的费用全部退还
+            let revert_reason = match result {
+                Ok(()) => None,
+                Err(ChainError::ExecutionReverted(reason)) => Some(reason),
+                Err(error) => {
+                    self.accounts
+                        .add_account_balance(&transaction.from, gas_cost)?;
+                    return Err(error);
                 }
-            }?;
+            };
+
+            // 退还未使用的gas，实际消耗的费用支付给coinbase
+            let gas_used = transaction.gas_used();
+            let gas_price = self.effective_gas_price(transaction);
+            let refund = (transaction.gas - gas_used).saturating_mul(gas_price);
+            self.accounts
+                .add_account_balance(&transaction.from, refund)?;
+            self.accounts
+                .add_account_balance(&self.config.coinbase, gas_cost - refund)?;
 
             // 更新账户的nonce值
             self.accounts.update_nonce(&transaction.from, nonce)?;
@@ -243,6 +1030,12 @@ impl BlockChain {
                 block_number: None,
                 contract_address,
                 transaction_hash,
+                logs,
+                status: U64::from(u64::from(revert_reason.is_none())),
+                // 单独处理时只包含这笔交易，打包进区块时再按区块中的顺序累加
+                cumulative_gas_used: gas_used,
+                effective_gas_price: gas_price,
+                revert_reason,
             };
 
             // 返回处理后的交易和交易收据
@@ -255,31 +1048,537 @@ impl BlockChain {
         ))
     }
 
-    pub(crate) async fn get_transaction_receipt(
-        &mut self,
-        transaction_hash: H256,
-    ) -> Result<TransactionReceipt> {
-        let transaction_receipt = self
-            .transactions
-            .lock()
-            .await
-            .get_transaction_receipt(&transaction_hash)?;
-
-        Ok(transaction_receipt)
+    /// 交易在当前区块的基础费用下实际支付的gas价格
+    fn effective_gas_price(&self, transaction: &Transaction) -> U256 {
+        transaction.effective_gas_price(U256::from(self.config.base_fee_per_gas))
     }
-}
 
-#[cfg(test)]
-pub(crate) mod tests {
-    use ethereum_types::U256;
-    use types::account::AccountData;
+    /// 检查发送方的余额足以同时支付gas上限对应的费用和转账金额，返回需要预先扣除的gas费用
+    ///
+    /// gas费用按交易实际支付的gas价格计算，余额不足时返回`InsufficientFunds`
+    fn check_balance(&self, transaction: &Transaction) -> Result<U256> {
+        let balance = self.accounts.get_account(&transaction.from)?.balance;
+        let gas_cost = transaction
+            .gas
+            .saturating_mul(self.effective_gas_price(transaction));
+        let total_cost = gas_cost.saturating_add(transaction.value);
 
-    use super::*;
-    use crate::helpers::tests::{setup, ACCOUNT_1, STORAGE};
+        if balance < total_cost {
+            return Err(ChainError::InsufficientFunds(
+                transaction.from.to_string(),
+                total_cost.to_string(),
+            ));
+        }
 
-    /// 创建一个新的区块链实例
+        Ok(gas_cost)
+    }
+
+    /// 根据交易类型执行交易，部署合约时将新合约的地址写入`contract_address`，
+    /// 合约执行成功时将合约发出的日志写入`logs`
+    fn execute_kind(
+        &mut self,
+        transaction: &Transaction,
+        kind: TransactionKind,
+        contract_address: &mut Option<Account>,
+        logs: &mut Vec<Log>,
+        block: Option<&Block>,
+        trace: Option<&mut Vec<TraceStep>>,
+    ) -> Result<()> {
+        match kind {
+            // 处理常规转账交易
+            TransactionKind::Regular(from, to, value) => self.accounts.transfer(&from, &to, value),
+            // 处理合约部署交易
+            TransactionKind::ContractDeployment(from, data) => {
+                // 拒绝导入了宿主不支持的函数的合约，无法解析接口的字节码仍然按原样部署
+                if let Err(RuntimeError::UnsupportedImports(imports)) =
+                    runtime::contract::check_imports(&data)
+                {
+                    return Err(ChainError::UnsupportedContractImports(imports));
+                }
+
+                // 部署合约，并尝试获取合约地址
+                *contract_address = self.accounts.add_contract_account(&from, data).ok();
+                Ok(())
+            }
+            // 处理合约执行交易
+            TransactionKind::ContractExecution(from, to, data) => {
+                // 获取合约账户的代码哈希
+                let code = self
+                    .accounts
+                    .get_account(&to)?
+                    .code_hash
+                    .ok_or_else(|| ChainError::NotAContractAccount(to.to_string()))?;
+                // 解码合约数据以获取函数和参数
+                let (function, params) = TransactionKind::decode_call_data(&data)?;
+                let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+
+                // 重新执行已打包的交易时，合约读取到的是交易所在区块的区块号和时间戳
+                let env = match block {
+                    Some(block) => Self::environment_at(
+                        block.number.as_u64(),
+                        block.timestamp / 1000,
+                        &from,
+                        &to,
+                        transaction.value,
+                    )?,
+                    None => self.environment(&from, &to, transaction.value)?,
+                };
+
+                // 在账户状态的快照上调用合约函数，合约可以通过宿主调用其他合约，
+                // 调用前先将交易金额转入合约账户，执行失败时一并回滚
+                let mut host = ContractHost::new(self.accounts.snapshot()?, to);
+                host.accounts.transfer(&from, &to, transaction.value)?;
+                let mut context = Context::new(host)
+                    .with_call_depth(CallDepth::new(self.config.max_call_depth))
+                    .with_limits(self.config.resource_limits())
+                    .with_environment(env);
+                if trace.is_some() {
+                    context = context.with_tracing();
+                }
+                let (_, context) =
+                    runtime::contract::call_function_in_context(&code, &function, &params, context)
+                        .map_err(|e| match e {
+                            RuntimeError::Revert { reason } => {
+                                ChainError::ExecutionReverted(reason)
+                            }
+                            e => ChainError::RuntimeError(to.to_string(), e.to_string()),
+                        })?;
+
+                // 合约执行成功后，采用执行过程中产生的账户状态和日志
+                self.accounts.merge(context.host.accounts)?;
+                *logs = context.host.logs;
+                if let (Some(trace), Some(steps)) = (trace, context.trace) {
+                    trace.extend(steps);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 跟踪一笔已打包交易的执行过程
+    ///
+    /// 在父区块的状态上依次重新执行区块中排在该交易之前的交易，再以跟踪模式执行该交易，
+    /// 合约读取到的区块号和时间戳与交易打包时一致，
+    /// 执行完成后恢复原有的账户存储，因此不会修改任何状态。
+    /// 交易所在区块的区块体已被裁剪时无法重新执行，返回`BlockPruned`
+    ///
+    /// # 参数
+    ///
+    /// * `transaction_hash`: 需要跟踪的交易哈希
+    pub(crate) async fn trace_transaction(
+        &mut self,
+        transaction_hash: H256,
+    ) -> Result<TransactionTrace> {
+        // 通过收据找到交易所在的区块，区块体被裁剪后仍然可以据此给出明确的错误
+        let block_number = self
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash)?
+            .block_number
+            .ok_or_else(|| ChainError::TransactionNotFound(transaction_hash.to_string()))?;
+        let block = self.get_block_body(*block_number)?;
+        let parent = self.get_block_by_number(block.number - 1)?;
+
+        // 在父区块的状态上重新执行，结束后无论成功与否都换回原有的账户存储；
+        // 提交状态时不会删除被替换的trie节点，因此较早区块的状态根仍然可以打开
+        let parent_accounts = self.accounts.at_root(parent.state_root)?;
+        let accounts = std::mem::replace(&mut self.accounts, parent_accounts);
+        let result = self.replay_and_trace(&block, transaction_hash);
+        self.accounts = accounts;
+
+        result
+    }
+
+    /// 在区块中依次执行区块的交易，直到以跟踪模式执行完指定的交易
+    fn replay_and_trace(
+        &mut self,
+        block: &Block,
+        transaction_hash: H256,
+    ) -> Result<TransactionTrace> {
+        for mut transaction in block.transactions.iter().cloned() {
+            if transaction.hash != Some(transaction_hash) {
+                self.execute_transaction(&mut transaction, Some(block), None)?;
+                continue;
+            }
+
+            let mut steps = vec![];
+            let (_, receipt) =
+                self.execute_transaction(&mut transaction, Some(block), Some(&mut steps))?;
+
+            return Ok(TransactionTrace {
+                transaction_hash,
+                steps,
+                revert_reason: receipt.revert_reason,
+            });
+        }
+
+        Err(ChainError::TransactionNotFound(
+            transaction_hash.to_string(),
+        ))
+    }
+
+    /// 模拟执行一笔交易
+    ///
+    /// 在复制出的链上执行交易，返回执行结果、gas消耗、合约发出的日志以及涉及账户的状态变化，
+    /// 当前的账户存储不受影响，因此不会持久化任何状态
+    ///
+    /// 参数:
+    /// - `transaction_request`: 需要模拟执行的交易请求
+    ///
+    /// 返回值:
+    /// - `Result<SimulationResult>`: 模拟执行的结果，交易本身执行失败时 `success` 为 false
+    pub(crate) fn simulate(
+        &self,
+        transaction_request: TransactionRequest,
+    ) -> Result<SimulationResult> {
+        let mut transaction: Transaction = transaction_request.try_into()?;
+
+        if !self.is_sender_allowed(&transaction.from) {
+            return Err(ChainError::SenderNotAllowed(transaction.from.to_string()));
+        }
+
+        let nonce = match transaction.nonce {
+            Some(nonce) => nonce,
+            None => self
+                .accounts
+                .get_account(&transaction.from)?
+                .nonce
+                .checked_add(U256::one())
+                .ok_or_else(|| ChainError::NonceOverflow(transaction.from.to_string()))?,
+        };
+
+        transaction.nonce = Some(nonce);
+        transaction.hash()?;
+
+        // 在复制出的链上执行交易，无论成功与否都不会修改当前的账户存储
+        let mut fork = self.fork()?;
+        let processed = fork
+            .process_transaction(&mut transaction)
+            .map(|(_, receipt)| receipt);
+        let simulated = fork.accounts;
+
+        let mut touched: Vec<Account> = vec![transaction.from];
+        touched.extend(transaction.to);
+
+        if let Ok(TransactionReceipt {
+            contract_address: Some(contract_address),
+            ..
+        }) = processed
+        {
+            touched.push(contract_address);
+        }
+
+        let state_diff = touched
+            .iter()
+            .map(|address| {
+                let before = self
+                    .accounts
+                    .get_account(address)
+                    .unwrap_or_else(|_| AccountData::empty());
+                let after = simulated
+                    .get_account(address)
+                    .unwrap_or_else(|_| AccountData::empty());
+
+                AccountDiff {
+                    address: *address,
+                    balance_before: before.balance,
+                    balance_after: after.balance,
+                    nonce_before: before.nonce,
+                    nonce_after: after.nonce,
+                }
+            })
+            .collect();
+
+        // 执行的交易使用收据中实际消耗的gas，未能执行的交易使用交易本身的gas消耗
+        let simulation_result = match processed {
+            Ok(TransactionReceipt {
+                revert_reason: Some(reason),
+                cumulative_gas_used,
+                ..
+            }) => SimulationResult {
+                success: false,
+                gas_used: cumulative_gas_used,
+                error: Some(reason),
+                logs: vec![],
+                state_diff,
+                contract_address: None,
+            },
+            Ok(receipt) => SimulationResult {
+                success: true,
+                gas_used: receipt.cumulative_gas_used,
+                error: None,
+                logs: receipt.logs,
+                state_diff,
+                contract_address: receipt.contract_address,
+            },
+            Err(error) => SimulationResult {
+                success: false,
+                gas_used: transaction.gas_used(),
+                error: Some(error.to_string()),
+                logs: vec![],
+                state_diff,
+                contract_address: None,
+            },
+        };
+
+        Ok(simulation_result)
+    }
+
+    /// 获取待打包交易生效后的账户状态
+    ///
+    /// 基于当前账户状态的快照依次执行交易池中的交易，返回执行后的快照，
+    /// 无法执行的交易会被跳过，当前的账户存储不受影响
+    pub(crate) async fn pending_accounts(&mut self) -> Result<AccountStorage> {
+        let mut transactions = self
+            .transactions
+            .lock()
+            .await
+            .mempool
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let snapshot = self.accounts.snapshot()?;
+        let accounts = std::mem::replace(&mut self.accounts, snapshot);
+
+        for transaction in transactions.iter_mut() {
+            if let Err(error) = self.process_transaction(transaction) {
+                tracing::warn!("Skipping pending transaction {:?}: {}", transaction, error);
+            }
+        }
+
+        Ok(std::mem::replace(&mut self.accounts, accounts))
+    }
+
+    /// 获取账户在指定区块标签下的余额
+    pub(crate) async fn get_balance(
+        &mut self,
+        account: &Account,
+        block_tag: BlockTag,
+    ) -> Result<U256> {
+        let balance = match block_tag {
+            BlockTag::Latest => self.accounts.get_account(account)?.balance,
+            BlockTag::Pending => self.pending_accounts().await?.get_account(account)?.balance,
+        };
+
+        Ok(balance)
+    }
+
+    /// 获取账户在指定区块标签下的交易数量，即账户的nonce
+    ///
+    /// pending标签会计入交易池中该账户nonce最高的交易，与自动分配nonce时使用的值一致
+    pub(crate) async fn get_transaction_count(
+        &self,
+        account: &Account,
+        block_tag: BlockTag,
+    ) -> Result<U256> {
+        let nonce = self.accounts.get_account(account)?.nonce;
+        let count = match block_tag {
+            BlockTag::Latest => nonce,
+            BlockTag::Pending => self
+                .transactions
+                .lock()
+                .await
+                .highest_pending_nonce(account)
+                .map_or(nonce, |pending_nonce| nonce.max(pending_nonce)),
+        };
+
+        Ok(count)
+    }
+
+    /// 估算执行一笔交易所需的gas
+    ///
+    /// 通过模拟执行交易得到gas消耗，交易执行失败时返回 `ChainError::ExecutionReverted`
+    pub(crate) fn estimate_gas(&self, transaction_request: TransactionRequest) -> Result<U256> {
+        let simulation_result = self.simulate(transaction_request)?;
+
+        if !simulation_result.success {
+            return Err(ChainError::ExecutionReverted(
+                simulation_result.error.unwrap_or_default(),
+            ));
+        }
+
+        Ok(simulation_result.gas_used)
+    }
+
+    /// 根据过滤条件获取日志
+    ///
+    /// 遍历过滤范围内的区块，从每笔交易的收据中收集满足条件的日志，
+    /// 布隆过滤器表明不可能包含匹配日志的区块会被直接跳过
+    pub(crate) async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>> {
+        let transaction_storage = self.transactions.lock().await;
+        let mut logs = vec![];
+
+        for block in self.blocks.iter().filter(|block| {
+            filter.matches_block(block.number) && filter.matches_bloom(&block.logs_bloom)
+        }) {
+            for transaction in block.transactions.iter() {
+                if let Ok(receipt) =
+                    transaction_storage.get_transaction_receipt(&transaction.transaction_hash()?)
+                {
+                    logs.extend(receipt.logs.into_iter().filter(|log| filter.matches(log)));
+                }
+            }
+        }
+
+        Ok(logs)
+    }
+
+    /// 获取区块中所有交易的收据，收据按交易在区块中的顺序排列
+    ///
+    /// 区块不存在或区块体已被裁剪时返回错误
+    pub(crate) async fn block_receipts(
+        &self,
+        block_number: U64,
+    ) -> Result<Vec<TransactionReceipt>> {
+        let block = self.get_block_body(block_number)?;
+        let transaction_storage = self.transactions.lock().await;
+
+        block
+            .transactions
+            .iter()
+            .map(|transaction| {
+                transaction_storage.get_transaction_receipt(&transaction.transaction_hash()?)
+            })
+            .collect()
+    }
+
+    pub(crate) async fn get_transaction_receipt(
+        &mut self,
+        transaction_hash: H256,
+    ) -> Result<TransactionReceipt> {
+        let transaction_receipt = self
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash)?;
+
+        Ok(transaction_receipt)
+    }
+}
+
+/// 当前的系统时间，Unix时间戳，单位为毫秒
+fn now_millis() -> Result<u64> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| ChainError::InternalError(e.to_string()))?;
+
+    Ok(now.as_millis() as u64)
+}
+
+/// 检查交易的gas上限是否足以支付固有gas，不足时返回`IntrinsicGasTooLow`
+fn check_intrinsic_gas(transaction: &Transaction) -> Result<()> {
+    let intrinsic_gas = transaction.intrinsic_gas();
+
+    if transaction.gas < intrinsic_gas {
+        return Err(ChainError::IntrinsicGasTooLow(
+            transaction.gas.to_string(),
+            intrinsic_gas.to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+thread_local! {
+    // 测试中处理该哈希对应的交易时，在预先扣除gas费用之后注入一次panic
+    static PANICKING_TRANSACTION: std::cell::Cell<Option<H256>> = const { std::cell::Cell::new(None) };
+}
+
+/// 从panic的负载中取出panic信息
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".into())
+}
+
+/// 将区块信息写入区块中交易的收据
+///
+/// `receipts`需要按交易在区块中的顺序排列。
+/// 日志的`log_index`是日志在整个区块中的位置，按交易顺序连续递增，
+/// `transaction_index`是所属交易在区块中的位置，
+/// `transaction_log_index`是日志在所属交易中的位置
+pub(crate) fn assign_receipts_to_block(block: &Block, receipts: &mut [TransactionReceipt]) {
+    let mut log_index = U256::zero();
+
+    for (transaction_index, receipt) in receipts.iter_mut().enumerate() {
+        receipt.block_number = Some(block.number.into());
+        receipt.block_hash = block.hash;
+
+        for (transaction_log_index, log) in receipt.logs.iter_mut().enumerate() {
+            log.block_hash = block.hash;
+            log.block_number = Some(block.number);
+            log.transaction_hash = Some(receipt.transaction_hash);
+            log.transaction_index = Some(format!("{:#x}", transaction_index));
+            log.log_index = Some(log_index);
+            log.transaction_log_index = Some(U256::from(transaction_log_index));
+            log_index += U256::one();
+        }
+    }
+}
+
+/// 分批处理共享区块链中交易池的交易
+///
+/// 与`BlockChain::process_transactions`相同，但每批之间都会释放区块链的锁，
+/// 使得处理大量交易时其他请求可以在批次之间读取已经打包的收据
+pub(crate) async fn process_transactions_in_chunks(blockchain: &Mutex<BlockChain>) -> Result<()> {
+    let (chunk_size, mut remaining) = {
+        let blockchain = blockchain.lock().await;
+        let remaining = blockchain.transactions.lock().await.mempool.len();
+
+        (blockchain.config.max_block_transactions.max(1), remaining)
+    };
+
+    // 交易池为空时按配置打包空区块
+    if remaining == 0 {
+        let mut blockchain = blockchain.lock().await;
+        if blockchain.config.produce_empty_blocks {
+            blockchain.produce_empty_block()?;
+        }
+    }
+
+    while remaining > 0 {
+        let taken = blockchain
+            .lock()
+            .await
+            .process_transaction_chunk(remaining.min(chunk_size))
+            .await?;
+
+        if taken == 0 {
+            break;
+        }
+
+        remaining -= taken;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::helpers::deserialize;
+    use crate::helpers::tests::{setup, ACCOUNT_1, STORAGE};
+    use runtime::contract::{call_function_in_context, ContractInstance};
+    use types::account::AccountExt;
+    use utils::crypto::{keypair, public_key_address};
+    use wasmtime::component::Val;
+
+    const ERC20: &[u8] = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20.wasm");
+    const ENVIRONMENT: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/environment.wasm");
+    const REVERTER: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/reverter.wasm");
+    const CALLER: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/caller.wasm");
+
+    /// 创建一个新的区块链实例
     pub(crate) fn new_blockchain() -> BlockChain {
-        BlockChain::new((*STORAGE).clone()).unwrap()
+        BlockChain::new((*STORAGE).clone(), &NodeConfig::default()).unwrap()
     }
 
     /// 创建一个新的交易
@@ -351,16 +1650,105 @@ pub(crate) mod tests {
         let (blockchain, _, _) = setup().await;
         let block_number = blockchain.lock().await.get_current_block().unwrap().number;
         let transaction = new_transaction(Account::random(), blockchain.clone()).await;
-        let response = blockchain
-            .lock()
-            .await
-            .new_block(vec![transaction], H256::zero());
+        let response =
+            blockchain
+                .lock()
+                .await
+                .new_block(vec![transaction], H256::zero(), Bloom::zero());
         assert!(response.is_ok());
 
         let new_block_number = blockchain.lock().await.get_current_block().unwrap().number;
         assert_eq!(new_block_number, block_number + 1);
     }
 
+    /// 测试超出裁剪深度的区块只保留区块头，需要区块体的操作返回`BlockPruned`
+    #[tokio::test]
+    async fn prunes_block_bodies_beyond_the_prune_depth() {
+        let (blockchain, _, _) = setup().await;
+        // 区块写入区块链自己的存储，而不是测试共用的存储
+        let storage = Arc::new(Storage::new(Some("test_prune_blocks")).unwrap());
+        blockchain.lock().await.storage = storage.clone();
+        blockchain.lock().await.config.prune_depth = Some(2);
+        let first_block_number = blockchain.lock().await.get_current_block().unwrap().number + 1;
+        let mut transaction_hashes = vec![];
+
+        for _ in 0..4 {
+            let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+            let to = transaction.to.unwrap();
+            blockchain
+                .lock()
+                .await
+                .accounts
+                .add_account(&to, &AccountData::new(None))
+                .unwrap();
+            let transaction_hash = blockchain
+                .lock()
+                .await
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            transaction_hashes.push(transaction_hash);
+            process_transactions(blockchain.clone()).await;
+        }
+
+        let mut blockchain = blockchain.lock().await;
+        let current_block = blockchain.get_current_block().unwrap();
+        let pruned_block = blockchain.get_block_by_number(first_block_number).unwrap();
+
+        // 区块头和根哈希保留，交易列表被清空
+        assert!(blockchain.is_pruned(first_block_number).unwrap());
+        assert!(pruned_block.hash.is_some());
+        assert_ne!(pruned_block.transactions_root, H256::zero());
+        assert!(pruned_block.transactions.is_empty());
+        assert!(matches!(
+            blockchain.transaction_by_block_and_index(first_block_number, 0),
+            Err(ChainError::BlockPruned(_))
+        ));
+        assert!(matches!(
+            pruned_block.verify().map_err(ChainError::from),
+            Err(ChainError::BlockPruned(_))
+        ));
+        assert!(matches!(
+            blockchain.block_receipts(first_block_number).await,
+            Err(ChainError::BlockPruned(_))
+        ));
+        assert!(matches!(
+            blockchain.trace_transaction(transaction_hashes[0]).await,
+            Err(ChainError::BlockPruned(_))
+        ));
+
+        // 存储中的区块同样只保留区块头
+        let stored = storage
+            .get(pruned_block.block_hash().unwrap().as_bytes())
+            .unwrap()
+            .unwrap();
+        let stored: Block = deserialize(&stored).unwrap();
+        assert!(stored.transactions.is_empty());
+        assert_eq!(stored.transactions_root, pruned_block.transactions_root);
+
+        // 最近的区块保留完整的区块体
+        assert!(!blockchain.is_pruned(current_block.number).unwrap());
+        assert_eq!(current_block.transactions.len(), 1);
+        assert!(current_block.verify().is_ok());
+    }
+
+    /// 测试向从未出现过的地址转账时创建接收方账户
+    #[tokio::test]
+    async fn transfers_to_a_new_address() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let mut transaction = new_transaction(to, blockchain.clone()).await;
+        let mut blockchain = blockchain.lock().await;
+        assert!(blockchain.accounts.get_account(&to).is_err());
+
+        let (_, receipt) = blockchain.process_transaction(&mut transaction).unwrap();
+
+        assert_eq!(receipt.status, U64::one());
+        let recipient = blockchain.accounts.get_account(&to).unwrap();
+        assert_eq!(recipient.balance, U256::from(10));
+        assert_eq!(recipient.nonce, U256::zero());
+    }
+
     /// 测试发送交易
     #[tokio::test]
     async fn sends_a_transaction() {
@@ -379,4 +1767,1744 @@ pub(crate) mod tests {
         let balance = get_balance(blockchain, &to).await;
         assert_eq!(balance, U256::from(10));
     }
+
+    /// 测试签名无效的交易在进入交易池时被拒绝，签名有效的交易保留签名和交易哈希
+    #[tokio::test]
+    async fn verifies_signed_transactions_at_admission() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+        let to = Account::random();
+        blockchain
+            .accounts
+            .add_account(&from, &AccountData::new(None))
+            .unwrap();
+        let transaction =
+            Transaction::new(from, Some(to), U256::from(10), Some(U256::one()), None).unwrap();
+        let signed_transaction = transaction.sign(secret_key).unwrap();
+
+        // 签名后修改交易金额
+        let mut tampered: Transaction = signed_transaction.clone().into();
+        tampered.value = U256::from(1_000);
+        let tampered = SignedTransaction::try_from(tampered).unwrap();
+        let result = blockchain.send_transaction(tampered.into()).await;
+
+        assert!(matches!(result, Err(ChainError::TransactionNotVerified(_))));
+        assert!(blockchain.transactions.lock().await.mempool.is_empty());
+
+        let transaction_hash = blockchain
+            .send_transaction(signed_transaction.clone().into())
+            .await
+            .unwrap();
+        let transaction_storage = blockchain.transactions.lock().await;
+
+        assert_eq!(
+            transaction_hash,
+            signed_transaction.transaction_hash().unwrap()
+        );
+        assert_eq!(
+            transaction_storage.mempool[0].signature,
+            Some(signed_transaction.signature())
+        );
+    }
+
+    /// 测试拒绝绑定了其他链ID的已签名交易
+    #[tokio::test]
+    async fn rejects_signed_transactions_for_another_chain() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(1_000_000);
+        blockchain
+            .accounts
+            .add_account(&from, &account_data)
+            .unwrap();
+        let transaction = Transaction::new(
+            from,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        let chain_id = blockchain.config.chain_id;
+
+        let other_chain = transaction.sign_eip155(secret_key, chain_id + 1).unwrap();
+        assert_eq!(
+            blockchain.send_transaction(other_chain.into()).await,
+            Err(ChainError::ChainIdMismatch(
+                (chain_id + 1).to_string(),
+                chain_id.to_string()
+            ))
+        );
+
+        let this_chain = transaction.sign_eip155(secret_key, chain_id).unwrap();
+        assert!(blockchain.send_transaction(this_chain.into()).await.is_ok());
+    }
+
+    /// 测试缺少nonce的已签名交易被拒绝，重复提交同一笔交易也不会进入交易池
+    #[tokio::test]
+    async fn rejects_signed_transactions_without_a_nonce() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(1_000_000_000);
+        blockchain
+            .accounts
+            .add_account(&from, &account_data)
+            .unwrap();
+        let transaction =
+            Transaction::new(from, Some(Account::random()), U256::from(10), None, None).unwrap();
+        let signed_transaction = transaction.sign(secret_key).unwrap();
+
+        for _ in 0..2 {
+            let result = blockchain
+                .send_transaction(signed_transaction.clone().into())
+                .await;
+
+            assert!(matches!(
+                result,
+                Err(ChainError::MissingTransactionNonce(_))
+            ));
+        }
+
+        assert!(blockchain.transactions.lock().await.mempool.is_empty());
+    }
+
+    /// 测试开发模式的测试账户有余额，并且其交易由节点签名
+    #[tokio::test]
+    async fn signs_transactions_from_dev_accounts() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let dev_accounts = blockchain.add_dev_accounts(2).unwrap();
+        let (from, _) = dev_accounts[0];
+
+        assert_eq!(dev_accounts.len(), 2);
+        assert_eq!(
+            blockchain.accounts.get_account(&from).unwrap().balance,
+            U256::from(DEV_ACCOUNT_BALANCE)
+        );
+
+        let transaction =
+            Transaction::new(from, Some(Account::random()), U256::from(10), None, None).unwrap();
+        blockchain
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let pending = blockchain.transactions.lock().await.mempool[0].clone();
+        let signed_transaction = SignedTransaction::try_from(pending).unwrap();
+
+        assert!(Transaction::verify(signed_transaction, from).unwrap());
+    }
+
+    /// 测试交易同时出现在发送方和接收方的交易历史中
+    #[tokio::test]
+    async fn indexes_transactions_by_account() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        blockchain
+            .lock()
+            .await
+            .accounts
+            .add_account(&to, &AccountData::new(None))
+            .unwrap();
+        let mut transaction_hashes = vec![];
+
+        for _ in 0..2 {
+            let transaction = new_transaction(to, blockchain.clone()).await;
+            let transaction_hash = blockchain
+                .lock()
+                .await
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            process_transactions(blockchain.clone()).await;
+            transaction_hashes.push(transaction_hash);
+        }
+
+        let blockchain = blockchain.lock().await;
+        let sender_history = blockchain.transactions_for(&ACCOUNT_1);
+
+        assert_eq!(blockchain.transactions_for(&to), transaction_hashes);
+        assert!(transaction_hashes
+            .iter()
+            .all(|hash| sender_history.contains(hash)));
+        assert!(blockchain.transactions_for(&Account::random()).is_empty());
+    }
+
+    /// 测试交易池拒绝低于最低gas价格的交易，接受等于最低gas价格的交易
+    #[tokio::test]
+    async fn enforces_the_minimum_gas_price() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.min_gas_price = 10;
+        let mut underpriced = Transaction::new(
+            *ACCOUNT_1,
+            Some(Account::random()),
+            U256::from(10),
+            None,
+            None,
+        )
+        .unwrap();
+        underpriced.gas_price = U256::from(9);
+        let mut at_floor = underpriced.clone();
+        at_floor.gas_price = U256::from(10);
+
+        let result = blockchain.send_transaction(underpriced.into()).await;
+        assert_eq!(
+            result,
+            Err(ChainError::GasPriceTooLow("9".into(), "10".into()))
+        );
+
+        let result = blockchain.send_transaction(at_floor.into()).await;
+        assert!(result.is_ok());
+    }
+
+    /// 测试账户nonce达到上限时自动分配nonce返回错误而不是回绕
+    #[tokio::test]
+    async fn rejects_auto_nonces_that_overflow() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let mut account_data = blockchain.accounts.get_account(&ACCOUNT_1).unwrap();
+        account_data.nonce = U256::max_value();
+        blockchain
+            .accounts
+            .add_account(&ACCOUNT_1, &account_data)
+            .unwrap();
+        let transaction = Transaction::new(
+            *ACCOUNT_1,
+            Some(Account::random()),
+            U256::from(10),
+            None,
+            None,
+        )
+        .unwrap();
+        let result = blockchain.send_transaction(transaction.into()).await;
+
+        assert_eq!(
+            result,
+            Err(ChainError::NonceOverflow(ACCOUNT_1.to_string()))
+        );
+    }
+
+    /// 测试区块号达到上限时创建新区块返回错误而不是回绕
+    #[tokio::test]
+    async fn rejects_block_numbers_that_overflow() {
+        let mut blockchain = new_blockchain();
+        let last_block = Block::new(
+            U64::max_value(),
+            H256::zero(),
+            vec![],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+        blockchain.blocks.push(last_block);
+        let result = blockchain.new_block(vec![], H256::zero(), Bloom::zero());
+
+        assert!(matches!(
+            result,
+            Err(ChainError::BlockNumberOverflow(number)) if number == U64::max_value().to_string()
+        ));
+
+        // 合约执行环境中的下一个区块号同样不会回绕
+        let environment = blockchain.environment(&ACCOUNT_1, &ACCOUNT_1, U256::zero());
+        assert!(matches!(
+            environment,
+            Err(ChainError::BlockNumberOverflow(_))
+        ));
+    }
+
+    /// 测试允许列表中的账户可以发送交易
+    #[tokio::test]
+    async fn allows_transactions_from_allowed_senders() {
+        let (blockchain, _, _) = setup().await;
+        blockchain.lock().await.allowed_senders = Some(HashSet::from([*ACCOUNT_1]));
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let result = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    /// 测试不在允许列表中的账户发送交易被拒绝
+    #[tokio::test]
+    async fn rejects_transactions_from_disallowed_senders() {
+        let (blockchain, _, _) = setup().await;
+        blockchain.lock().await.allowed_senders = Some(HashSet::from([Account::random()]));
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let result = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await;
+
+        assert_eq!(
+            result,
+            Err(ChainError::SenderNotAllowed(ACCOUNT_1.to_string()))
+        );
+    }
+
+    /// 测试连续提交的自动nonce交易得到连续且不重复的nonce
+    #[tokio::test]
+    async fn assigns_sequential_auto_nonces_to_pending_transactions() {
+        let (blockchain, from, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let mined_nonce = blockchain.accounts.get_account(&from).unwrap().nonce;
+
+        for _ in 0..2 {
+            let transaction =
+                Transaction::new(from, Some(Account::random()), U256::from(10), None, None)
+                    .unwrap();
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+        }
+
+        let nonces = blockchain
+            .transactions
+            .lock()
+            .await
+            .mempool
+            .iter()
+            .map(|transaction| transaction.nonce.unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(nonces, vec![mined_nonce + 1, mined_nonce + 2]);
+    }
+
+    /// 测试模拟执行交易不会修改链上状态
+    #[tokio::test]
+    async fn simulates_a_transaction_without_committing() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        blockchain
+            .lock()
+            .await
+            .accounts
+            .add_account(&to, &AccountData::new(None))
+            .unwrap();
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let gas_used = transaction.gas_used();
+        let simulation_result = blockchain
+            .lock()
+            .await
+            .simulate(transaction.into())
+            .unwrap();
+
+        assert!(simulation_result.success);
+        assert_eq!(simulation_result.gas_used, gas_used);
+
+        let diff = simulation_result
+            .state_diff
+            .iter()
+            .find(|diff| diff.address == to)
+            .unwrap();
+        assert_eq!(diff.balance_after - diff.balance_before, U256::from(10));
+
+        let balance = get_balance(blockchain, &to).await;
+        assert_eq!(balance, U256::zero());
+    }
+
+    /// 测试模拟执行和估算gas之后，已提交的账户仍然可以读取和修改
+    ///
+    /// 模拟执行在内存中的副本上进行，不能写入或删除共享存储中的trie节点
+    #[tokio::test]
+    async fn reads_committed_accounts_after_simulating() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let balance = get_balance(blockchain.clone(), &ACCOUNT_1).await;
+
+        {
+            let blockchain = blockchain.lock().await;
+            blockchain.simulate(transaction.clone().into()).unwrap();
+            blockchain.estimate_gas(transaction.clone().into()).unwrap();
+        }
+
+        assert_eq!(get_balance(blockchain.clone(), &ACCOUNT_1).await, balance);
+        assert!(blockchain.lock().await.accounts.get_account(&to).is_err());
+
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        process_transactions(blockchain.clone()).await;
+        assert_eq!(get_balance(blockchain, &to).await, U256::from(10));
+    }
+
+    /// 测试模拟执行合约调用时返回合约发出的日志
+    #[tokio::test]
+    async fn simulation_returns_contract_logs() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1;
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(ERC20.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+
+        let account = Account::random().to_string();
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::zero(),
+            None,
+            Some(data.into()),
+        )
+        .unwrap();
+        let simulation_result = blockchain.simulate(mint.into()).unwrap();
+
+        assert!(simulation_result.success);
+        assert_eq!(simulation_result.logs.len(), 1);
+        assert_eq!(simulation_result.logs[0].address, contract);
+    }
+
+    /// 测试模拟执行时自动分配的nonce溢出返回错误而不是回绕
+    #[tokio::test]
+    async fn simulation_rejects_auto_nonces_that_overflow() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let mut account_data = blockchain.accounts.get_account(&ACCOUNT_1).unwrap();
+        account_data.nonce = U256::max_value();
+        blockchain
+            .accounts
+            .add_account(&ACCOUNT_1, &account_data)
+            .unwrap();
+        let transaction = Transaction::new(
+            *ACCOUNT_1,
+            Some(Account::random()),
+            U256::from(10),
+            None,
+            None,
+        )
+        .unwrap();
+        let result = blockchain.simulate(transaction.into());
+
+        assert_eq!(
+            result,
+            Err(ChainError::NonceOverflow(ACCOUNT_1.to_string()))
+        );
+    }
+
+    /// 测试相同的创世分配产生相同的创世块
+    #[tokio::test]
+    async fn identical_genesis_allocations_produce_the_same_genesis() {
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(1_000);
+        let allocations = vec![(Account::random(), account_data.clone())];
+
+        let genesis_1 =
+            BlockChain::from_genesis((*STORAGE).clone(), &allocations, &NodeConfig::default())
+                .unwrap()
+                .get_current_block()
+                .unwrap();
+        let genesis_2 =
+            BlockChain::from_genesis((*STORAGE).clone(), &allocations, &NodeConfig::default())
+                .unwrap()
+                .get_current_block()
+                .unwrap();
+
+        assert_ne!(genesis_1.state_root, H256::zero());
+        assert_eq!(genesis_1.hash, genesis_2.hash);
+
+        let other_allocations = vec![(Account::random(), account_data)];
+        let genesis_3 = BlockChain::from_genesis(
+            (*STORAGE).clone(),
+            &other_allocations,
+            &NodeConfig::default(),
+        )
+        .unwrap()
+        .get_current_block()
+        .unwrap();
+
+        assert_ne!(genesis_1.hash, genesis_3.hash);
+    }
+
+    /// 测试合约状态在不同交易之间持久化
+    #[tokio::test]
+    async fn persists_contract_state_across_transactions() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let account = Account::random().to_string();
+        let next_nonce = |blockchain: &BlockChain| {
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1
+        };
+
+        // 部署合约
+        let nonce = next_nonce(&blockchain);
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(ERC20.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+
+        // 在一笔交易中铸币
+        let nonce = next_nonce(&blockchain);
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mut mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::zero(),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        blockchain.process_transaction(&mut mint).unwrap();
+
+        // 在另一次执行中读取余额
+        let host = ContractHost::new(blockchain.accounts.snapshot().unwrap(), contract);
+        let (balance, _) = call_function_in_context(
+            ERC20,
+            "balance-of",
+            &["String", &account],
+            Context::new(host),
+        )
+        .unwrap();
+
+        assert_eq!(balance, vec![Val::U64(10)]);
+    }
+
+    /// 测试合约读取到的调用方为交易的发送者，erc20转账从发送者的余额中扣减
+    #[tokio::test]
+    async fn transfers_tokens_from_the_transaction_sender() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let sender = format!("{:?}", *ACCOUNT_1);
+        let account = Account::random().to_string();
+        let execute = |blockchain: &mut BlockChain, data: Option<Bytes>, to: Option<Account>| {
+            let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1;
+            let mut transaction =
+                Transaction::new(*ACCOUNT_1, to, U256::zero(), Some(nonce), data).unwrap();
+            let (_, receipt) = blockchain.process_transaction(&mut transaction).unwrap();
+            receipt
+        };
+
+        let receipt = execute(&mut blockchain, Some(ERC20.into()), None);
+        let contract = receipt.contract_address.unwrap();
+        let mint = bincode::serialize(&("mint", vec!["String", &sender, "U64", "10"])).unwrap();
+        execute(&mut blockchain, Some(mint.into()), Some(contract));
+        let transfer =
+            bincode::serialize(&("transfer", vec!["String", &account, "U64", "4"])).unwrap();
+        execute(&mut blockchain, Some(transfer.into()), Some(contract));
+
+        let host = ContractHost::new(blockchain.accounts.snapshot().unwrap(), contract);
+        let mut contract = ContractInstance::new(ERC20, Context::new(host)).unwrap();
+        let balance = contract.call("balance-of", &["String", &sender]).unwrap();
+        assert_eq!(balance, vec![Val::U64(6)]);
+        let balance = contract.call("balance-of", &["String", &account]).unwrap();
+        assert_eq!(balance, vec![Val::U64(4)]);
+    }
+
+    /// 测试调用合约时交易金额转入合约账户
+    #[tokio::test]
+    async fn transfers_the_value_to_a_called_contract() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let account = "0x6cd4a0a8b1d5a8b2b4f4d1d8d0e0e0e0a1b2c3d4".to_string();
+        let next_nonce = |blockchain: &BlockChain| {
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1
+        };
+
+        let nonce = next_nonce(&blockchain);
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(ERC20.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+        let sender_balance = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+
+        let nonce = next_nonce(&blockchain);
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mut mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::from(5),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        blockchain.process_transaction(&mut mint).unwrap();
+
+        let contract_balance = blockchain.accounts.get_account(&contract).unwrap().balance;
+        assert_eq!(contract_balance, U256::from(5));
+        // 重新编码后的交易数据共57个零字节和64个非零字节：21000 + 57 * 4 + 64 * 16
+        let fee = U256::from(22_252) * mint.gas_price;
+        let balance = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+        assert_eq!(balance, sender_balance - U256::from(5) - fee);
+    }
+
+    /// 测试合约执行成功时发出的日志写入交易收据
+    #[tokio::test]
+    async fn records_contract_logs_in_the_receipt() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let account = Account::random().to_string();
+        let next_nonce = |blockchain: &BlockChain| {
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1
+        };
+
+        let nonce = next_nonce(&blockchain);
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(ERC20.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+        assert!(receipt.logs.is_empty());
+
+        let nonce = next_nonce(&blockchain);
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mut mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::zero(),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut mint).unwrap();
+
+        let mut topic = [0; 32];
+        topic[..8].copy_from_slice(b"Transfer");
+        assert_eq!(receipt.logs.len(), 1);
+        assert_eq!(receipt.logs[0].address, contract);
+        assert_eq!(receipt.logs[0].topics, vec![H256::from(topic)]);
+        assert_eq!(
+            receipt.logs[0].data.as_ref(),
+            format!("0x0000000000000000000000000000000000000000:{}:10", account).as_bytes()
+        );
+    }
+
+    /// 测试legacy交易的收据记录交易的gas价格
+    #[tokio::test]
+    async fn records_the_effective_gas_price_in_the_receipt() {
+        let (blockchain, _, to) = setup().await;
+        let mut transaction = new_transaction(to, blockchain.clone()).await;
+        transaction.gas_price = U256::from(42);
+        let mut blockchain = blockchain.lock().await;
+        // legacy交易不受基础费用影响
+        blockchain.config.base_fee_per_gas = 7;
+        let (_, receipt) = blockchain.process_transaction(&mut transaction).unwrap();
+
+        assert_eq!(receipt.effective_gas_price, U256::from(42));
+    }
+
+    /// 测试EIP-1559交易的收据记录由基础费用和小费计算出的gas价格，并按该价格扣除费用
+    #[tokio::test]
+    async fn records_the_effective_gas_price_of_an_eip1559_transaction() {
+        let (blockchain, _, to) = setup().await;
+        let mut transaction = new_transaction(to, blockchain.clone()).await;
+        transaction.max_fee_per_gas = Some(U256::from(100));
+        transaction.max_priority_fee_per_gas = Some(U256::from(2));
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.base_fee_per_gas = 7;
+        let balance = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+        let (transaction, receipt) = blockchain.process_transaction(&mut transaction).unwrap();
+
+        assert_eq!(receipt.effective_gas_price, U256::from(9));
+        assert_eq!(
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance,
+            balance - transaction.value - transaction.gas_used() * 9
+        );
+    }
+
+    /// 测试合约执行回滚时交易收据记录回滚原因，并且状态不发生变化
+    #[tokio::test]
+    async fn records_the_revert_reason_in_the_receipt() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let next_nonce = |blockchain: &BlockChain| {
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1
+        };
+
+        let nonce = next_nonce(&blockchain);
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(REVERTER.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+        assert_eq!(receipt.status, U64::one());
+        let balance = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+
+        let nonce = next_nonce(&blockchain);
+        let data = bincode::serialize(&("fail", vec!["String", "not allowed"])).unwrap();
+        let mut fail = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::from(5),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut fail).unwrap();
+
+        assert_eq!(receipt.status, U64::zero());
+        assert_eq!(receipt.revert_reason, Some("not allowed".to_string()));
+
+        // 交易消耗了nonce并支付了gas费用，但转入合约的金额被回滚
+        // 重新编码后的交易数据共43个零字节和26个非零字节：21000 + 43 * 4 + 26 * 16
+        let fee = U256::from(21_588) * fail.gas_price;
+        let account = blockchain.accounts.get_account(&ACCOUNT_1).unwrap();
+        assert_eq!(account.nonce, nonce);
+        assert_eq!(account.balance, balance - fee);
+        let contract_balance = blockchain.accounts.get_account(&contract).unwrap().balance;
+        assert_eq!(contract_balance, U256::zero());
+    }
+
+    /// 测试交易预先支付gas上限的费用，执行后退还未使用的gas
+    #[tokio::test]
+    async fn refunds_unused_gas() {
+        let (blockchain, _, to) = setup().await;
+        let mut transaction = new_transaction(to, blockchain.clone()).await;
+        transaction.gas = U256::from(100_000);
+        transaction.gas_price = U256::from(2);
+        let mut blockchain = blockchain.lock().await;
+        let coinbase = blockchain.config.coinbase;
+        let coinbase_balance = blockchain
+            .accounts
+            .get_account(&coinbase)
+            .map(|account| account.balance)
+            .unwrap_or_default();
+        let balance = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+
+        blockchain.process_transaction(&mut transaction).unwrap();
+
+        // 普通转账只消耗21000 gas
+        let fee = U256::from(21_000 * 2);
+        let balance_after = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+        assert_eq!(balance_after, balance - transaction.value - fee);
+        let coinbase_balance_after = blockchain.accounts.get_account(&coinbase).unwrap().balance;
+        assert_eq!(coinbase_balance_after, coinbase_balance + fee);
+    }
+
+    /// 测试gas上限低于固有gas的交易在进入交易池和执行时都被拒绝
+    #[tokio::test]
+    async fn rejects_transactions_below_the_intrinsic_gas() {
+        let (blockchain, _, to) = setup().await;
+        let mut transaction = new_transaction(to, blockchain.clone()).await;
+        transaction.gas = U256::from(20_999);
+        let mut blockchain = blockchain.lock().await;
+        let balance = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+
+        let result = blockchain
+            .send_transaction(transaction.clone().into())
+            .await;
+        assert!(matches!(result, Err(ChainError::IntrinsicGasTooLow(_, _))));
+        assert!(blockchain.transactions.lock().await.mempool.is_empty());
+
+        let result = blockchain.process_transaction(&mut transaction);
+        assert!(matches!(result, Err(ChainError::IntrinsicGasTooLow(_, _))));
+        let balance_after = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().balance;
+        assert_eq!(balance_after, balance);
+    }
+
+    /// 测试跟踪已打包的合约调用交易
+    #[tokio::test]
+    async fn traces_a_contract_call() {
+        let (blockchain, _, _) = setup().await;
+        let account = Account::random().to_string();
+        let send = |to: Option<Account>, data: Vec<u8>| {
+            let blockchain = blockchain.clone();
+            async move {
+                let nonce = blockchain
+                    .lock()
+                    .await
+                    .accounts
+                    .get_account(&ACCOUNT_1)
+                    .unwrap()
+                    .nonce
+                    + 1;
+                let transaction =
+                    Transaction::new(*ACCOUNT_1, to, U256::zero(), Some(nonce), Some(data.into()))
+                        .unwrap();
+                let transaction_hash = blockchain
+                    .lock()
+                    .await
+                    .send_transaction(transaction.into())
+                    .await
+                    .unwrap();
+                process_transactions(blockchain.clone()).await;
+                transaction_hash
+            }
+        };
+
+        let deployment = send(None, ERC20.to_vec()).await;
+        let contract = blockchain
+            .lock()
+            .await
+            .get_transaction_receipt(deployment)
+            .await
+            .unwrap()
+            .contract_address
+            .unwrap();
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mint = send(Some(contract), data).await;
+
+        let mut blockchain = blockchain.lock().await;
+        let root_hash = blockchain.accounts.root_hash().unwrap();
+        let trace = blockchain.trace_transaction(mint).await.unwrap();
+
+        assert_eq!(trace.transaction_hash, mint);
+        assert_eq!(trace.revert_reason, None);
+        assert!(trace
+            .steps
+            .iter()
+            .any(|step| step.operation == "enter mint"));
+        // 跟踪不会修改当前的账户状态
+        assert_eq!(blockchain.accounts.root_hash().unwrap(), root_hash);
+    }
+
+    /// 测试之后的区块打包后，仍然可以跟踪较早区块中的交易
+    #[tokio::test]
+    async fn traces_a_transaction_from_an_earlier_block() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let mut transaction_hashes = vec![];
+
+        // 第一个区块的父区块是创世区块，从第二个区块开始跟踪
+        for _ in 0..3 {
+            let transaction = new_transaction(to, blockchain.clone()).await;
+            let transaction_hash = blockchain
+                .lock()
+                .await
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            process_transactions(blockchain.clone()).await;
+            transaction_hashes.push(transaction_hash);
+        }
+
+        let mut blockchain = blockchain.lock().await;
+        let root_hash = blockchain.accounts.root_hash().unwrap();
+        let trace = blockchain
+            .trace_transaction(transaction_hashes[1])
+            .await
+            .unwrap();
+
+        assert_eq!(trace.transaction_hash, transaction_hashes[1]);
+        assert_eq!(trace.revert_reason, None);
+        assert_eq!(blockchain.accounts.root_hash().unwrap(), root_hash);
+        assert_eq!(
+            blockchain.accounts.get_account(&to).unwrap().balance,
+            U256::from(30)
+        );
+    }
+
+    /// 测试合约递归调用超过配置的最大调用深度时交易执行失败
+    #[tokio::test]
+    async fn stops_contract_calls_at_the_configured_call_depth() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.max_call_depth = 4;
+        let next_nonce = |blockchain: &BlockChain| {
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1
+        };
+
+        let nonce = next_nonce(&blockchain);
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(CALLER.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+
+        // 合约不断调用自身，直到超过最大调用深度
+        let nonce = next_nonce(&blockchain);
+        let address = format!("{:?}", contract);
+        let data = bincode::serialize(&("recurse", vec!["String", &address])).unwrap();
+        let mut recurse = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::zero(),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        let result = blockchain.process_transaction(&mut recurse);
+
+        assert_eq!(
+            result.map(|_| ()),
+            Err(ChainError::RuntimeError(
+                contract.to_string(),
+                RuntimeError::CallDepthExceeded(4).to_string()
+            ))
+        );
+    }
+
+    /// 测试合约执行耗尽配置的fuel上限时交易执行失败
+    #[tokio::test]
+    async fn stops_contracts_at_the_configured_fuel_limit() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let next_nonce = |blockchain: &BlockChain| {
+            blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce + 1
+        };
+
+        let nonce = next_nonce(&blockchain);
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce),
+            Some(ERC20.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+
+        blockchain.config.max_fuel = Some(1_000);
+        let nonce = next_nonce(&blockchain);
+        let account = Account::random().to_string();
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mut mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::zero(),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        let result = blockchain.process_transaction(&mut mint);
+
+        assert!(matches!(
+            result,
+            Err(ChainError::RuntimeError(address, message))
+                if address == contract.to_string() && message.contains("fuel limit")
+        ));
+    }
+
+    /// 测试合约读取到的区块号与链的高度一致
+    #[tokio::test]
+    async fn exposes_the_block_number_to_contracts() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        process_transactions(blockchain.clone()).await;
+
+        let mut blockchain = blockchain.lock().await;
+        let current_block_number = blockchain.get_current_block().unwrap().number.as_u64();
+        let host = ContractHost::new(blockchain.accounts.snapshot().unwrap(), to);
+        let context = Context::new(host).with_environment(
+            blockchain
+                .environment(&ACCOUNT_1, &to, U256::zero())
+                .unwrap(),
+        );
+        let (block_number, _) =
+            call_function_in_context(ENVIRONMENT, "get-block-number", &[], context).unwrap();
+
+        // 合约在下一个区块中执行
+        assert_eq!(block_number, vec![Val::U64(current_block_number + 1)]);
+    }
+
+    /// 测试重新执行已打包的交易时，合约读取到交易所在区块的区块号和时间戳
+    #[tokio::test]
+    async fn exposes_the_mined_block_to_replayed_contracts() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        for _ in 0..2 {
+            let transaction = new_transaction(to, blockchain.clone()).await;
+            blockchain
+                .lock()
+                .await
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            process_transactions(blockchain.clone()).await;
+        }
+
+        let mut blockchain = blockchain.lock().await;
+        let current_block = blockchain.get_current_block().unwrap();
+        let block = blockchain
+            .get_block_by_number(current_block.number - 1)
+            .unwrap();
+        let environment = BlockChain::environment_at(
+            block.number.as_u64(),
+            block.timestamp / 1000,
+            &ACCOUNT_1,
+            &to,
+            U256::zero(),
+        )
+        .unwrap();
+        let host = ContractHost::new(blockchain.accounts.snapshot().unwrap(), to);
+        let context = Context::new(host).with_environment(environment);
+        let (block_number, context) =
+            call_function_in_context(ENVIRONMENT, "get-block-number", &[], context).unwrap();
+        let (block_timestamp, _) =
+            call_function_in_context(ENVIRONMENT, "get-block-timestamp", &[], context).unwrap();
+
+        // 合约在较早的区块中执行，而不是在下一个区块中
+        assert_eq!(block_number, vec![Val::U64(block.number.as_u64())]);
+        assert_eq!(block_timestamp, vec![Val::U64(block.timestamp / 1000)]);
+        assert!(block.number < current_block.number);
+    }
+
+    /// 测试区块的日志布隆过滤器能够标记匹配的地址
+    #[tokio::test]
+    async fn flags_matching_logs_in_the_block_bloom() {
+        let (blockchain, _, _) = setup().await;
+        let address = Account::random();
+        let log = Log {
+            address,
+            block_hash: None,
+            block_number: None,
+            data: Bytes::new(),
+            log_index: None,
+            log_type: None,
+            removed: None,
+            topics: vec![H256::random()],
+            transaction_hash: None,
+            transaction_index: None,
+            transaction_log_index: None,
+        };
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let block = blockchain
+            .lock()
+            .await
+            .new_block(vec![transaction], H256::zero(), logs_bloom([&log]))
+            .unwrap();
+
+        let matching = LogFilter {
+            address: Some(address),
+            ..Default::default()
+        };
+        let unrelated = LogFilter {
+            address: Some(Account::random()),
+            ..Default::default()
+        };
+
+        assert!(matching.matches_bloom(&block.logs_bloom));
+        assert!(!unrelated.matches_bloom(&block.logs_bloom));
+    }
+
+    /// 测试同一区块中多笔交易的日志得到连续递增的区块内位置
+    #[test]
+    fn assigns_log_indices_across_a_block() {
+        let log = Log {
+            address: Account::random(),
+            block_hash: None,
+            block_number: None,
+            data: Bytes::new(),
+            log_index: None,
+            log_type: None,
+            removed: None,
+            topics: vec![],
+            transaction_hash: None,
+            transaction_index: None,
+            transaction_log_index: None,
+        };
+        let receipt = |logs: Vec<Log>| TransactionReceipt {
+            block_hash: None,
+            block_number: None,
+            contract_address: None,
+            transaction_hash: H256::random(),
+            logs,
+            status: U64::one(),
+            cumulative_gas_used: U256::zero(),
+            effective_gas_price: U256::zero(),
+            revert_reason: None,
+        };
+        let mut receipts = vec![
+            receipt(vec![log.clone(), log.clone()]),
+            receipt(vec![log.clone()]),
+        ];
+        let block = Block::genesis().unwrap();
+
+        assign_receipts_to_block(&block, &mut receipts);
+
+        let logs = receipts
+            .iter()
+            .flat_map(|receipt| receipt.logs.iter())
+            .collect::<Vec<_>>();
+        let log_indices = logs
+            .iter()
+            .map(|log| log.log_index.unwrap().as_u64())
+            .collect::<Vec<_>>();
+        let transaction_log_indices = logs
+            .iter()
+            .map(|log| log.transaction_log_index.unwrap().as_u64())
+            .collect::<Vec<_>>();
+
+        assert_eq!(log_indices, vec![0, 1, 2]);
+        assert_eq!(transaction_log_indices, vec![0, 1, 0]);
+        assert_eq!(logs[0].transaction_index, Some("0x0".to_string()));
+        assert_eq!(logs[2].transaction_index, Some("0x1".to_string()));
+        assert_eq!(logs[2].transaction_hash, Some(receipts[1].transaction_hash));
+        assert!(receipts
+            .iter()
+            .all(|receipt| receipt.block_hash == block.hash));
+    }
+
+    /// 测试可以查询一个账户部署的所有合约
+    #[tokio::test]
+    async fn looks_up_contracts_deployed_by_an_account() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let mut transaction_hashes = vec![];
+
+        for _ in 0..2 {
+            let mut transaction_request: TransactionRequest =
+                Transaction::new(*ACCOUNT_1, None, U256::zero(), None, Some("erc20".into()))
+                    .unwrap()
+                    .into();
+            transaction_request.data = Some("erc20".into());
+            let transaction_hash = blockchain
+                .send_transaction(transaction_request.into())
+                .await
+                .unwrap();
+            transaction_hashes.push(transaction_hash);
+        }
+
+        blockchain.process_transactions().await.unwrap();
+
+        let contract_addresses = transaction_hashes
+            .iter()
+            .map(|transaction_hash| {
+                blockchain
+                    .transactions
+                    .try_lock()
+                    .unwrap()
+                    .get_transaction_receipt(transaction_hash)
+                    .unwrap()
+                    .contract_address
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(contract_addresses.len(), 2);
+        assert_ne!(contract_addresses[0], contract_addresses[1]);
+        assert_eq!(
+            blockchain.contracts_deployed_by(&ACCOUNT_1),
+            contract_addresses
+        );
+        assert!(blockchain
+            .contracts_deployed_by(&Account::random())
+            .is_empty());
+    }
+
+    /// 测试模拟执行部署交易不会留下部署记录
+    #[tokio::test]
+    async fn simulating_a_deployment_does_not_record_the_contract() {
+        let (blockchain, _, _) = setup().await;
+        let blockchain = blockchain.lock().await;
+        let deployed_contracts = blockchain.contracts_deployed_by(&ACCOUNT_1);
+        // 请求中使用合约名称作为数据，gas上限取部署合约代码的固有gas
+        let mut transaction_request: TransactionRequest =
+            Transaction::new(*ACCOUNT_1, None, U256::zero(), None, Some("erc20".into()))
+                .unwrap()
+                .into();
+        transaction_request.data = Some("erc20".into());
+
+        let simulation_result = blockchain.simulate(transaction_request).unwrap();
+
+        assert!(simulation_result.success);
+        assert!(simulation_result.contract_address.is_some());
+        assert_eq!(
+            blockchain.contracts_deployed_by(&ACCOUNT_1),
+            deployed_contracts
+        );
+    }
+
+    /// 测试区块只能容纳一笔交易时优先打包gas价格更高的交易
+    #[tokio::test]
+    async fn includes_higher_gas_price_transactions_first() {
+        let (blockchain, _, account_2) = setup().await;
+        let to = Account::random();
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.max_block_transactions = 1;
+
+        for account in [account_2, to] {
+            let mut account_data = AccountData::new(None);
+            account_data.balance = U256::from(10_000_000);
+            blockchain
+                .accounts
+                .add_account(&account, &account_data)
+                .unwrap();
+        }
+
+        let mut low_fee =
+            Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), None, None).unwrap();
+        low_fee.gas_price = U256::from(1);
+        let mut high_fee =
+            Transaction::new(account_2, Some(to), U256::from(10), None, None).unwrap();
+        high_fee.gas_price = U256::from(100);
+
+        let low_fee_hash = blockchain.send_transaction(low_fee.into()).await.unwrap();
+        let high_fee_hash = blockchain.send_transaction(high_fee.into()).await.unwrap();
+
+        let chunk_size = blockchain.config.max_block_transactions;
+        blockchain
+            .process_transaction_chunk(chunk_size)
+            .await
+            .unwrap();
+        let block = blockchain.get_current_block().unwrap();
+        assert_eq!(block.transactions.len(), 1);
+        assert_eq!(block.transactions[0].hash, Some(high_fee_hash));
+
+        blockchain
+            .process_transaction_chunk(chunk_size)
+            .await
+            .unwrap();
+        let block = blockchain.get_current_block().unwrap();
+        assert_eq!(block.transactions[0].hash, Some(low_fee_hash));
+    }
+
+    /// 测试区块中交易的gas消耗之和不超过区块的gas上限，放不下的交易打包进下一个区块
+    #[tokio::test]
+    async fn fills_blocks_up_to_the_gas_limit() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+        let mut transaction_hashes = vec![];
+
+        for i in 1..=3 {
+            let transaction = Transaction::new(
+                *ACCOUNT_1,
+                Some(Account::random()),
+                U256::from(10),
+                Some(nonce + i),
+                None,
+            )
+            .unwrap();
+            transaction_hashes.push(
+                blockchain
+                    .send_transaction(transaction.into())
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        // 数量上限可以放下全部三笔交易，gas上限只能放下两笔
+        blockchain.config.max_block_transactions = 3;
+        blockchain.config.gas_limit = 2 * 21_000 + 20_999;
+        let start = blockchain.get_current_block().unwrap().number;
+        blockchain.process_transactions().await.unwrap();
+
+        let first_block = blockchain.get_block_by_number(start + 1).unwrap();
+        let second_block = blockchain.get_block_by_number(start + 2).unwrap();
+        let hashes = |block: &Block| {
+            block
+                .transactions
+                .iter()
+                .map(|transaction| transaction.hash.unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(hashes(&first_block), transaction_hashes[..2].to_vec());
+        assert_eq!(first_block.gas_used, U256::from(42_000));
+        assert_eq!(hashes(&second_block), transaction_hashes[2..].to_vec());
+        assert!(blockchain.transactions.lock().await.mempool.is_empty());
+    }
+
+    /// 测试拒绝gas消耗超过区块gas上限的交易
+    #[tokio::test]
+    async fn rejects_transactions_above_the_block_gas_limit() {
+        let (blockchain, _, to) = setup().await;
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.gas_limit = 20_999;
+
+        assert_eq!(
+            blockchain.send_transaction(transaction.into()).await,
+            Err(ChainError::BlockGasLimitExceeded(
+                "21000".into(),
+                "20999".into()
+            ))
+        );
+    }
+
+    /// 测试某笔交易处理失败后，其余交易仍然被处理
+    #[tokio::test]
+    async fn keeps_processing_after_a_transaction_fails() {
+        let (blockchain, _, account_2) = setup().await;
+        let to = Account::random();
+
+        {
+            let mut blockchain = blockchain.lock().await;
+            let mut account_data = AccountData::new(None);
+            account_data.balance = U256::from(1);
+            blockchain
+                .accounts
+                .add_account(&account_2, &account_data)
+                .unwrap();
+            blockchain
+                .accounts
+                .add_account(&to, &AccountData::new(None))
+                .unwrap();
+
+            // 余额不足以支付gas费用和转账金额，处理时返回错误而不是panic
+            let underfunded =
+                Transaction::new(account_2, Some(to), U256::from(10), Some(U256::one()), None)
+                    .unwrap();
+            let mut underfunded_clone = underfunded.clone();
+            let result = blockchain.process_transaction(&mut underfunded_clone);
+            assert!(matches!(result, Err(ChainError::InsufficientFunds(_, _))));
+            blockchain
+                .send_transaction(underfunded.into())
+                .await
+                .unwrap();
+        }
+
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        process_transactions_in_chunks(&blockchain).await.unwrap();
+
+        let receipt = blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash);
+        assert!(receipt.is_ok());
+        assert_eq!(
+            get_balance(blockchain.clone(), &account_2).await,
+            U256::from(1)
+        );
+        assert_eq!(get_balance(blockchain, &to).await, U256::from(10));
+    }
+
+    /// 测试处理某笔交易时发生panic后，其余交易仍然被处理
+    ///
+    /// panic发生在预先扣除gas费用之后，发送方的余额和nonce需要回滚到处理这笔交易之前
+    #[tokio::test]
+    async fn keeps_processing_after_a_transaction_panics() {
+        let (blockchain, _, account_2) = setup().await;
+        let to = Account::random();
+        let balance = U256::from(u64::MAX);
+
+        {
+            let mut blockchain = blockchain.lock().await;
+            let mut account_data = AccountData::new(None);
+            account_data.balance = balance;
+            blockchain
+                .accounts
+                .add_account(&account_2, &account_data)
+                .unwrap();
+            blockchain
+                .accounts
+                .add_account(&to, &AccountData::new(None))
+                .unwrap();
+
+            let panicking =
+                Transaction::new(account_2, Some(to), U256::from(10), Some(U256::one()), None)
+                    .unwrap();
+            let panicking_hash = blockchain.send_transaction(panicking.into()).await.unwrap();
+            PANICKING_TRANSACTION.with(|hash| hash.set(Some(panicking_hash)));
+        }
+
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+
+        process_transactions_in_chunks(&blockchain).await.unwrap();
+        PANICKING_TRANSACTION.with(|hash| hash.set(None));
+
+        let receipt = blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash);
+        assert!(receipt.is_ok());
+        assert_eq!(get_balance(blockchain.clone(), &to).await, U256::from(10));
+
+        let sender = blockchain
+            .lock()
+            .await
+            .accounts
+            .get_account(&account_2)
+            .unwrap();
+        assert_eq!(sender.balance, balance);
+        assert_eq!(sender.nonce, U256::zero());
+    }
+
+    /// 测试分批处理交易时收据逐步可用
+    #[tokio::test]
+    async fn makes_receipts_available_chunk_by_chunk() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.max_block_transactions = 2;
+        blockchain
+            .accounts
+            .add_account(&to, &AccountData::new(None))
+            .unwrap();
+
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+        let mut transaction_hashes = vec![];
+
+        for i in 1..=5 {
+            let transaction =
+                Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce + i), None)
+                    .unwrap();
+            let transaction_hash = blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+            transaction_hashes.push(transaction_hash);
+        }
+
+        let has_receipt = |blockchain: &BlockChain, transaction_hash: &H256| {
+            blockchain
+                .transactions
+                .try_lock()
+                .unwrap()
+                .get_transaction_receipt(transaction_hash)
+                .is_ok()
+        };
+
+        // 处理第一批交易后，只有这一批交易的收据可用
+        blockchain.process_transaction_chunk(2).await.unwrap();
+        let available = transaction_hashes
+            .iter()
+            .filter(|transaction_hash| has_receipt(&blockchain, transaction_hash))
+            .count();
+        assert_eq!(available, 2);
+
+        // 处理剩余的交易后，所有收据都可用
+        blockchain.process_transactions().await.unwrap();
+        assert!(transaction_hashes
+            .iter()
+            .all(|transaction_hash| has_receipt(&blockchain, transaction_hash)));
+    }
+
+    /// 测试收据的累计gas消耗按交易在区块中的顺序累加
+    #[tokio::test]
+    async fn accumulates_gas_used_across_a_block() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let mut blockchain = blockchain.lock().await;
+        blockchain
+            .accounts
+            .add_account(&to, &AccountData::new(None))
+            .unwrap();
+
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+        let mut transactions = vec![];
+        let mut transaction_hashes = vec![];
+
+        for i in 1..=2 {
+            let mut transaction =
+                Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce + i), None)
+                    .unwrap();
+            transaction.gas = U256::from(30_000);
+            transaction.gas_price = U256::one();
+            let transaction_hash = blockchain
+                .send_transaction(transaction.clone().into())
+                .await
+                .unwrap();
+            transactions.push(transaction);
+            transaction_hashes.push(transaction_hash);
+        }
+
+        blockchain.process_transaction_chunk(2).await.unwrap();
+
+        let transaction_storage = blockchain.transactions.lock().await;
+        let receipt_1 = transaction_storage
+            .get_transaction_receipt(&transaction_hashes[0])
+            .unwrap();
+        let receipt_2 = transaction_storage
+            .get_transaction_receipt(&transaction_hashes[1])
+            .unwrap();
+
+        assert_eq!(receipt_1.cumulative_gas_used, transactions[0].gas_used());
+        assert_eq!(
+            receipt_2.cumulative_gas_used,
+            transactions[0].gas_used() + transactions[1].gas_used()
+        );
+
+        // 区块的gas消耗等于区块中最后一笔交易收据的累计gas消耗
+        let block = blockchain.get_current_block().unwrap();
+        assert_eq!(block.gas_used, receipt_2.cumulative_gas_used);
+        assert_eq!(receipt_2.cumulative_gas_used, U256::from(42_000));
+    }
+
+    /// 测试打包进区块的合约日志记录所属交易在区块中的位置
+    #[tokio::test]
+    async fn assigns_the_transaction_index_to_mined_logs() {
+        let (blockchain, _, _) = setup().await;
+        let mut blockchain = blockchain.lock().await;
+        let account = Account::random().to_string();
+
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+        let mut deployment = Transaction::new(
+            *ACCOUNT_1,
+            None,
+            U256::zero(),
+            Some(nonce + 1),
+            Some(ERC20.into()),
+        )
+        .unwrap();
+        let (_, receipt) = blockchain.process_transaction(&mut deployment).unwrap();
+        let contract = receipt.contract_address.unwrap();
+
+        // 同一区块中先打包一笔普通转账，再打包一笔铸币
+        let transfer = Transaction::new(
+            *ACCOUNT_1,
+            Some(Account::random()),
+            U256::from(10),
+            Some(nonce + 2),
+            None,
+        )
+        .unwrap();
+        let data = bincode::serialize(&("mint", vec!["String", &account, "U64", "10"])).unwrap();
+        let mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract),
+            U256::zero(),
+            Some(nonce + 3),
+            Some(data.into()),
+        )
+        .unwrap();
+        blockchain.send_transaction(transfer.into()).await.unwrap();
+        let mint_hash = blockchain.send_transaction(mint.into()).await.unwrap();
+
+        blockchain.process_transaction_chunk(2).await.unwrap();
+
+        let block = blockchain.get_current_block().unwrap();
+        let receipt = blockchain.get_transaction_receipt(mint_hash).await.unwrap();
+        assert_eq!(receipt.logs.len(), 1);
+
+        let log = &receipt.logs[0];
+        assert_eq!(log.transaction_index, Some("0x1".to_string()));
+        assert_eq!(log.transaction_hash, Some(mint_hash));
+        assert_eq!(log.log_index, Some(U256::zero()));
+        assert_eq!(log.block_hash, block.hash);
+        assert_eq!(log.block_number, Some(block.number));
+    }
+
+    /// 测试开启空区块后，交易池为空时区块高度仍然增长
+    #[tokio::test]
+    async fn produces_empty_blocks_when_enabled() {
+        let (blockchain, _, _) = setup().await;
+        let start = blockchain.lock().await.get_current_block().unwrap().number;
+
+        // 默认不打包空区块
+        process_transactions_in_chunks(&blockchain).await.unwrap();
+        let block = blockchain.lock().await.get_current_block().unwrap();
+        assert_eq!(block.number, start);
+
+        blockchain.lock().await.config.produce_empty_blocks = true;
+        process_transactions_in_chunks(&blockchain).await.unwrap();
+        process_transactions_in_chunks(&blockchain).await.unwrap();
+
+        let block = blockchain.lock().await.get_current_block().unwrap();
+        assert_eq!(block.number, start + 2);
+        assert!(block.transactions.is_empty());
+        assert!(block.verify().is_ok());
+    }
+
+    /// 测试读取待打包状态后，已提交的账户仍然可以读取
+    ///
+    /// 待打包状态在快照上执行交易池中的交易，不能删除或修改已提交状态的trie节点
+    #[tokio::test]
+    async fn reads_committed_accounts_after_pending_accounts() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let mut blockchain = blockchain.lock().await;
+        let balance = blockchain
+            .get_balance(&ACCOUNT_1, BlockTag::Latest)
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let pending = blockchain
+                .get_balance(&to, BlockTag::Pending)
+                .await
+                .unwrap();
+            assert_eq!(pending, U256::from(10));
+        }
+
+        assert_eq!(
+            blockchain
+                .get_balance(&ACCOUNT_1, BlockTag::Latest)
+                .await
+                .unwrap(),
+            balance
+        );
+        assert!(blockchain.accounts.get_account(&to).is_err());
+
+        blockchain.process_transactions().await.unwrap();
+        assert_eq!(
+            blockchain.get_balance(&to, BlockTag::Latest).await.unwrap(),
+            U256::from(10)
+        );
+    }
+
+    /// 测试相同的交易集合以不同顺序提交时得到相同的状态根
+    #[tokio::test]
+    async fn applies_transactions_deterministically() {
+        let sender_1 = Account::from_seed(1433);
+        let sender_2 = Account::from_seed(1434);
+        let recipient = Account::from_seed(1435);
+
+        let transactions = vec![
+            Transaction::new(
+                sender_1,
+                Some(recipient),
+                U256::from(10),
+                Some(U256::from(1)),
+                None,
+            )
+            .unwrap(),
+            Transaction::new(
+                sender_1,
+                Some(sender_2),
+                U256::from(20),
+                Some(U256::from(2)),
+                None,
+            )
+            .unwrap(),
+            Transaction::new(
+                sender_2,
+                Some(recipient),
+                U256::from(30),
+                Some(U256::from(1)),
+                None,
+            )
+            .unwrap(),
+            Transaction::new(
+                sender_2,
+                Some(sender_1),
+                U256::from(40),
+                Some(U256::from(2)),
+                None,
+            )
+            .unwrap(),
+        ];
+
+        let apply = |transactions: Vec<Transaction>| {
+            let mut blockchain = new_blockchain();
+            let mut account_data = AccountData::new(None);
+            account_data.balance = U256::from(1_000_000);
+
+            for sender in [sender_1, sender_2] {
+                blockchain
+                    .accounts
+                    .add_account(&sender, &account_data)
+                    .unwrap();
+            }
+
+            let state_root = blockchain.accounts.root_hash().unwrap();
+            let root = blockchain
+                .apply_transactions_deterministic(transactions)
+                .unwrap();
+
+            // 交易只在快照上执行，链上的状态根保持不变
+            assert_ne!(root, state_root);
+            assert_eq!(blockchain.accounts.root_hash().unwrap(), state_root);
+            assert_eq!(
+                blockchain.accounts.get_account(&sender_1).unwrap().nonce,
+                U256::zero()
+            );
+
+            root
+        };
+
+        let root_1 = apply(transactions.clone());
+        let root_2 = apply(transactions.into_iter().rev().collect());
+
+        assert_eq!(root_1, root_2);
+    }
+
+    /// 测试每打包一个区块调用一次区块回调
+    #[tokio::test]
+    async fn calls_block_callbacks_once_per_block() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let mut blockchain = blockchain.lock().await;
+        blockchain.config.max_block_transactions = 1;
+
+        let block_numbers = Arc::new(std::sync::Mutex::new(vec![]));
+        let recorded = block_numbers.clone();
+        blockchain.add_block_callbacks(vec![Box::new(move |block: &Block| {
+            recorded.lock().unwrap().push(block.number)
+        })]);
+
+        let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+        let start = blockchain.get_current_block().unwrap().number;
+
+        for i in 1..=3 {
+            let transaction =
+                Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce + i), None)
+                    .unwrap();
+            blockchain
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+        }
+
+        blockchain.process_transactions().await.unwrap();
+
+        assert_eq!(
+            *block_numbers.lock().unwrap(),
+            vec![start + 1, start + 2, start + 3]
+        );
+    }
 }
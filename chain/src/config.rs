@@ -0,0 +1,249 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use ethereum_types::U256;
+use runtime::host::{
+    ResourceLimits, DEFAULT_MAX_CALL_DEPTH, DEFAULT_MAX_MEMORY_BYTES, DEFAULT_MAX_TABLE_ELEMENTS,
+};
+use serde::Deserialize;
+use types::account::{Account, AccountData};
+use types::block::GENESIS_DIFFICULTY;
+
+use crate::error::{ChainError, Result};
+use crate::storage;
+
+// 未配置创世分配文件时预先分配余额的开发账户
+const DEFAULT_GENESIS_ACCOUNT: &str = "0x4a0d457e884ebd9b9773d172ed687417caac4f14";
+
+// 开发账户在创世时的余额
+const DEFAULT_GENESIS_BALANCE: u64 = 1_000_000_000_000;
+
+/// 节点的统一配置，可以从TOML文件中加载，文件中省略的字段使用默认值
+///
+/// 字段:
+/// - listen_address: JSON-RPC服务监听的地址
+/// - data_dir: 数据库所在的目录
+/// - chain_id: 链ID
+/// - coinbase: 节点的收益地址，未配置时为零地址
+/// - block_time: 出块间隔，单位为毫秒，也是难度调整的目标出块间隔
+/// - genesis_difficulty: 创世块的难度，之后每个区块根据出块间隔调整难度
+/// - gas_limit: 区块的gas上限
+/// - max_block_transactions: 每个区块最多打包的交易数量，交易池按此大小分批处理
+/// - produce_empty_blocks: 交易池为空时是否仍然在每个出块间隔打包一个空区块，使区块高度持续增长
+/// - min_gas_price: 交易池接受交易的最低gas价格
+/// - base_fee_per_gas: 每个区块的基础费用，EIP-1559交易实际支付的gas价格由它和小费上限计算
+/// - max_call_depth: 合约间调用的最大深度，超过时合约执行失败
+/// - max_memory_bytes: 每个合约可以使用的最大内存，单位为字节
+/// - max_table_elements: 每个合约的表最多包含的元素数量
+/// - max_fuel: 每个合约执行可以消耗的fuel上限，为空时不限制
+/// - prune_depth: 保留完整区块体的最近区块数量，更早的区块只保留区块头，为空时不裁剪
+/// - max_receipts: 内存中保留的交易收据数量上限，超过时淘汰最早的收据，为空时不限制
+/// - persist_evicted_receipts: 是否将被淘汰的收据写入数据库，之后仍然可以查询
+/// - genesis_path: 创世分配文件的路径，为空时只为开发模式的默认账户分配余额
+/// - dev: 是否以开发模式启动，启动时生成有余额且已解锁的测试账户，也可以通过`--dev`参数开启
+/// - dev_accounts: 开发模式下生成的测试账户数量
+/// - mnemonic: 启动时从该BIP-39助记词导入节点密钥，为空时使用已有的密钥或随机生成
+/// - mnemonic_passphrase: 助记词的口令，默认为空
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub(crate) struct NodeConfig {
+    pub(crate) listen_address: String,
+    pub(crate) data_dir: PathBuf,
+    pub(crate) chain_id: u64,
+    pub(crate) coinbase: Account,
+    pub(crate) block_time: u64,
+    pub(crate) genesis_difficulty: u64,
+    pub(crate) gas_limit: u64,
+    pub(crate) max_block_transactions: usize,
+    pub(crate) produce_empty_blocks: bool,
+    pub(crate) min_gas_price: u64,
+    pub(crate) base_fee_per_gas: u64,
+    pub(crate) max_call_depth: usize,
+    pub(crate) max_memory_bytes: usize,
+    pub(crate) max_table_elements: u32,
+    pub(crate) max_fuel: Option<u64>,
+    pub(crate) prune_depth: Option<u64>,
+    pub(crate) max_receipts: Option<usize>,
+    pub(crate) persist_evicted_receipts: bool,
+    pub(crate) genesis_path: Option<PathBuf>,
+    pub(crate) dev: bool,
+    pub(crate) dev_accounts: usize,
+    pub(crate) mnemonic: Option<String>,
+    pub(crate) mnemonic_passphrase: String,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            listen_address: "127.0.0.1:8545".into(),
+            data_dir: PathBuf::from(storage::PATH),
+            chain_id: 1337,
+            coinbase: Account::zero(),
+            block_time: 1000,
+            genesis_difficulty: GENESIS_DIFFICULTY,
+            gas_limit: 30_000_000,
+            max_block_transactions: 100,
+            produce_empty_blocks: false,
+            min_gas_price: 0,
+            base_fee_per_gas: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES,
+            max_table_elements: DEFAULT_MAX_TABLE_ELEMENTS,
+            max_fuel: None,
+            prune_depth: None,
+            max_receipts: None,
+            persist_evicted_receipts: false,
+            genesis_path: None,
+            dev: false,
+            dev_accounts: 10,
+            mnemonic: None,
+            mnemonic_passphrase: String::new(),
+        }
+    }
+}
+
+impl NodeConfig {
+    /// 从TOML字符串解析配置
+    pub(crate) fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| ChainError::ConfigError(e.to_string()))
+    }
+
+    /// 从TOML文件加载配置
+    pub(crate) fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let toml = read_to_string(path.as_ref())
+            .map_err(|e| ChainError::ConfigError(format!("{}: {}", path.as_ref().display(), e)))?;
+
+        Self::from_toml(&toml)
+    }
+
+    /// 出块间隔
+    pub(crate) fn block_time(&self) -> Duration {
+        Duration::from_millis(self.block_time)
+    }
+
+    /// 合约执行的资源限制
+    pub(crate) fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            max_memory_bytes: self.max_memory_bytes,
+            max_table_elements: self.max_table_elements,
+            max_fuel: self.max_fuel,
+        }
+    }
+
+    /// 读取创世分配文件中的账户，未配置创世文件时只为开发模式的默认账户分配余额
+    ///
+    /// 创世分配文件是一个JSON数组，每个元素为账户地址和账户数据组成的二元组
+    pub(crate) fn genesis_allocations(&self) -> Result<Vec<(Account, AccountData)>> {
+        let Some(genesis_path) = self.genesis_path.as_ref() else {
+            let account = Account::from_str(DEFAULT_GENESIS_ACCOUNT)
+                .map_err(|e| ChainError::ConfigError(e.to_string()))?;
+            let mut account_data = AccountData::new(None);
+            account_data.balance = U256::from(DEFAULT_GENESIS_BALANCE);
+
+            return Ok(vec![(account, account_data)]);
+        };
+        let genesis = read_to_string(genesis_path)
+            .map_err(|e| ChainError::ConfigError(format!("{}: {}", genesis_path.display(), e)))?;
+        let allocations = serde_json::from_str(&genesis)
+            .map_err(|e| ChainError::ConfigError(format!("{}: {}", genesis_path.display(), e)))?;
+
+        Ok(allocations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_a_node_config() {
+        let toml = r#"
+            listen_address = "0.0.0.0:9545"
+            data_dir = "/var/lib/chain"
+            chain_id = 42
+            coinbase = "0x4a0d457e884ebd9b9773d172ed687417caac4f14"
+            block_time = 500
+            genesis_difficulty = 1024
+            gas_limit = 8000000
+            max_block_transactions = 10
+            produce_empty_blocks = true
+            min_gas_price = 5
+            base_fee_per_gas = 7
+            max_call_depth = 16
+            max_memory_bytes = 1048576
+            max_table_elements = 100
+            max_fuel = 1000000
+            prune_depth = 128
+            max_receipts = 10000
+            persist_evicted_receipts = true
+            genesis_path = "genesis.json"
+            dev = true
+            dev_accounts = 3
+            mnemonic = "test test test test test test test test test test test junk"
+            mnemonic_passphrase = "secret"
+        "#;
+        let config = NodeConfig::from_toml(toml).unwrap();
+
+        assert_eq!(
+            config,
+            NodeConfig {
+                listen_address: "0.0.0.0:9545".into(),
+                data_dir: PathBuf::from("/var/lib/chain"),
+                chain_id: 42,
+                coinbase: Account::from_str("0x4a0d457e884ebd9b9773d172ed687417caac4f14").unwrap(),
+                block_time: 500,
+                genesis_difficulty: 1_024,
+                gas_limit: 8_000_000,
+                max_block_transactions: 10,
+                produce_empty_blocks: true,
+                min_gas_price: 5,
+                base_fee_per_gas: 7,
+                max_call_depth: 16,
+                max_memory_bytes: 1_048_576,
+                max_table_elements: 100,
+                max_fuel: Some(1_000_000),
+                prune_depth: Some(128),
+                max_receipts: Some(10_000),
+                persist_evicted_receipts: true,
+                genesis_path: Some(PathBuf::from("genesis.json")),
+                dev: true,
+                dev_accounts: 3,
+                mnemonic: Some(
+                    "test test test test test test test test test test test junk".into()
+                ),
+                mnemonic_passphrase: "secret".into(),
+            }
+        );
+        assert_eq!(config.block_time(), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn it_applies_defaults_for_omitted_fields() {
+        let config = NodeConfig::from_toml("chain_id = 42").unwrap();
+
+        assert_eq!(
+            config,
+            NodeConfig {
+                chain_id: 42,
+                ..NodeConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn it_allocates_the_default_account_without_a_genesis_file() {
+        let allocations = NodeConfig::default().genesis_allocations().unwrap();
+
+        assert_eq!(allocations.len(), 1);
+        assert_eq!(
+            allocations[0].0,
+            Account::from_str(DEFAULT_GENESIS_ACCOUNT).unwrap()
+        );
+        assert_eq!(
+            allocations[0].1.balance,
+            U256::from(DEFAULT_GENESIS_BALANCE)
+        );
+    }
+}
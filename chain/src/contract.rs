@@ -0,0 +1,94 @@
+use std::str::FromStr;
+
+use runtime::{
+    contract::call_function_in_context,
+    error::{Result, RuntimeError},
+    host::{CallDepth, Context, ContractLog, Environment, Host, ResourceLimits},
+};
+use types::account::Account;
+use types::transaction::Log;
+
+use crate::{account::AccountStorage, error::ChainError};
+
+/// 合约执行时的宿主，为合约提供访问链上账户状态的能力
+///
+/// 宿主持有账户存储的快照，合约执行（包括嵌套的合约调用）都在该快照上进行，
+/// 只有执行成功后，调用方才会采用快照中的状态和合约发出的日志。
+/// 合约的存储读写都作用在`address`对应账户的存储trie上
+pub(crate) struct ContractHost {
+    pub(crate) accounts: AccountStorage,
+    pub(crate) logs: Vec<Log>,
+    address: Account,
+}
+
+impl ContractHost {
+    pub(crate) fn new(accounts: AccountStorage, address: Account) -> Self {
+        Self {
+            accounts,
+            logs: vec![],
+            address,
+        }
+    }
+}
+
+impl Host for ContractHost {
+    /// 加载目标合约的代码并在新的快照上执行，执行成功后合并其状态和日志
+    fn call(
+        &mut self,
+        call_depth: CallDepth,
+        limits: ResourceLimits,
+        env: Environment,
+        address: &str,
+        function: &str,
+        params: &[String],
+    ) -> Result<()> {
+        let address =
+            Account::from_str(address).map_err(|e| RuntimeError::HostError(e.to_string()))?;
+        let code = self
+            .accounts
+            .get_account(&address)?
+            .code_hash
+            .ok_or_else(|| ChainError::NotAContractAccount(address.to_string()))?;
+        let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+        let host = ContractHost::new(self.accounts.snapshot()?, address);
+        let context = Context::new(host)
+            .with_call_depth(call_depth)
+            .with_limits(limits)
+            .with_environment(env);
+        let (_, context) = call_function_in_context(&code, function, &params, context)?;
+
+        self.accounts.merge(context.host.accounts)?;
+        self.logs.extend(context.host.logs);
+
+        Ok(())
+    }
+
+    fn sload(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.accounts.get_storage(&self.address, key.as_bytes())?)
+    }
+
+    fn sstore(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        Ok(self
+            .accounts
+            .set_storage(&self.address, key.as_bytes(), value)?)
+    }
+
+    /// 将日志记录为当前合约发出的日志，区块和交易相关的字段在写入收据和打包时填充
+    fn log(&mut self, log: ContractLog) -> Result<()> {
+        self.logs.push(Log {
+            address: self.address,
+            block_hash: None,
+            block_number: None,
+            data: log.data.into(),
+            log_index: None,
+            log_type: None,
+            removed: None,
+            topics: log.topics.into_iter().map(Into::into).collect(),
+            transaction_hash: None,
+            transaction_index: None,
+            transaction_log_index: None,
+        });
+
+        Ok(())
+    }
+}
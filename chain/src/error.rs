@@ -1,4 +1,6 @@
 use jsonrpsee::core::Error as JsonRpseeError;
+use jsonrpsee::types::error::{CallError, ErrorObject, CALL_EXECUTION_FAILED_CODE};
+use runtime::error::RuntimeError;
 use serde::{Deserialize, Serialize};
 use std::{net::AddrParseError, sync::PoisonError};
 use thiserror::Error;
@@ -19,30 +21,72 @@ pub enum ChainError {
     #[error("Block {0} not found")]
     BlockNotFound(String),
 
+    #[error("Block number overflow after block {0}")]
+    BlockNumberOverflow(String),
+
+    #[error("Block {0} has been pruned")]
+    BlockPruned(String),
+
     #[error("Could not create root hash for : {0}")]
     CannotCreateRootHash(String),
 
+    #[error("Could not open the trie at root {0}")]
+    CannotOpenTrie(String),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
     #[error("Error encoding/decoding: {0}")]
     EncodingDecodingError(String),
 
     #[error("Could not deserialize: {0}")]
     DeserializeError(String),
 
+    #[error("Execution reverted: {0}")]
+    ExecutionReverted(String),
+
+    #[error("Gas price {0} is below the minimum gas price {1}")]
+    GasPriceTooLow(String, String),
+
+    #[error("Account {0} has insufficient funds to pay {1}")]
+    InsufficientFunds(String, String),
+
     #[error("Interal Error: {0}")]
     InternalError(String),
 
+    #[error("Gas limit {0} is below the intrinsic gas {1}")]
+    IntrinsicGasTooLow(String, String),
+
+    #[error("Transaction gas {0} exceeds the block gas limit {1}")]
+    BlockGasLimitExceeded(String, String),
+
+    #[error("Transaction chain id {0} does not match the chain id {1}")]
+    ChainIdMismatch(String, String),
+
     #[error("Invalid block number {0}")]
     InvalidBlockNumber(String),
 
+    #[error("Block timestamp {0} must be later than the current block timestamp {1}")]
+    InvalidBlockTimestamp(String, String),
+
+    #[error("Invalid typed data: {0}")]
+    InvalidTypedData(String),
+
     #[error("JsonRpsee Error: {0}")]
     JsonRpseeError(String),
 
+    #[error("Could not read key file {0}: {1}")]
+    KeyFileError(String, String),
+
     #[error("Parent hash is missing: {0}")]
     MissingHash(String),
 
     #[error("Missing nonce for transaction: {0}")]
     MissingTransactionNonce(String),
 
+    #[error("Nonce overflow for account {0}")]
+    NonceOverflow(String),
+
     #[error("Nonce {0} too high for account {1}")]
     NonceTooHigh(String, String),
 
@@ -52,23 +96,23 @@ pub enum ChainError {
     #[error("Account {0} is not a contract account")]
     NotAContractAccount(String),
 
+    #[error("Account {0} is not the deployer of contract {1}")]
+    NotContractDeployer(String, String),
+
     #[error("Error executing contract at address {0}: {1}")]
     RuntimeError(String, String),
 
-    #[error("Could not serialize: {0}")]
-    SerializeError(String),
+    #[error("Sender {0} is not allowed to send transactions")]
+    SenderNotAllowed(String),
 
-    #[error("Could not open the database: {0}")]
-    StorageCannotOpenDb(String),
+    #[error("No key available to sign for account {0}")]
+    SignerNotFound(String),
 
-    #[error("Could not destroy the database: {0}")]
-    StorageDestroyError(String),
-
-    #[error("Could not remove the key: {0}")]
-    StorageRemoveError(String),
+    #[error("Could not serialize: {0}")]
+    SerializeError(String),
 
-    #[error("Could not flush the database: {0}")]
-    StorageFlushError(String),
+    #[error("Storage error: {0}")]
+    StorageError(String),
 
     #[error("Could not find {0} in storage")]
     StorageNotFound(String),
@@ -93,6 +137,12 @@ pub enum ChainError {
 
     #[error("Type Error {0}")]
     TypeError(String),
+
+    #[error("Contract imports unsupported host functions: {0}")]
+    UnsupportedContractImports(String),
+
+    #[error("Value {0} is too large to pass to a contract")]
+    ValueTooLarge(String),
 }
 
 pub type Result<T> = std::result::Result<T, ChainError>;
@@ -127,9 +177,14 @@ impl From<JsonRpseeError> for ChainError {
     }
 }
 
+// 将ChainError转换为标准的JSON-RPC错误对象，使RPC处理函数可以直接使用`?`传播错误
 impl From<ChainError> for JsonRpseeError {
     fn from(error: ChainError) -> Self {
-        JsonRpseeError::Custom(error.to_string())
+        JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(
+            CALL_EXECUTION_FAILED_CODE,
+            error.to_string(),
+            None::<()>,
+        )))
     }
 }
 
@@ -139,9 +194,18 @@ impl<T> From<PoisonError<T>> for ChainError {
     }
 }
 
+impl From<rocksdb::Error> for ChainError {
+    fn from(error: rocksdb::Error) -> Self {
+        ChainError::StorageError(error.to_string())
+    }
+}
+
 impl From<TypeError> for ChainError {
     fn from(error: TypeError) -> Self {
-        ChainError::TypeError(error.to_string())
+        match error {
+            TypeError::BlockPruned(block_number) => ChainError::BlockPruned(block_number),
+            error => ChainError::TypeError(error.to_string()),
+        }
     }
 }
 
@@ -150,3 +214,9 @@ impl From<Box<bincode::ErrorKind>> for ChainError {
         ChainError::EncodingDecodingError(error.to_string())
     }
 }
+
+impl From<ChainError> for RuntimeError {
+    fn from(error: ChainError) -> Self {
+        RuntimeError::HostError(error.to_string())
+    }
+}
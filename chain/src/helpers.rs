@@ -18,6 +18,7 @@ pub(crate) fn deserialize<V: DeserializeOwned>(value: &[u8]) -> Result<V> {
     Ok(deserialized)
 }
 
+#[cfg(test)]
 #[allow(unused)]
 pub mod tests {
 
@@ -34,9 +35,8 @@ pub mod tests {
     use types::account::{Account, AccountData};
     use types::transaction::Transaction;
 
-    use crate::{blockchain::BlockChain, server::serve, storage::Storage};
+    use crate::{blockchain::BlockChain, config::NodeConfig, server::serve, storage::Storage};
 
-    static ADDRESS: &str = "127.0.0.1:8545";
     static DATABASE_NAME: Option<&str> = Some("test");
 
     lazy_static! {
@@ -49,21 +49,25 @@ pub mod tests {
     }
 
     pub(crate) async fn server(blockchain: Option<Arc<Mutex<BlockChain>>>) -> ServerHandle {
-        let blockchain = blockchain
-            .unwrap_or_else(|| Arc::new(Mutex::new(BlockChain::new((*STORAGE).clone()).unwrap())));
-        serve(ADDRESS, blockchain).await.unwrap()
+        let config = NodeConfig::default();
+        let blockchain = blockchain.unwrap_or_else(|| {
+            Arc::new(Mutex::new(
+                BlockChain::new((*STORAGE).clone(), &config).unwrap(),
+            ))
+        });
+        serve(&config, blockchain).await.unwrap()
     }
 
     pub(crate) fn client() -> HttpClient {
-        let url = format!("http://{}", ADDRESS);
+        let url = format!("http://{}", NodeConfig::default().listen_address);
         HttpClientBuilder::default().build(url).unwrap()
     }
 
     pub(crate) async fn setup() -> (Arc<Mutex<BlockChain>>, H160, H160) {
-        let mut blockchain = BlockChain::new((*STORAGE).clone()).unwrap();
+        let mut blockchain = BlockChain::new((*STORAGE).clone(), &NodeConfig::default()).unwrap();
         let mut account_data_1 = AccountData::new(None);
 
-        account_data_1.balance = U256::from(100_000);
+        account_data_1.balance = U256::from(1_000_000_000_000u64);
 
         blockchain
             .accounts
@@ -1,50 +1,104 @@
 use crate::error::{ChainError, Result};
 use ethereum_types::Address;
 use lazy_static::lazy_static;
-use std::fs::{create_dir, read, write};
+use std::fs::{create_dir_all, read, write};
+use std::path::Path;
 use utils::{
     crypto::{keypair, public_key_address},
+    hdwallet::{derive_child, mnemonic_to_seed},
     PublicKey, SecretKey,
 };
 
 // 定义密钥路径常量
 const PATH: &str = "./../.keys";
-const PRIVATE_KEY_PATH: &str = "./../.keys/private.key";
-const PUBLIC_KEY_PATH: &str = "./../.keys/public.key";
+const PRIVATE_KEY_FILE: &str = "private.key";
+const PUBLIC_KEY_FILE: &str = "public.key";
+// 从助记词派生节点密钥时使用的以太坊第一个账户的路径
+const MNEMONIC_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
 
 // 使用lazy_static宏来初始化静态变量
 lazy_static! {
-    // 初始化私钥
-    pub(crate) static ref PRIVATE_KEY: SecretKey =
-        get_private_key().expect("Could not retrieve the private key");
-    // 初始化公钥
-    pub(crate) static ref PUBLIC_KEY: PublicKey =
-        get_public_key().expect("Could not retrieve the public key");
+    // 初始化私钥，密钥文件不存在时先生成
+    pub(crate) static ref PRIVATE_KEY: SecretKey = add_keys()
+        .and_then(|_| get_private_key())
+        .expect("Could not retrieve the private key");
+    // 初始化公钥，密钥文件不存在时先生成
+    pub(crate) static ref PUBLIC_KEY: PublicKey = add_keys()
+        .and_then(|_| get_public_key())
+        .expect("Could not retrieve the public key");
     // 根据公钥初始化地址
     pub(crate) static ref ADDRESS: Address = public_key_address(&PUBLIC_KEY);
 }
 
-/// 添加密钥对到指定路径
+/// 添加密钥对到密钥路径
 ///
-/// 该函数首先尝试创建密钥目录，如果目录已存在或创建失败，将记录错误信息。
-/// 如果目录创建成功，将生成新的密钥对，并将其分别保存到私钥路径和公钥路径。
+/// 密钥目录不存在时先创建，只有在密钥文件不存在时才生成新的密钥对，
+/// 因此可以重复调用，已有的密钥不会被覆盖。
 ///
 /// # Returns
 ///
 /// 返回一个结果，表示操作是否成功。
 pub(crate) fn add_keys() -> Result<()> {
-    // 尝试创建密钥目录，如果失败则记录错误信息
-    if let Err(e) = create_dir(PATH) {
-        tracing::info!("Did not create key directory '{}' {}", PATH, e.to_string());
-    } else {
-        // 生成新的密钥对
-        let (private_key, public_key) = keypair();
-
-        // 将私钥和公钥分别写入文件
-        write(PRIVATE_KEY_PATH, private_key.as_ref()).unwrap();
-        write(PUBLIC_KEY_PATH, public_key.serialize()).unwrap();
+    add_keys_in(Path::new(PATH))
+}
+
+/// 在指定目录下添加密钥对，已有的密钥文件不会被覆盖
+fn add_keys_in(dir: &Path) -> Result<()> {
+    create_dir_all(dir).map_err(|e| ChainError::InternalError(e.to_string()))?;
+
+    let private_key_path = dir.join(PRIVATE_KEY_FILE);
+    let public_key_path = dir.join(PUBLIC_KEY_FILE);
+
+    if private_key_path.exists() && public_key_path.exists() {
+        tracing::info!("Using the existing keys in '{}'", dir.display());
+        return Ok(());
     }
 
+    // 生成新的密钥对，并将私钥和公钥分别写入文件
+    let (private_key, public_key) = keypair();
+    write(private_key_path, private_key.as_ref())
+        .map_err(|e| ChainError::InternalError(e.to_string()))?;
+    write(public_key_path, public_key.serialize())
+        .map_err(|e| ChainError::InternalError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// 由BIP-39助记词和口令派生私钥
+///
+/// 使用以太坊钱包第一个账户的派生路径`m/44'/60'/0'/0/0`，与常见钱包导入同一助记词得到的账户一致
+///
+/// # Returns
+///
+/// 返回派生得到的私钥，派生失败时返回错误。
+pub(crate) fn keys_from_mnemonic(phrase: &str, passphrase: &str) -> Result<SecretKey> {
+    let seed = mnemonic_to_seed(phrase, passphrase)
+        .map_err(|e| ChainError::InternalError(e.to_string()))?;
+
+    derive_child(&seed, MNEMONIC_DERIVATION_PATH)
+        .map_err(|e| ChainError::InternalError(e.to_string()))
+}
+
+/// 从助记词导入密钥对并保存到密钥路径
+///
+/// 与`add_keys`不同，已有的密钥文件会被助记词派生的密钥覆盖，用于在丢失密钥目录后恢复节点账户。
+///
+/// # Returns
+///
+/// 返回一个结果，表示操作是否成功。
+pub(crate) fn import_keys(phrase: &str, passphrase: &str) -> Result<()> {
+    let private_key = keys_from_mnemonic(phrase, passphrase)?;
+    let public_key = PublicKey::from_secret_key_global(&private_key);
+
+    create_dir_all(PATH).map_err(|e| ChainError::InternalError(e.to_string()))?;
+    write(Path::new(PATH).join(PRIVATE_KEY_FILE), private_key.as_ref())
+        .map_err(|e| ChainError::InternalError(e.to_string()))?;
+    write(
+        Path::new(PATH).join(PUBLIC_KEY_FILE),
+        public_key.serialize(),
+    )
+    .map_err(|e| ChainError::InternalError(e.to_string()))?;
+
     Ok(())
 }
 
@@ -54,10 +108,14 @@ pub(crate) fn add_keys() -> Result<()> {
 ///
 /// # Returns
 ///
-/// 返回一个结果，包含解析后的SecretKey对象，如果操作成功。
+/// 返回一个结果，包含解析后的SecretKey对象；私钥文件不存在或无法解析时返回错误。
 pub(crate) fn get_private_key() -> Result<SecretKey> {
-    // 读取私钥数据
-    let key = read(PRIVATE_KEY_PATH).expect("Could not read private key");
+    get_private_key_in(Path::new(PATH))
+}
+
+/// 从指定目录读取私钥
+fn get_private_key_in(dir: &Path) -> Result<SecretKey> {
+    let key = read_key_file(&dir.join(PRIVATE_KEY_FILE))?;
     // 将数据解析为SecretKey对象，如果解析失败，返回错误
     SecretKey::from_slice(&key).map_err(|e| ChainError::InternalError(e.to_string()))
 }
@@ -68,23 +126,86 @@ pub(crate) fn get_private_key() -> Result<SecretKey> {
 ///
 /// # Returns
 ///
-/// 返回一个结果，包含解析后的PublicKey对象，如果操作成功。
+/// 返回一个结果，包含解析后的PublicKey对象；公钥文件不存在或无法解析时返回错误。
 pub(crate) fn get_public_key() -> Result<PublicKey> {
-    // 读取公钥数据
-    let key = read(PUBLIC_KEY_PATH).expect("Could not read public key");
+    get_public_key_in(Path::new(PATH))
+}
+
+/// 从指定目录读取公钥
+fn get_public_key_in(dir: &Path) -> Result<PublicKey> {
+    let key = read_key_file(&dir.join(PUBLIC_KEY_FILE))?;
     // 将数据解析为PublicKey对象，如果解析失败，返回错误
     PublicKey::from_slice(&key).map_err(|e| ChainError::InternalError(e.to_string()))
 }
 
+/// 读取密钥文件的内容
+fn read_key_file(path: &Path) -> Result<Vec<u8>> {
+    read(path).map_err(|e| ChainError::KeyFileError(path.display().to_string(), e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn it_derives_keys_from_a_mnemonic() {
+        let private_key = keys_from_mnemonic(
+            "test test test test test test test test test test test junk",
+            "",
+        )
+        .unwrap();
+
+        assert_eq!(
+            utils::crypto::private_key_address(&private_key),
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                .parse()
+                .unwrap()
+        );
+    }
+
     #[test]
     fn it_save_keys() {
         add_keys().unwrap();
     }
 
+    #[test]
+    fn it_does_not_overwrite_existing_keys() {
+        let dir = Path::new(crate::storage::PATH).join("keys_add_twice");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        add_keys_in(&dir).unwrap();
+        let private_key = read(dir.join(PRIVATE_KEY_FILE)).unwrap();
+        let public_key = read(dir.join(PUBLIC_KEY_FILE)).unwrap();
+
+        add_keys_in(&dir).unwrap();
+
+        assert_eq!(read(dir.join(PRIVATE_KEY_FILE)).unwrap(), private_key);
+        assert_eq!(read(dir.join(PUBLIC_KEY_FILE)).unwrap(), public_key);
+    }
+
+    #[test]
+    fn it_regenerates_missing_key_files() {
+        let dir = Path::new(crate::storage::PATH).join("keys_regenerate");
+        add_keys_in(&dir).unwrap();
+        std::fs::remove_file(dir.join(PRIVATE_KEY_FILE)).unwrap();
+        std::fs::remove_file(dir.join(PUBLIC_KEY_FILE)).unwrap();
+
+        assert!(matches!(
+            get_private_key_in(&dir),
+            Err(ChainError::KeyFileError(_, _))
+        ));
+        assert!(matches!(
+            get_public_key_in(&dir),
+            Err(ChainError::KeyFileError(_, _))
+        ));
+
+        add_keys_in(&dir).unwrap();
+        let private_key = get_private_key_in(&dir).unwrap();
+        let public_key = get_public_key_in(&dir).unwrap();
+
+        assert_eq!(PublicKey::from_secret_key_global(&private_key), public_key);
+    }
+
     #[test]
     fn it_retrieves_the_saved_private_key() {
         add_keys().unwrap();
@@ -1,5 +1,7 @@
 mod account;
 mod blockchain;
+mod config;
+mod contract;
 mod error;
 mod helpers;
 mod keys;
@@ -10,13 +12,56 @@ mod storage;
 mod transaction;
 mod world_state;
 
+use std::sync::Arc;
+
+use ethereum_types::H256;
+
+use blockchain::BlockChain;
+use config::NodeConfig;
 use error::Result;
 use server::serve;
+use storage::Storage;
+use tokio::sync::Mutex;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let (blockchain, _, _) = crate::helpers::tests::setup().await;
-    let _server = serve("127.0.0.1:8545", blockchain).await?;
+    // 第一个不以`--`开头的命令行参数为可选的配置文件路径，`--dev`参数开启开发模式
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut config = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(path) => NodeConfig::load(path)?,
+        None => NodeConfig::default(),
+    };
+    if args.iter().any(|arg| arg == "--dev") {
+        config.dev = true;
+    }
+
+    // 数据库位于配置的数据目录中，未配置创世分配文件时为开发模式的默认账户分配余额
+    let storage = Arc::new(Storage::open(&config.data_dir, None)?);
+    let allocations = config.genesis_allocations()?;
+    let blockchain = Arc::new(Mutex::new(BlockChain::from_genesis(
+        storage,
+        &allocations,
+        &config,
+    )?));
+
+    // 开发模式下生成有余额且已解锁的测试账户，并打印地址和私钥
+    if config.dev {
+        let dev_accounts = blockchain
+            .lock()
+            .await
+            .add_dev_accounts(config.dev_accounts)?;
+
+        println!("Dev accounts:");
+        for (index, (account, secret_key)) in dev_accounts.iter().enumerate() {
+            println!(
+                "({}) {:?} private key: {:?}",
+                index,
+                account,
+                H256::from_slice(secret_key.as_ref())
+            );
+        }
+    }
+    let _server = serve(&config, blockchain).await?;
 
     futures::future::pending().await
 }
@@ -1,15 +1,19 @@
-use ethereum_types::H256;
-use jsonrpsee::core::Error;
-use jsonrpsee::core::Error as JsonRpseeError;
+use ethereum_types::{H256, U64};
 use jsonrpsee::RpcModule;
+use serde_json::Value;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 use types::{
-    account::{Account, AccountData},
-    block::BlockNumber,
+    account::{Account, AccountData, AddressParam},
+    block::{BlockNumber, BlockTag},
+    bytes::Bytes,
     helpers::to_hex,
-    transaction::TransactionRequest,
+    transaction::{LogFilter, SignedTransaction, TransactionRequest},
 };
 
-use crate::{error::Result, server::Context};
+use crate::{
+    error::{ChainError, Result},
+    server::Context,
+};
 
 /// 在RpcModule中添加一个新的异步方法`eth_add_account`。
 ///
@@ -35,9 +39,7 @@ pub(crate) fn eth_add_account(module: &mut RpcModule<Context>) -> Result<()> {
             .await
             .accounts
             // 尝试将新生成的账户添加到区块链上下文中。
-            .add_account(&key, &AccountData::new(None))
-            // 如果添加失败，将错误转换为JsonRpseeError::Custom。
-            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
+            .add_account(&key, &AccountData::new(None))?;
 
         // 返回新生成的账户公钥作为成功响应。
         Ok(key)
@@ -61,13 +63,7 @@ pub(crate) fn eth_accounts(module: &mut RpcModule<Context>) -> Result<()> {
     // 注册一个名为"eth_accounts"的异步RPC方法
     module.register_async_method("eth_accounts", |_, blockchain| async move {
         // 异步获取区块链锁，并尝试获取所有账户
-        let accounts = blockchain
-            .lock()
-            .await
-            .accounts
-            .get_all_accounts()
-            // 如果获取账户信息时发生错误，将其转换为JsonRpseeError::Custom
-            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?;
+        let accounts = blockchain.lock().await.accounts.get_all_accounts()?;
 
         // 成功获取账户信息后，返回账户
         Ok(accounts)
@@ -77,6 +73,23 @@ pub(crate) fn eth_accounts(module: &mut RpcModule<Context>) -> Result<()> {
     Ok(())
 }
 
+/// 在RpcModule中注册一个异步方法"eth_coinbase"，返回节点配置的收益地址
+///
+/// 参数:
+/// - module: 一个可变引用到RpcModule，用于注册RPC方法
+///
+/// 返回:
+/// - Result<()>: 表示方法注册成功或失败的空结果类型
+pub(crate) fn eth_coinbase(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_coinbase", |_, blockchain| async move {
+        let coinbase = blockchain.lock().await.config.coinbase;
+
+        Ok(coinbase)
+    })?;
+
+    Ok(())
+}
+
 /// 在RpcModule中注册一个异步方法，用于获取当前区块链的块号。
 ///
 /// # 参数
@@ -90,13 +103,7 @@ pub(crate) fn eth_block_number(module: &mut RpcModule<Context>) -> Result<()> {
     // 注册一个名为"eth_blockNumber"的异步RPC方法。
     module.register_async_method("eth_blockNumber", |_, blockchain| async move {
         // 异步获取区块链锁，并尝试获取当前块的信息。
-        let block_number = blockchain
-            .lock()
-            .await
-            .get_current_block()
-            // 如果获取块信息时发生错误，将其转换为JsonRpseeError::Custom错误返回。
-            .map_err(|e| JsonRpseeError::Custom(e.to_string()))?
-            .number;
+        let block_number = blockchain.lock().await.get_current_block()?.number;
         // 返回当前块的编号。
         Ok(block_number)
     })?;
@@ -134,6 +141,78 @@ pub(crate) fn eth_get_block_by_number(module: &mut RpcModule<Context>) -> Result
     Ok(())
 }
 
+/// 在RpcModule中注册一个异步方法`eth_getBlockByHash`，用于根据区块哈希获取区块
+///
+/// 第二个参数表示是否返回完整的交易对象，节点中的区块总是包含完整的交易，
+/// 因此该参数只为兼容以太坊的调用方式而接受
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_get_block_by_hash(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_getBlockByHash", |params, blockchain| async move {
+        // 从参数中解析出区块哈希和可选的完整交易标志
+        let mut seq = params.sequence();
+        let block_hash = seq.next::<H256>()?;
+        let _full = seq.optional_next::<bool>()?;
+        let block = blockchain.lock().await.get_block_by_hash(block_hash)?;
+
+        Ok(block)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`eth_getUncleCountByBlockNumber`，用于获取区块的叔块数量
+///
+/// 链上没有叔块，区块存在时总是返回`0x0`，只为兼容调用叔块相关方法的工具
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_get_uncle_count_by_block_number(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "eth_getUncleCountByBlockNumber",
+        |params, blockchain| async move {
+            // 先确认区块存在，不存在时返回错误
+            let block_number = params.one::<BlockNumber>()?;
+            blockchain.lock().await.get_block_by_number(*block_number)?;
+
+            Ok(to_hex(0))
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`eth_getUncleCountByBlockHash`，用于获取区块的叔块数量
+///
+/// 链上没有叔块，区块存在时总是返回`0x0`，只为兼容调用叔块相关方法的工具
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_get_uncle_count_by_block_hash(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "eth_getUncleCountByBlockHash",
+        |params, blockchain| async move {
+            // 先确认区块存在，不存在时返回错误
+            let block_hash = params.one::<H256>()?;
+            blockchain.lock().await.get_block_by_hash(block_hash)?;
+
+            Ok(to_hex(0))
+        },
+    )?;
+
+    Ok(())
+}
+
 /// 在RpcModule中注册一个异步方法`eth_getBalance`来获取账户余额
 ///
 /// # Parameters
@@ -151,17 +230,13 @@ pub(crate) fn eth_get_block_by_number(module: &mut RpcModule<Context>) -> Result
 pub(crate) fn eth_get_balance(module: &mut RpcModule<Context>) -> Result<()> {
     // 注册一个异步RPC方法`eth_getBalance`
     module.register_async_method("eth_getBalance", move |params, blockchain| async move {
-        // 从请求参数中解析出账户信息
-        let key = params.one::<Account>()?;
+        // 从请求参数中解析出账户信息和可选的区块标签，默认为latest
+        let mut seq = params.sequence();
+        let key: Account = seq.next::<AddressParam>()?.into();
+        let block_tag = seq.optional_next::<BlockTag>()?.unwrap_or_default();
 
-        // 根据账户信息获取账户余额
-        let balance = blockchain
-            .lock()
-            .await
-            .accounts
-            .get_account(&key)
-            .map_err(|e| Error::Custom(e.to_string()))?
-            .balance;
+        // 根据账户信息获取账户余额，pending标签会计入交易池中尚未打包的交易
+        let balance = blockchain.lock().await.get_balance(&key, block_tag).await?;
 
         // 将账户余额转换为十六进制字符串并返回
         Ok(to_hex(balance))
@@ -174,16 +249,16 @@ pub(crate) fn eth_get_balance(module: &mut RpcModule<Context>) -> Result<()> {
 pub(crate) fn eth_get_transaction_count(module: &mut RpcModule<Context>) -> Result<()> {
     // 注册一个名为"eth_getTransactionCount"的异步方法
     module.register_async_method("eth_getTransactionCount", |params, blockchain| async move {
-        // 从参数中解析出账户信息
-        let account = params.one::<Account>()?;
-        // 获取账户的交易计数
+        // 从参数中解析出账户信息和可选的区块标签，默认为latest
+        let mut seq = params.sequence();
+        let account: Account = seq.next::<AddressParam>()?.into();
+        let block_tag = seq.optional_next::<BlockTag>()?.unwrap_or_default();
+        // 获取账户的交易计数，pending标签会计入交易池中尚未打包的交易
         let count = blockchain
             .lock()
             .await
-            .accounts
-            .get_account(&account)
-            .map_err(|e| Error::Custom(e.to_string()))?
-            .nonce;
+            .get_transaction_count(&account, block_tag)
+            .await?;
 
         // 将交易计数转换为十六进制字符串并返回
         Ok(to_hex(count))
@@ -218,7 +293,7 @@ pub(crate) fn eth_send_transaction(module: &mut RpcModule<Context>) -> Result<()
             let transaction_hash = blockchain
                 .lock()
                 .await
-                .send_transaction(transaction_request)
+                .send_transaction(transaction_request.into())
                 .await;
 
             // 返回发送交易后的哈希值
@@ -229,6 +304,36 @@ pub(crate) fn eth_send_transaction(module: &mut RpcModule<Context>) -> Result<()
     Ok(())
 }
 
+/// 在RpcModule中注册一个异步方法`eth_sendRawTransaction`，用于发送已签名的交易
+///
+/// 参数为bincode序列化后的`SignedTransaction`，签名验证通过后放入交易池
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_send_raw_transaction(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "eth_sendRawTransaction",
+        move |params, blockchain| async move {
+            // 从参数中解析出序列化的已签名交易
+            let raw_transaction = params.one::<Bytes>()?;
+            let signed_transaction: SignedTransaction = bincode::deserialize(&raw_transaction)
+                .map_err(|e| ChainError::DeserializeError(e.to_string()))?;
+            let transaction_hash = blockchain
+                .lock()
+                .await
+                .send_transaction(signed_transaction.into())
+                .await?;
+
+            Ok(transaction_hash)
+        },
+    )?;
+
+    Ok(())
+}
+
 // 在RpcModule中注册一个异步方法，用于获取交易收据
 pub(crate) fn eth_get_transaction_receipt(module: &mut RpcModule<Context>) -> Result<()> {
     // 注册一个名为"eth_getTransactionReceipt"的异步方法
@@ -243,9 +348,7 @@ pub(crate) fn eth_get_transaction_receipt(module: &mut RpcModule<Context>) -> Re
                 .lock()
                 .await
                 .get_transaction_receipt(transaction_hash)
-                .await
-                // 如果获取失败，返回自定义错误
-                .map_err(|e| Error::Custom(e.to_string()))?;
+                .await?;
 
             // 返回获取到的交易收据
             Ok(transaction_receipt)
@@ -258,6 +361,7 @@ pub(crate) fn eth_get_transaction_receipt(module: &mut RpcModule<Context>) -> Re
 
 // 在RpcModule中注册以太坊获取智能合约代码的异步方法
 // 该函数负责处理来自RPC的请求，获取指定地址和区块的代码哈希
+// 非合约账户返回空字节码，账户不存在时仍然返回错误
 pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
     // 注册一个名为"eth_getCode"的异步方法
     // 该方法接受两个参数：params（请求参数）和blockchain（区块链数据）
@@ -265,19 +369,16 @@ pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
         // 创建一个序列对象，用于解析传入的参数
         let mut seq = params.sequence();
         // 解析第一个参数：账户地址
-        let address = seq.next::<Account>()?;
+        let address: Account = seq.next::<AddressParam>()?.into();
 
-        // 获取指定合约账户的代码哈希
+        // 获取指定账户的代码哈希，非合约账户没有代码，返回空字节码
         let code_hash = blockchain
             .lock()
             .await
             .accounts
-            .get_account(&address)
-            .map_err(|e| Error::Custom(e.to_string()))?
+            .get_account(&address)?
             .code_hash
-            .ok_or_else(|| {
-                JsonRpseeError::Custom(format!("missing code hash for account {:?}", address))
-            })?;
+            .unwrap_or_default();
 
         // 返回代码哈希
         Ok(code_hash)
@@ -287,25 +388,1056 @@ pub(crate) fn eth_get_code(module: &mut RpcModule<Context>) -> Result<()> {
     Ok(())
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use crate::helpers::tests::setup;
+/// 在RpcModule中注册一个异步方法`eth_simulate`，用于模拟执行交易
+///
+/// 该方法基于当前状态执行交易并返回执行结果、gas消耗和账户状态变化，
+/// 但不会将任何状态写入区块链
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_simulate(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_simulate", move |params, blockchain| async move {
+        // 从参数中解析出交易请求
+        let transaction_request = params.one::<TransactionRequest>()?;
+        // 在当前状态的快照上模拟执行交易
+        let simulation_result = blockchain.lock().await.simulate(transaction_request)?;
 
-    #[tokio::test]
-    async fn gets_an_account_balance() {
-        let (blockchain, id_1, _) = setup().await;
-        let balance = blockchain
+        Ok(simulation_result)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`eth_signTypedData_v4`，按EIP-712对结构化数据签名
+///
+/// 参数依次为签名账户和结构化数据，结构化数据可以是JSON对象，也可以是JSON字符串，
+/// 签名账户必须是节点账户或已解锁的账户
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_sign_typed_data_v4(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_signTypedData_v4", |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let account: Account = seq.next::<AddressParam>()?.into();
+        let typed_data = match seq.next::<Value>()? {
+            Value::String(json) => serde_json::from_str(&json)
+                .map_err(|e| ChainError::InvalidTypedData(e.to_string()))?,
+            typed_data => typed_data,
+        };
+        let signature = blockchain
             .lock()
             .await
-            .accounts
-            .get_account(&id_1)
-            .unwrap()
-            .balance;
-        let mut module = RpcModule::new(blockchain);
-        eth_get_balance(&mut module).unwrap();
-        let response: String = module.call("eth_getBalance", [id_1]).await.unwrap();
+            .sign_typed_data(&account, &typed_data)?;
 
-        assert_eq!(response, to_hex(balance));
+        Ok(signature)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`debug_traceTransaction`，用于跟踪已打包交易的执行过程
+///
+/// 该方法在父区块的状态上重新执行交易，返回合约执行过程中记录的函数调用、宿主函数调用和fuel消耗，
+/// 不会修改任何状态
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn debug_trace_transaction(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "debug_traceTransaction",
+        move |params, blockchain| async move {
+            // 从参数中解析出交易哈希
+            let transaction_hash = params.one::<H256>()?;
+            let trace = blockchain
+                .lock()
+                .await
+                .trace_transaction(transaction_hash)
+                .await?;
+
+            Ok(trace)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`eth_estimateGas`，用于估算交易所需的gas
+///
+/// 该方法模拟执行交易并返回gas消耗，交易执行失败时返回错误
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_estimate_gas(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_estimateGas", move |params, blockchain| async move {
+        // 从参数中解析出交易请求
+        let transaction_request = params.one::<TransactionRequest>()?;
+        // 模拟执行交易得到gas消耗
+        let gas = blockchain.lock().await.estimate_gas(transaction_request)?;
+
+        Ok(gas)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`test_compactStorage`，用于压缩底层数据库
+///
+/// 在大量写入和删除之后调用，可以清理删除标记和过期的文件以回收磁盘空间
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn test_compact_storage(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("test_compactStorage", |_, blockchain| async move {
+        blockchain.lock().await.accounts.compact();
+
+        Ok(true)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`test_clearMempool`，用于清空交易池
+///
+/// 便于在测试场景之间丢弃尚未处理的交易，返回丢弃的交易数量
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn test_clear_mempool(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("test_clearMempool", |_, blockchain| async move {
+        let transactions = blockchain.lock().await.transactions.clone();
+        let num_dropped = transactions.lock().await.clear_mempool();
+
+        Ok(num_dropped)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`evm_setNextBlockTimestamp`，指定下一个区块的时间戳
+///
+/// 参数为Unix时间戳，单位为秒，必须晚于当前区块的时间戳，之后的区块从该时间戳继续计时
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn evm_set_next_block_timestamp(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "evm_setNextBlockTimestamp",
+        |params, blockchain| async move {
+            let timestamp = params.one::<U64>()?;
+            blockchain
+                .lock()
+                .await
+                .set_next_block_timestamp(timestamp.as_u64())?;
+
+            Ok(true)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`evm_increaseTime`，将之后区块的时间戳向后推移
+///
+/// 参数为推移的秒数，返回累计推移的秒数
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn evm_increase_time(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("evm_increaseTime", |params, blockchain| async move {
+        let seconds = params.one::<U64>()?;
+        let offset = blockchain.lock().await.increase_time(seconds.as_u64());
+
+        Ok(U64::from(offset))
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`test_contractsDeployedBy`，用于查询一个账户部署的所有合约
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn test_contracts_deployed_by(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "test_contractsDeployedBy",
+        |params, blockchain| async move {
+            // 从参数中解析出部署者地址
+            let deployer: Account = params.one::<AddressParam>()?.into();
+            let contracts = blockchain.lock().await.contracts_deployed_by(&deployer);
+
+            Ok(contracts)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`test_upgradeContract`，用于替换已部署合约的代码
+///
+/// 参数依次为部署者地址、合约地址和新的合约代码，只有合约的部署者可以升级合约
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn test_upgrade_contract(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("test_upgradeContract", |params, blockchain| async move {
+        let mut seq = params.sequence();
+        let deployer: Account = seq.next::<AddressParam>()?.into();
+        let contract: Account = seq.next::<AddressParam>()?.into();
+        let code = seq.next::<Bytes>()?;
+        blockchain
+            .lock()
+            .await
+            .upgrade_contract(&deployer, &contract, code)?;
+
+        Ok(true)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个开发用的异步方法`test_transactionsFor`，用于查询一个账户参与的所有交易哈希
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn test_transactions_for(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("test_transactionsFor", |params, blockchain| async move {
+        // 从参数中解析出账户地址
+        let address: Account = params.one::<AddressParam>()?.into();
+        let transaction_hashes = blockchain.lock().await.transactions_for(&address);
+
+        Ok(transaction_hashes)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册订阅`eth_subscribeMinedTransactions`，交易被打包后推送其交易哈希
+///
+/// 客户端通过WebSocket订阅后无需轮询交易收据，通知的方法名为`eth_minedTransactions`
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_subscribe_mined_transactions(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_subscription(
+        "eth_subscribeMinedTransactions",
+        "eth_minedTransactions",
+        "eth_unsubscribeMinedTransactions",
+        |_, mut sink, blockchain| {
+            tokio::spawn(async move {
+                let receiver = blockchain.lock().await.mined_transactions.subscribe();
+                // 订阅者落后过多时跳过丢失的通知，继续推送之后的交易哈希
+                let stream = BroadcastStream::new(receiver).filter_map(|result| result.ok());
+                sink.pipe_from_stream(stream).await;
+            });
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册订阅`eth_subscribeNewHeads`，每打包一个新区块推送该区块
+///
+/// 通知的方法名为`eth_newHeads`
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_subscribe_new_heads(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_subscription(
+        "eth_subscribeNewHeads",
+        "eth_newHeads",
+        "eth_unsubscribeNewHeads",
+        |_, mut sink, blockchain| {
+            tokio::spawn(async move {
+                let receiver = blockchain.lock().await.new_heads.subscribe();
+                let stream = BroadcastStream::new(receiver).filter_map(|result| result.ok());
+                sink.pipe_from_stream(stream).await;
+            });
+
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`system_health`，用于节点的存活检查
+///
+/// 返回节点是否健康、当前区块号和连接的节点数量，只在读取状态时短暂持有区块链的锁
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn system_health(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("system_health", |_, blockchain| async move {
+        let health = blockchain.lock().await.health()?;
+
+        Ok(health)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`eth_getLogs`，用于按过滤条件查询日志
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_get_logs(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method("eth_getLogs", move |params, blockchain| async move {
+        // 从参数中解析出日志过滤条件
+        let filter = params.one::<LogFilter>()?;
+        // 遍历区块收据，收集满足条件的日志
+        let logs = blockchain.lock().await.get_logs(&filter).await?;
+
+        Ok(logs)
+    })?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法，根据区块号和交易索引获取交易
+///
+/// 该方法接收区块号和交易在区块中的索引两个参数，索引超出范围时返回null
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_get_transaction_by_block_number_and_index(
+    module: &mut RpcModule<Context>,
+) -> Result<()> {
+    module.register_async_method(
+        "eth_getTransactionByBlockNumberAndIndex",
+        move |params, blockchain| async move {
+            // 依次解析区块号和交易索引
+            let mut seq = params.sequence();
+            let block_number = seq.next::<BlockNumber>()?;
+            let index = seq.next::<U64>()?;
+
+            // 获取区块中指定位置的交易，超出范围时为None
+            let transaction = blockchain
+                .lock()
+                .await
+                .transaction_by_block_and_index(*block_number, index.as_usize())?;
+
+            Ok(transaction)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// 在RpcModule中注册一个异步方法`eth_getBlockReceipts`，一次性获取区块中所有交易的收据
+///
+/// # 参数
+/// * `module`: &mut RpcModule<Context> - RpcModule的可变引用，用于注册RPC方法。
+///
+/// # 返回值
+/// * `Result<()>` - 表示方法注册成功或失败的结果类型。
+pub(crate) fn eth_get_block_receipts(module: &mut RpcModule<Context>) -> Result<()> {
+    module.register_async_method(
+        "eth_getBlockReceipts",
+        move |params, blockchain| async move {
+            let block_number = params.one::<BlockNumber>()?;
+            let receipts = blockchain
+                .lock()
+                .await
+                .block_receipts(*block_number)
+                .await?;
+
+            Ok(receipts)
+        },
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::blockchain::tests::new_transaction;
+    use crate::blockchain::Health;
+    use crate::helpers::tests::{setup, ACCOUNT_1};
+    use crate::storage::Storage;
+    use ethereum_types::H520;
+    use ethereum_types::{Bloom, U256};
+    use jsonrpsee::core::Error;
+    use jsonrpsee::rpc_params;
+    use jsonrpsee::types::error::{CallError, CALL_EXECUTION_FAILED_CODE};
+    use types::account::to_checksum_string;
+    use types::block::Block;
+    use types::transaction::{Transaction, TransactionReceipt};
+    use utils::crypto::{hash_typed_data, keypair, public_key_address};
+    use utils::{Message, RecoverableSignature, RecoveryId, Secp256k1};
+
+    #[tokio::test]
+    async fn gets_the_coinbase() {
+        let (blockchain, _, _) = setup().await;
+        let coinbase = Account::random();
+        blockchain.lock().await.config.coinbase = coinbase;
+        let mut module = RpcModule::new(blockchain);
+        eth_coinbase(&mut module).unwrap();
+        let response: Account = module.call("eth_coinbase", rpc_params![]).await.unwrap();
+
+        assert_eq!(response, coinbase);
+    }
+
+    #[tokio::test]
+    async fn gets_an_account_balance() {
+        let (blockchain, id_1, _) = setup().await;
+        let balance = blockchain
+            .lock()
+            .await
+            .accounts
+            .get_account(&id_1)
+            .unwrap()
+            .balance;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_balance(&mut module).unwrap();
+        let response: String = module.call("eth_getBalance", [id_1]).await.unwrap();
+
+        assert_eq!(response, to_hex(balance));
+    }
+
+    #[tokio::test]
+    async fn gets_an_account_balance_with_a_hex_string_address() {
+        let (blockchain, id_1, _) = setup().await;
+        let balance = blockchain
+            .lock()
+            .await
+            .accounts
+            .get_account(&id_1)
+            .unwrap()
+            .balance;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_balance(&mut module).unwrap();
+        let checksummed = to_checksum_string(&id_1);
+        let unprefixed = format!("{:x}", id_1);
+
+        for address in [checksummed, unprefixed] {
+            let response: String = module
+                .call("eth_getBalance", rpc_params![address, "latest"])
+                .await
+                .unwrap();
+
+            assert_eq!(response, to_hex(balance));
+        }
+    }
+
+    #[tokio::test]
+    async fn gets_the_balance_of_a_dev_account() {
+        let (blockchain, _, _) = setup().await;
+        let dev_accounts = blockchain.lock().await.add_dev_accounts(3).unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_balance(&mut module).unwrap();
+
+        for (account, _) in dev_accounts {
+            let balance: U256 = module.call("eth_getBalance", [account]).await.unwrap();
+            assert!(!balance.is_zero());
+        }
+    }
+
+    #[tokio::test]
+    async fn gets_a_pending_account_balance() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        blockchain
+            .lock()
+            .await
+            .accounts
+            .add_account(&to, &AccountData::new(None))
+            .unwrap();
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_balance(&mut module).unwrap();
+
+        let latest: String = module
+            .call("eth_getBalance", rpc_params![to, BlockTag::Latest])
+            .await
+            .unwrap();
+        let pending: String = module
+            .call("eth_getBalance", rpc_params![to, BlockTag::Pending])
+            .await
+            .unwrap();
+
+        assert_eq!(latest, to_hex(0));
+        assert_eq!(pending, to_hex(10));
+    }
+
+    #[tokio::test]
+    async fn gets_a_pending_transaction_count() {
+        let (blockchain, id_1, _) = setup().await;
+        let transaction = new_transaction(Account::random(), blockchain.clone()).await;
+        let nonce = transaction.nonce.unwrap();
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_count(&mut module).unwrap();
+
+        let latest: String = module
+            .call(
+                "eth_getTransactionCount",
+                rpc_params![id_1, BlockTag::Latest],
+            )
+            .await
+            .unwrap();
+        let pending: String = module
+            .call(
+                "eth_getTransactionCount",
+                rpc_params![id_1, BlockTag::Pending],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(latest, to_hex(nonce - 1));
+        assert_eq!(pending, to_hex(nonce));
+    }
+
+    #[tokio::test]
+    async fn estimates_gas_for_a_transfer() {
+        let (blockchain, _, to) = setup().await;
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let mut module = RpcModule::new(blockchain);
+        eth_estimate_gas(&mut module).unwrap();
+        let request: TransactionRequest = transaction.into();
+        let response: U256 = module.call("eth_estimateGas", [request]).await.unwrap();
+
+        assert_eq!(response, U256::from(21_000));
+    }
+
+    #[tokio::test]
+    async fn gets_empty_code_for_a_non_contract_account() {
+        let (blockchain, id_1, _) = setup().await;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_code(&mut module).unwrap();
+        let response: Bytes = module.call("eth_getCode", [id_1]).await.unwrap();
+
+        assert!(response.is_empty());
+    }
+
+    #[tokio::test]
+    async fn upgrades_a_contract() {
+        let (blockchain, id_1, id_2) = setup().await;
+        let mut transaction_request: TransactionRequest =
+            Transaction::new(id_1, None, U256::zero(), None, Some("erc20".into()))
+                .unwrap()
+                .into();
+        transaction_request.data = Some("erc20".into());
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction_request.into())
+            .await
+            .unwrap();
+        blockchain
+            .lock()
+            .await
+            .process_transactions()
+            .await
+            .unwrap();
+        let contract = blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .get_transaction_receipt(&transaction_hash)
+            .unwrap()
+            .contract_address
+            .unwrap();
+        let mut module = RpcModule::new(blockchain);
+        test_upgrade_contract(&mut module).unwrap();
+        eth_get_code(&mut module).unwrap();
+        let new_code = Bytes::from_static(b"erc20 v2");
+
+        let response = module
+            .call::<_, bool>(
+                "test_upgradeContract",
+                rpc_params![id_2, contract, &new_code],
+            )
+            .await;
+        assert!(response.is_err());
+
+        let response: bool = module
+            .call(
+                "test_upgradeContract",
+                rpc_params![id_1, contract, &new_code],
+            )
+            .await
+            .unwrap();
+        assert!(response);
+
+        let code: Bytes = module.call("eth_getCode", [contract]).await.unwrap();
+        assert_eq!(code, new_code);
+    }
+
+    #[tokio::test]
+    async fn notifies_subscribers_of_mined_transactions() {
+        let (blockchain, _, to) = setup().await;
+        let mut module = RpcModule::new(blockchain.clone());
+        eth_subscribe_mined_transactions(&mut module).unwrap();
+        let mut subscription = module
+            .subscribe("eth_subscribeMinedTransactions", rpc_params![])
+            .await
+            .unwrap();
+
+        // 订阅在后台任务中建立，等待接收端就绪后再打包交易
+        while blockchain.lock().await.mined_transactions.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        let transaction_hash = blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        blockchain
+            .lock()
+            .await
+            .process_transactions()
+            .await
+            .unwrap();
+        let (notified_hash, _) = subscription.next::<H256>().await.unwrap().unwrap();
+
+        assert_eq!(notified_hash, transaction_hash);
+    }
+
+    #[tokio::test]
+    async fn sets_the_next_block_timestamp() {
+        let (blockchain, _, to) = setup().await;
+        let mut module = RpcModule::new(blockchain.clone());
+        evm_set_next_block_timestamp(&mut module).unwrap();
+        evm_increase_time(&mut module).unwrap();
+
+        // 一小时之后的时间戳
+        let current_timestamp = blockchain.lock().await.pending_block_timestamp().unwrap() / 1000;
+        let timestamp = current_timestamp + 3_600;
+        let response: bool = module
+            .call("evm_setNextBlockTimestamp", [to_hex(timestamp)])
+            .await
+            .unwrap();
+        assert!(response);
+
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        blockchain
+            .lock()
+            .await
+            .process_transactions()
+            .await
+            .unwrap();
+        let block = blockchain.lock().await.get_current_block().unwrap();
+        assert_eq!(block.timestamp, timestamp * 1000);
+
+        // 时间戳不能早于当前区块
+        let result: std::result::Result<bool, _> = module
+            .call("evm_setNextBlockTimestamp", [to_hex(current_timestamp)])
+            .await;
+        assert!(result.is_err());
+
+        // 之后的区块从指定的时间戳继续计时，推移的时间在此基础上累加
+        let offset: U64 = module.call("evm_increaseTime", [to_hex(60)]).await.unwrap();
+        assert!(offset.as_u64() > 3_000 + 60);
+    }
+
+    #[tokio::test]
+    async fn notifies_subscribers_of_new_heads() {
+        let (blockchain, _, to) = setup().await;
+        let mut module = RpcModule::new(blockchain.clone());
+        eth_subscribe_new_heads(&mut module).unwrap();
+        let mut subscription = module
+            .subscribe("eth_subscribeNewHeads", rpc_params![])
+            .await
+            .unwrap();
+
+        while blockchain.lock().await.new_heads.receiver_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let transaction = new_transaction(to, blockchain.clone()).await;
+        blockchain
+            .lock()
+            .await
+            .send_transaction(transaction.into())
+            .await
+            .unwrap();
+        blockchain
+            .lock()
+            .await
+            .process_transactions()
+            .await
+            .unwrap();
+        let current_block = blockchain.lock().await.get_current_block().unwrap();
+        let (block, _) = subscription.next::<Block>().await.unwrap().unwrap();
+
+        assert_eq!(block.hash, current_block.hash);
+        assert_eq!(block.number, current_block.number);
+    }
+
+    #[tokio::test]
+    async fn signs_typed_data() {
+        let (blockchain, _, _) = setup().await;
+        let (signer, _) = blockchain.lock().await.add_dev_accounts(1).unwrap()[0];
+        let mut module = RpcModule::new(blockchain);
+        eth_sign_typed_data_v4(&mut module).unwrap();
+        let typed_data = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "chainId", "type": "uint256" }
+                ],
+                "Greeting": [{ "name": "text", "type": "string" }]
+            },
+            "primaryType": "Greeting",
+            "domain": { "name": "Greeter", "chainId": 1337 },
+            "message": { "text": "hello" }
+        });
+
+        // 结构化数据以JSON字符串的形式传入
+        let signature: H520 = module
+            .call(
+                "eth_signTypedData_v4",
+                rpc_params![signer, typed_data.to_string()],
+            )
+            .await
+            .unwrap();
+
+        let digest = hash_typed_data(
+            &typed_data["domain"],
+            &typed_data["types"],
+            &typed_data["message"],
+        )
+        .unwrap();
+        let recovery_id = RecoveryId::from_i32(i32::from(signature[64]) - 27).unwrap();
+        let recoverable =
+            RecoverableSignature::from_compact(&signature[..64], recovery_id).unwrap();
+        let public_key = Secp256k1::new()
+            .recover_ecdsa(
+                &Message::from_slice(digest.as_bytes()).unwrap(),
+                &recoverable,
+            )
+            .unwrap();
+
+        assert_eq!(public_key_address(&public_key), signer);
+    }
+
+    #[tokio::test]
+    async fn reports_the_node_health() {
+        let (blockchain, _, _) = setup().await;
+        let block_number = blockchain.lock().await.get_current_block().unwrap().number;
+        let mut module = RpcModule::new(blockchain);
+        system_health(&mut module).unwrap();
+        let response: Health = module.call("system_health", rpc_params![]).await.unwrap();
+
+        assert_eq!(
+            response,
+            Health {
+                healthy: true,
+                block_number,
+                peers: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_a_raw_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+        let mut account_data = AccountData::new(None);
+        account_data.balance = U256::from(100);
+        blockchain
+            .lock()
+            .await
+            .accounts
+            .add_account(&from, &account_data)
+            .unwrap();
+        let transaction = Transaction::new(
+            from,
+            Some(Account::random()),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        let signed_transaction = transaction.sign(secret_key).unwrap();
+        let raw_transaction: Bytes = bincode::serialize(&signed_transaction).unwrap().into();
+        let mut module = RpcModule::new(blockchain.clone());
+        eth_send_raw_transaction(&mut module).unwrap();
+        let response: H256 = module
+            .call("eth_sendRawTransaction", [raw_transaction])
+            .await
+            .unwrap();
+
+        let blockchain = blockchain.lock().await;
+        let transaction_storage = blockchain.transactions.lock().await;
+        assert_eq!(transaction_storage.mempool.len(), 1);
+        assert_eq!(transaction_storage.mempool[0].from, from);
+        assert_eq!(transaction_storage.mempool[0].hash, Some(response));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_raw_transaction_signed_by_another_key() {
+        let (blockchain, id_1, _) = setup().await;
+        let (secret_key, _) = keypair();
+        let transaction =
+            Transaction::new(id_1, Some(Account::random()), U256::from(10), None, None).unwrap();
+        let signed_transaction = transaction.sign(secret_key).unwrap();
+        let raw_transaction: Bytes = bincode::serialize(&signed_transaction).unwrap().into();
+        let mut module = RpcModule::new(blockchain);
+        eth_send_raw_transaction(&mut module).unwrap();
+        let response = module
+            .call::<_, H256>("eth_sendRawTransaction", [raw_transaction])
+            .await;
+
+        match response {
+            Err(Error::Call(CallError::Custom(error_object))) => assert_eq!(
+                error_object.message(),
+                ChainError::TransactionNotVerified(
+                    signed_transaction.transaction_hash().unwrap().to_string()
+                )
+                .to_string()
+            ),
+            _ => panic!("expected a JSON-RPC call error, got {:?}", response),
+        }
+    }
+
+    #[tokio::test]
+    async fn clears_the_mempool() {
+        let (blockchain, id_1, _) = setup().await;
+
+        for _ in 0..3 {
+            let transaction =
+                Transaction::new(id_1, Some(Account::random()), U256::from(10), None, None)
+                    .unwrap();
+            blockchain
+                .lock()
+                .await
+                .send_transaction(transaction.into())
+                .await
+                .unwrap();
+        }
+
+        let mut module = RpcModule::new(blockchain.clone());
+        test_clear_mempool(&mut module).unwrap();
+        let response: usize = module
+            .call("test_clearMempool", rpc_params![])
+            .await
+            .unwrap();
+
+        assert_eq!(response, 3);
+        assert!(blockchain
+            .lock()
+            .await
+            .transactions
+            .lock()
+            .await
+            .mempool
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn gets_the_uncle_count_by_block_number() {
+        let (blockchain, _, _) = setup().await;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_uncle_count_by_block_number(&mut module).unwrap();
+        let method = "eth_getUncleCountByBlockNumber";
+
+        let response: String = module.call(method, [to_hex(0)]).await.unwrap();
+        assert_eq!(response, "0x0");
+
+        let response = module.call::<_, String>(method, [to_hex(1_000)]).await;
+        assert!(response.is_err());
+    }
+
+    #[tokio::test]
+    async fn gets_the_uncle_count_by_block_hash() {
+        let (blockchain, _, _) = setup().await;
+        let block_hash = blockchain.lock().await.get_current_block().unwrap().hash;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_uncle_count_by_block_hash(&mut module).unwrap();
+        let method = "eth_getUncleCountByBlockHash";
+
+        let response: String = module.call(method, [block_hash]).await.unwrap();
+        assert_eq!(response, "0x0");
+
+        let response = module.call::<_, String>(method, [H256::random()]).await;
+        assert!(response.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_tracing_an_unknown_transaction() {
+        let (blockchain, _, _) = setup().await;
+        let mut module = RpcModule::new(blockchain);
+        debug_trace_transaction(&mut module).unwrap();
+        let response = module
+            .call::<_, serde_json::Value>("debug_traceTransaction", [H256::random()])
+            .await;
+
+        assert!(response.is_err());
+    }
+
+    #[tokio::test]
+    async fn propagates_chain_errors_as_rpc_errors() {
+        let (blockchain, _, _) = setup().await;
+        let account = Account::random();
+        let mut module = RpcModule::new(blockchain);
+        eth_get_balance(&mut module).unwrap();
+        let response = module.call::<_, String>("eth_getBalance", [account]).await;
+
+        match response {
+            Err(Error::Call(CallError::Custom(error_object))) => {
+                assert_eq!(error_object.code(), CALL_EXECUTION_FAILED_CODE);
+                assert_eq!(
+                    error_object.message(),
+                    ChainError::StorageNotFound(Storage::key_string(account)).to_string()
+                );
+            }
+            _ => panic!("expected a JSON-RPC call error, got {:?}", response),
+        }
+    }
+
+    #[tokio::test]
+    async fn gets_a_transaction_by_block_number_and_index() {
+        let (blockchain, _, _) = setup().await;
+        let transaction_1 = new_transaction(Account::random(), blockchain.clone()).await;
+        let transaction_2 = new_transaction(Account::random(), blockchain.clone()).await;
+        let block = blockchain
+            .lock()
+            .await
+            .new_block(
+                vec![transaction_1.clone(), transaction_2.clone()],
+                H256::zero(),
+                Bloom::zero(),
+            )
+            .unwrap();
+        let block_number = to_hex(block.number);
+        let mut module = RpcModule::new(blockchain);
+        eth_get_transaction_by_block_number_and_index(&mut module).unwrap();
+
+        let method = "eth_getTransactionByBlockNumberAndIndex";
+        let response: Option<Transaction> = module
+            .call(method, [block_number.clone(), to_hex(0)])
+            .await
+            .unwrap();
+        assert_eq!(response, Some(transaction_1));
+
+        let response: Option<Transaction> = module
+            .call(method, [block_number.clone(), to_hex(1)])
+            .await
+            .unwrap();
+        assert_eq!(response, Some(transaction_2));
+
+        let response: Option<Transaction> = module
+            .call(method, [block_number, to_hex(2)])
+            .await
+            .unwrap();
+        assert_eq!(response, None);
+    }
+
+    #[tokio::test]
+    async fn gets_the_receipts_of_a_block() {
+        let (blockchain, _, _) = setup().await;
+        let to = Account::random();
+        let mut transaction_hashes = vec![];
+        {
+            let mut blockchain = blockchain.lock().await;
+            blockchain.config.max_block_transactions = 2;
+            blockchain
+                .accounts
+                .add_account(&to, &AccountData::new(None))
+                .unwrap();
+            let nonce = blockchain.accounts.get_account(&ACCOUNT_1).unwrap().nonce;
+
+            for i in 1..=2 {
+                let transaction =
+                    Transaction::new(*ACCOUNT_1, Some(to), U256::from(10), Some(nonce + i), None)
+                        .unwrap();
+                let transaction_hash = blockchain
+                    .send_transaction(transaction.into())
+                    .await
+                    .unwrap();
+                transaction_hashes.push(transaction_hash);
+            }
+
+            blockchain.process_transaction_chunk(2).await.unwrap();
+        }
+        let block_number = blockchain.lock().await.get_current_block().unwrap().number;
+        let mut module = RpcModule::new(blockchain);
+        eth_get_block_receipts(&mut module).unwrap();
+
+        let response: Vec<TransactionReceipt> = module
+            .call("eth_getBlockReceipts", [to_hex(block_number)])
+            .await
+            .unwrap();
+        let receipt_hashes = response
+            .iter()
+            .map(|receipt| receipt.transaction_hash)
+            .collect::<Vec<_>>();
+
+        assert_eq!(receipt_hashes, transaction_hashes);
     }
 }
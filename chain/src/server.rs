@@ -1,38 +1,46 @@
+use futures::FutureExt;
 use hyper::Method;
 use jsonrpsee::{
     server::{ServerBuilder, ServerHandle},
     RpcModule,
 };
-use std::{env, net::SocketAddr, sync::Arc, time::Duration};
+use std::{env, net::SocketAddr, panic::AssertUnwindSafe, sync::Arc};
 use tokio::{sync::Mutex, task, time};
 use tower_http::cors::{Any, CorsLayer};
 use tracing_subscriber::{util::SubscriberInitExt, FmtSubscriber};
 
 use crate::{
-    blockchain::BlockChain,
+    blockchain::{panic_message, process_transactions_in_chunks, BlockChain},
+    config::NodeConfig,
     error::{ChainError, Result},
-    keys::{add_keys, ADDRESS},
+    keys::{add_keys, import_keys, ADDRESS},
     logger::Logger,
     method::*,
 };
 
 pub(crate) type Context = Arc<Mutex<BlockChain>>;
 
-pub(crate) async fn serve(addr: &str, blockchain: Context) -> Result<ServerHandle> {
+pub(crate) async fn serve(config: &NodeConfig, blockchain: Context) -> Result<ServerHandle> {
     if env::var("RUST_LOG").is_err() {
         env::set_var("RUST_LOG", "info")
     }
 
     FmtSubscriber::builder().finish().try_init()?;
 
-    add_keys()?;
+    // 配置了助记词时从助记词导入节点密钥，否则使用已有的密钥或随机生成
+    match config.mnemonic.as_deref() {
+        Some(mnemonic) => import_keys(mnemonic, &config.mnemonic_passphrase)?,
+        None => add_keys()?,
+    }
 
-    let addrs = addr.parse::<SocketAddr>()?;
+    let addrs = config.listen_address.parse::<SocketAddr>()?;
     let cors = CorsLayer::new()
         .allow_methods([Method::POST])
         .allow_origin(Any)
         .allow_headers([hyper::header::CONTENT_TYPE]);
     let middleware = tower::ServiceBuilder::new().layer(cors);
+    // 在同一地址上同时接受HTTP和WebSocket连接，订阅方法只能通过WebSocket调用，
+    // 因此不能调用`http_only`
     let server = ServerBuilder::default()
         .set_logger(Logger)
         .set_middleware(middleware)
@@ -43,36 +51,68 @@ pub(crate) async fn serve(addr: &str, blockchain: Context) -> Result<ServerHandl
 
     eth_add_account(&mut module)?;
     eth_accounts(&mut module)?;
+    eth_coinbase(&mut module)?;
     eth_block_number(&mut module)?;
     eth_get_block_by_number(&mut module)?;
+    eth_get_block_by_hash(&mut module)?;
+    eth_get_uncle_count_by_block_number(&mut module)?;
+    eth_get_uncle_count_by_block_hash(&mut module)?;
     eth_get_balance(&mut module)?;
     eth_send_transaction(&mut module)?;
+    eth_send_raw_transaction(&mut module)?;
     eth_get_transaction_receipt(&mut module)?;
+    eth_get_block_receipts(&mut module)?;
     eth_get_transaction_count(&mut module)?;
     eth_get_code(&mut module)?;
+    eth_simulate(&mut module)?;
+    eth_sign_typed_data_v4(&mut module)?;
+    debug_trace_transaction(&mut module)?;
+    eth_estimate_gas(&mut module)?;
+    eth_get_logs(&mut module)?;
+    eth_get_transaction_by_block_number_and_index(&mut module)?;
+    test_compact_storage(&mut module)?;
+    test_clear_mempool(&mut module)?;
+    test_contracts_deployed_by(&mut module)?;
+    evm_set_next_block_timestamp(&mut module)?;
+    evm_increase_time(&mut module)?;
+    test_upgrade_contract(&mut module)?;
+    test_transactions_for(&mut module)?;
+    eth_subscribe_mined_transactions(&mut module)?;
+    eth_subscribe_new_heads(&mut module)?;
+    system_health(&mut module)?;
 
     let server_handle = server.start(module)?;
 
     tracing::info!(
-        "Starting server on {}, with public address {:?}",
+        "Starting HTTP and WebSocket server on {}, with public address {:?}, chain id {}, gas limit {}",
         addrs,
-        *ADDRESS
+        *ADDRESS,
+        config.chain_id,
+        config.gas_limit
     );
 
+    let block_time = config.block_time();
     let transaction_processor = task::spawn(async move {
-        let mut interval = time::interval(Duration::from_millis(1000));
+        let mut interval = time::interval(block_time);
 
-        // 循环不断处理交易池中的交易
+        // 循环不断处理交易池中的交易，某一轮处理发生panic时记录错误并继续下一轮
         loop {
             interval.tick().await;
 
-            if let Err(error) = blockchain_for_transaction_processor
-                .lock()
-                .await
-                .process_transactions()
-                .await
-            {
-                tracing::error!("Error processing transactions {}", error.to_string());
+            let result = AssertUnwindSafe(process_transactions_in_chunks(
+                &blockchain_for_transaction_processor,
+            ))
+            .catch_unwind()
+            .await;
+
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => {
+                    tracing::error!("Error processing transactions {}", error.to_string())
+                }
+                Err(payload) => {
+                    tracing::error!("Panic processing transactions {}", panic_message(&*payload))
+                }
             }
         }
     });
@@ -1,17 +1,62 @@
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 
 use eth_trie::DB as EthDB;
-use rocksdb::{Options, DB};
+use rocksdb::{BlockBasedOptions, Cache, DBCompressionType, Options, WriteBatch, DB};
 
 use crate::error::{ChainError, Result};
 
-const PATH: &str = "./../.tmp";
+pub(crate) const PATH: &str = "./../.tmp";
 const DATABASE_NAME: &str = "db";
 
+/// RocksDB的配置选项
+///
+/// 字段:
+/// - block_cache_mb: 块缓存的大小，单位为MB
+/// - compression: 数据块使用的压缩算法
+/// - max_open_files: 最多同时打开的文件数，-1表示不限制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct StorageOptions {
+    pub(crate) block_cache_mb: usize,
+    pub(crate) compression: DBCompressionType,
+    pub(crate) max_open_files: i32,
+}
+
+impl Default for StorageOptions {
+    fn default() -> Self {
+        Self {
+            block_cache_mb: 8,
+            compression: DBCompressionType::Snappy,
+            max_open_files: -1,
+        }
+    }
+}
+
+impl StorageOptions {
+    /// 转换为RocksDB的Options，数据库不存在时自动创建
+    fn to_options(self) -> Result<Options> {
+        let cache = Cache::new_lru_cache(self.block_cache_mb * 1024 * 1024)?;
+        let mut block_based_options = BlockBasedOptions::default();
+        block_based_options.set_block_cache(&cache);
+
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.set_compression_type(self.compression);
+        options.set_max_open_files(self.max_open_files);
+        options.set_block_based_table_factory(&block_based_options);
+
+        Ok(options)
+    }
+}
+
 // 定义一个调试友好的Storage结构体，用于与RocksDB数据库交互
+// writes记录写入数据库的次数，一个WriteBatch计为一次
 #[derive(Debug)]
 pub(crate) struct Storage {
     db: rocksdb::DB,
+    writes: AtomicUsize,
 }
 
 // 实现EthDB trait，用于以太坊数据库操作
@@ -20,84 +65,290 @@ impl EthDB for Storage {
 
     /// 从数据库中获取与key关联的值
     fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
-        let value = self
-            .db
-            .get(key)
-            .map_err(|_| ChainError::StorageNotFound(Storage::key_string(key)))?;
+        let value = self.db.get(key)?;
 
         Ok(value)
     }
 
     /// 在数据库中插入键值对
     fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<()> {
-        self.db
-            .put(key, value)
-            .map_err(|_| ChainError::StoragePutError(Storage::key_string(key)))?;
+        self.db.put(key, value)?;
+        self.writes.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// 在一个RocksDB WriteBatch中插入多个键值对
+    ///
+    /// trie在计算根哈希时一次性提交所有修改过的节点，使用WriteBatch后
+    /// 每次提交只需要一次数据库写入，而不是每个节点写入一次，没有键值对时不写入
+    fn insert_batch(&self, keys: Vec<Vec<u8>>, values: Vec<Vec<u8>>) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = WriteBatch::default();
+
+        for (key, value) in keys.iter().zip(values) {
+            batch.put(key, value);
+        }
+
+        self.db.write(batch)?;
+        self.writes.fetch_add(1, Ordering::Relaxed);
 
         Ok(())
     }
 
     /// 从数据库中移除指定的键值对
     fn remove(&self, key: &[u8]) -> Result<()> {
-        self.db.delete(key).map_err(|_|ChainError::StorageRemoveError(Storage::key_string(key)))?;
+        self.db.delete(key)?;
+        self.writes.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// 在一个RocksDB WriteBatch中移除多个键值对，没有键时不写入
+    fn remove_batch(&self, keys: &[Vec<u8>]) -> Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut batch = WriteBatch::default();
+
+        for key in keys {
+            batch.delete(key);
+        }
+
+        self.db.write(batch)?;
+        self.writes.fetch_add(1, Ordering::Relaxed);
+
         Ok(())
     }
 
     /// 刷新数据库
     fn flush(&self) -> Result<()> {
-        self.db.flush().map_err(|_|ChainError::StorageFlushError(Storage::key_string(key)))?;
+        self.db.flush()?;
         Ok(())
     }
 }
 
+/// 数据库中的一个键值对
+pub(crate) type KeyValue = (Box<[u8]>, Box<[u8]>);
+
 // 实现Storage结构体的方法
 impl Storage {
     /// 创建或打开一个名为database_name的数据库
+    #[cfg(test)]
     pub(crate) fn new(database_name: Option<&str>) -> Result<Self> {
+        Storage::new_with_options(database_name, StorageOptions::default())
+    }
+
+    /// 使用指定的RocksDB选项创建或打开一个名为database_name的数据库
+    #[cfg(test)]
+    pub(crate) fn new_with_options(
+        database_name: Option<&str>,
+        options: StorageOptions,
+    ) -> Result<Self> {
+        Storage::open_with_options(Path::new(PATH), database_name, options)
+    }
+
+    /// 在指定的数据目录下创建或打开一个名为database_name的数据库
+    pub(crate) fn open(data_dir: &Path, database_name: Option<&str>) -> Result<Self> {
+        Storage::open_with_options(data_dir, database_name, StorageOptions::default())
+    }
+
+    /// 在指定的数据目录下使用指定的RocksDB选项创建或打开一个名为database_name的数据库
+    pub(crate) fn open_with_options(
+        data_dir: &Path,
+        database_name: Option<&str>,
+        options: StorageOptions,
+    ) -> Result<Self> {
         let database_name = database_name.unwrap_or(DATABASE_NAME);
-        let db = DB::open_default(Storage::path(database_name))
-            .map_err(|e| ChainError::StorageCannotOpenDb(e.to_string()))?;
+        let db = DB::open(&options.to_options()?, data_dir.join(database_name))?;
+
+        Ok(Self {
+            db,
+            writes: AtomicUsize::new(0),
+        })
+    }
 
-        Ok(Self { db })
+    /// 打开数据库以来写入数据库的次数，一个WriteBatch计为一次
+    #[cfg(test)]
+    pub(crate) fn write_count(&self) -> usize {
+        self.writes.load(Ordering::Relaxed)
     }
 
     /// 获取数据库中所有的键，主要用于调试和特殊操作
     pub(crate) fn _get_all_keys<K: AsRef<[u8]>>(&self) -> Result<Vec<Box<[u8]>>> {
-        let value: Vec<Box<[u8]>> = self
-            .db
-            .iterator(rocksdb::IteratorMode::Start)
-            .map(std::result::Result::unwrap)
+        let keys = self
+            .iter_prefix(&[])?
+            .into_iter()
             .map(|(key, _)| key)
             .collect();
 
-        Ok(value)
+        Ok(keys)
+    }
+
+    /// 获取键以prefix开头的所有键值对，按键的顺序排列
+    ///
+    /// 数据库没有配置前缀提取器，前缀迭代器会从prefix开始一直迭代到末尾，
+    /// 因此在遇到第一个不匹配的键时停止
+    #[allow(dead_code)]
+    pub(crate) fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<KeyValue>> {
+        let mut entries = Vec::new();
+
+        for item in self.db.prefix_iterator(prefix) {
+            let (key, value) = item?;
+
+            if !key.starts_with(prefix) {
+                break;
+            }
+
+            entries.push((key, value));
+        }
+
+        Ok(entries)
     }
 
     /// 销毁指定的数据库，主要用于测试和特殊操作
     pub(crate) fn _destroy(database_name: Option<&str>) -> Result<()> {
         let database_name = database_name.unwrap_or(DATABASE_NAME);
-        DB::destroy(&Options::default(), Storage::path(database_name))
-            .map_err(|e| ChainError::StorageDestroyError(e.into()))?;
+        DB::destroy(&Options::default(), Path::new(PATH).join(database_name))?;
 
         Ok(())
     }
 
+    /// 压缩整个数据库，清理删除标记和过期的SST文件以回收磁盘空间
+    pub(crate) fn compact(&self) {
+        self.db.compact_range::<&[u8], &[u8]>(None, None);
+    }
+
     /// 将字节转换为字符串，主要用于错误信息的显示
     pub(crate) fn key_string<K: AsRef<[u8]>>(key: K) -> String {
         String::from_utf8(key.as_ref().to_vec()).unwrap_or_else(|_| "UNKNOWN".into())
     }
+}
+
+/// 账户状态trie使用的数据库
+///
+/// trie提交时写入的节点先保存在内存中的覆盖层里，读取时先查找覆盖层，再查找下层数据库。
+/// 下层是底层存储时，`persist`将覆盖层中的节点一次性写入存储；
+/// 下层是另一个覆盖层时（例如快照），节点只会在快照被采用时通过`merge_into_base`合并到下层，
+/// 丢弃快照时一并丢弃，不会写入共享的存储。
+/// trie节点按内容哈希寻址，同一个节点可能同时被当前状态、快照和历史区块的状态根引用，
+/// 因此提交时不删除被替换的节点，历史状态根始终可以重新打开
+#[derive(Debug)]
+pub(crate) struct TrieDb {
+    base: TrieDbBase,
+    nodes: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+}
 
-    /// 构建数据库的路径
-    fn path(database_name: &str) -> PathBuf {
-        Path::new(PATH).join(database_name)
+#[derive(Debug)]
+enum TrieDbBase {
+    Storage(Arc<Storage>),
+    Overlay(Arc<TrieDb>),
+}
+
+impl TrieDb {
+    /// 创建一个以底层存储为下层的数据库
+    pub(crate) fn new(storage: Arc<Storage>) -> Self {
+        Self {
+            base: TrieDbBase::Storage(storage),
+            nodes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 创建一个以`base`为下层的覆盖层，写入只保存在新的覆盖层中
+    pub(crate) fn overlay(base: &Arc<TrieDb>) -> Self {
+        Self {
+            base: TrieDbBase::Overlay(Arc::clone(base)),
+            nodes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 判断`base`是否为该数据库的下层
+    pub(crate) fn is_overlay_of(&self, base: &Arc<TrieDb>) -> bool {
+        matches!(&self.base, TrieDbBase::Overlay(overlay_base) if Arc::ptr_eq(overlay_base, base))
+    }
+
+    /// 最底层的存储
+    pub(crate) fn storage(&self) -> &Arc<Storage> {
+        match &self.base {
+            TrieDbBase::Storage(storage) => storage,
+            TrieDbBase::Overlay(base) => base.storage(),
+        }
+    }
+
+    /// 将覆盖层中的节点通过一个WriteBatch写入底层存储，下层是另一个覆盖层时不做任何事
+    pub(crate) fn persist(&self) -> Result<()> {
+        if let TrieDbBase::Storage(storage) = &self.base {
+            let (keys, values) = self.nodes.write()?.drain().unzip();
+            storage.insert_batch(keys, values)?;
+        }
+
+        Ok(())
+    }
+
+    /// 将覆盖层中的节点合并到下层的覆盖层
+    pub(crate) fn merge_into_base(&self) -> Result<()> {
+        if let TrieDbBase::Overlay(base) = &self.base {
+            let nodes = std::mem::take(&mut *self.nodes.write()?);
+            base.nodes.write()?.extend(nodes);
+        }
+
+        Ok(())
+    }
+}
+
+impl EthDB for TrieDb {
+    type Error = ChainError;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.nodes.read()?.get(key) {
+            return Ok(Some(value.clone()));
+        }
+
+        match &self.base {
+            TrieDbBase::Storage(storage) => storage.get(key),
+            TrieDbBase::Overlay(base) => base.get(key),
+        }
+    }
+
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<()> {
+        self.nodes.write()?.insert(key.to_vec(), value);
+
+        Ok(())
+    }
+
+    fn insert_batch(&self, keys: Vec<Vec<u8>>, values: Vec<Vec<u8>>) -> Result<()> {
+        self.nodes.write()?.extend(keys.into_iter().zip(values));
+
+        Ok(())
+    }
+
+    /// 被替换的节点可能仍被其他状态根引用，因此不删除
+    fn remove(&self, _key: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    /// 被替换的节点可能仍被其他状态根引用，因此不删除
+    fn remove_batch(&self, _keys: &[Vec<u8>]) -> Result<()> {
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        Ok(())
     }
 }
 
 // 测试模块，用于验证Storage结构体的功能
 #[cfg(test)]
 mod tests {
+    use super::{Storage, StorageOptions};
+    use crate::error::ChainError;
     use crate::helpers::{deserialize, serialize, tests::STORAGE};
     use eth_trie::DB;
+    use std::path::Path;
     use types::account::{Account, AccountData};
 
     // 测试数据库的创建
@@ -119,4 +370,102 @@ mod tests {
 
         assert_eq!(account_data, deserialize(&retrieved).unwrap());
     }
+
+    // 测试使用自定义选项打开数据库
+    #[test]
+    fn it_opens_a_db_with_custom_options() {
+        let options = StorageOptions {
+            block_cache_mb: 16,
+            ..Default::default()
+        };
+        let storage = Storage::new_with_options(Some("test_options"), options).unwrap();
+        let account = Account::random();
+        let account_data = AccountData::new(None);
+        storage
+            .insert(account.as_ref(), serialize(&account_data).unwrap())
+            .unwrap();
+
+        let retrieved = storage.get(account.as_ref()).unwrap().unwrap();
+
+        assert_eq!(account_data, deserialize(&retrieved).unwrap());
+    }
+
+    // 测试批量插入和删除键值对
+    #[test]
+    fn it_inserts_and_removes_in_a_batch() {
+        let keys = (0..10)
+            .map(|_| Account::random().as_ref().to_vec())
+            .collect::<Vec<_>>();
+        let values = keys.iter().map(|key| key.repeat(2)).collect::<Vec<_>>();
+
+        STORAGE.insert_batch(keys.clone(), values.clone()).unwrap();
+
+        for (key, value) in keys.iter().zip(values) {
+            assert_eq!(STORAGE.get(key).unwrap(), Some(value));
+        }
+
+        STORAGE.remove_batch(&keys).unwrap();
+
+        assert!(keys.iter().all(|key| STORAGE.get(key).unwrap().is_none()));
+    }
+
+    // 测试RocksDB的错误信息被保留在ChainError中
+    #[test]
+    fn it_preserves_rocksdb_error_messages() {
+        // Cargo.toml是一个文件，无法在其下创建数据库目录
+        let error = rocksdb::DB::open_default(Path::new("Cargo.toml").join("db")).unwrap_err();
+        let message = error.to_string();
+
+        assert_eq!(
+            ChainError::from(error),
+            ChainError::StorageError(message.clone())
+        );
+        assert_eq!(
+            Storage::open(Path::new("Cargo.toml"), Some("db")).unwrap_err(),
+            ChainError::StorageError(message)
+        );
+    }
+
+    // 测试按前缀迭代只返回匹配前缀的键值对
+    #[test]
+    fn it_iterates_by_prefix() {
+        let storage = Storage::new(Some("test_iter_prefix")).unwrap();
+        storage.insert(b"account:1", b"a1".to_vec()).unwrap();
+        storage.insert(b"account:2", b"a2".to_vec()).unwrap();
+        storage.insert(b"block:1", b"b1".to_vec()).unwrap();
+
+        let accounts = storage.iter_prefix(b"account:").unwrap();
+        let blocks = storage.iter_prefix(b"block:").unwrap();
+
+        assert_eq!(
+            accounts,
+            vec![
+                (b"account:1".to_vec().into(), b"a1".to_vec().into()),
+                (b"account:2".to_vec().into(), b"a2".to_vec().into()),
+            ]
+        );
+        assert_eq!(
+            blocks,
+            vec![(b"block:1".to_vec().into(), b"b1".to_vec().into())]
+        );
+        assert!(storage.iter_prefix(b"code:").unwrap().is_empty());
+    }
+
+    // 测试大量写入和删除后压缩数据库
+    #[test]
+    fn it_compacts_the_db() {
+        let keys = (0..1_000).map(|_| Account::random()).collect::<Vec<_>>();
+
+        for key in keys.iter() {
+            STORAGE.insert(key.as_ref(), vec![0; 128]).unwrap();
+        }
+
+        for key in keys.iter() {
+            STORAGE.remove(key.as_ref()).unwrap();
+        }
+
+        STORAGE.compact();
+
+        assert_eq!(STORAGE.get(keys[0].as_ref()).unwrap(), None);
+    }
 }
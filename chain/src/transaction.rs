@@ -1,8 +1,13 @@
+use crate::config::NodeConfig;
 use crate::error::{ChainError, Result};
+use crate::storage::Storage;
 
 use dashmap::DashMap;
-use ethereum_types::H256;
-use std::collections::VecDeque;
+use eth_trie::DB;
+use ethereum_types::{H256, U256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use types::account::Account;
 use types::transaction::{Transaction, TransactionReceipt};
 
 // 定义一个用于存储交易信息的结构体
@@ -12,6 +17,14 @@ pub(crate) struct TransactionStorage {
     pub(crate) mempool: VecDeque<Transaction>,
     // 存储交易哈希与其收据的映射
     pub(crate) receipts: DashMap<H256, TransactionReceipt>,
+    // 每个账户在交易池中的最高nonce
+    pub(crate) pending_nonces: HashMap<Account, U256>,
+    // 收据的插入顺序，超过上限时从最早的收据开始淘汰
+    receipt_order: VecDeque<H256>,
+    // 内存中保留的收据数量上限，为None时不限制
+    max_receipts: Option<usize>,
+    // 被淘汰的收据持久化到的存储，为None时直接丢弃
+    receipt_archive: Option<Arc<Storage>>,
 }
 
 impl TransactionStorage {
@@ -20,24 +33,166 @@ impl TransactionStorage {
         Self {
             mempool: VecDeque::new(),
             receipts: DashMap::new(),
+            pending_nonces: HashMap::new(),
+            receipt_order: VecDeque::new(),
+            max_receipts: None,
+            receipt_archive: None,
         }
     }
 
-    // 向交易池中发送一个交易
+    // 根据节点配置创建TransactionStorage实例，配置了持久化时被淘汰的收据写入给定的存储
+    pub(crate) fn from_config(config: &NodeConfig, storage: &Arc<Storage>) -> Self {
+        Self {
+            max_receipts: config.max_receipts,
+            receipt_archive: config.persist_evicted_receipts.then(|| Arc::clone(storage)),
+            ..Self::new()
+        }
+    }
+
+    // 收据在存储中的键，加上前缀避免与区块和trie节点的键冲突
+    fn receipt_key(hash: &H256) -> Vec<u8> {
+        [b"receipt:".as_slice(), hash.as_bytes()].concat()
+    }
+
+    // 保存一个交易收据，超过上限时淘汰最早的收据，配置了持久化时将其写入存储
+    pub(crate) fn insert_receipt(&mut self, receipt: TransactionReceipt) -> Result<()> {
+        let hash = receipt.transaction_hash;
+
+        if self.receipts.insert(hash, receipt).is_none() {
+            self.receipt_order.push_back(hash);
+        }
+
+        let max_receipts = self.max_receipts.unwrap_or(usize::MAX);
+
+        while self.receipt_order.len() > max_receipts {
+            let Some(evicted) = self.receipt_order.pop_front() else {
+                break;
+            };
+
+            if let (Some((_, receipt)), Some(archive)) = (
+                self.receipts.remove(&evicted),
+                self.receipt_archive.as_ref(),
+            ) {
+                let value = serde_json::to_vec(&receipt)
+                    .map_err(|e| ChainError::SerializeError(e.to_string()))?;
+                archive.insert(&Self::receipt_key(&evicted), value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // 向交易池中发送一个交易，并记录发送方在交易池中的最高nonce
     pub(crate) fn send_transaction(&mut self, transaction: Transaction) {
+        if let Some(nonce) = transaction.nonce {
+            let pending_nonce = self.pending_nonces.entry(transaction.from).or_default();
+            *pending_nonce = (*pending_nonce).max(nonce);
+        }
+
         self.mempool.push_back(transaction);
     }
 
-    // 根据交易哈希获取交易收据
+    // 获取账户在交易池中的最高nonce，交易池中没有该账户的交易时返回None
+    pub(crate) fn highest_pending_nonce(&self, account: &Account) -> Option<U256> {
+        self.pending_nonces.get(account).copied()
+    }
+
+    // 按gas价格从高到低重新排列交易池，同一账户的交易仍然按照nonce从小到大排列
+    //
+    // 每次从各账户尚未排列的nonce最小的交易中选出gas价格最高的一笔，
+    // gas价格相同时先进入交易池的交易排在前面
+    pub(crate) fn order_by_gas_price(&mut self) {
+        let mut queues: HashMap<Account, VecDeque<(usize, Transaction)>> = HashMap::new();
+
+        for (index, transaction) in self.mempool.drain(..).enumerate() {
+            queues
+                .entry(transaction.from)
+                .or_default()
+                .push_back((index, transaction));
+        }
+
+        for queue in queues.values_mut() {
+            queue
+                .make_contiguous()
+                .sort_by_key(|(index, transaction)| (transaction.nonce, *index));
+        }
+
+        loop {
+            let next = queues
+                .iter()
+                .filter_map(|(sender, queue)| queue.front().map(|head| (sender, head)))
+                .min_by_key(|(_, (index, transaction))| {
+                    (std::cmp::Reverse(transaction.gas_price), *index)
+                })
+                .map(|(sender, _)| *sender);
+
+            let Some(sender) = next else {
+                break;
+            };
+
+            if let Some((_, transaction)) = queues.get_mut(&sender).and_then(VecDeque::pop_front) {
+                self.mempool.push_back(transaction);
+            }
+        }
+    }
+
+    // 按gas价格从高到低取出最多count笔交易，且gas消耗之和不超过gas_limit，
+    // 并根据剩余的交易重新计算各账户的最高nonce
+    pub(crate) fn take_transactions(
+        &mut self,
+        count: usize,
+        gas_limit: U256,
+    ) -> VecDeque<Transaction> {
+        self.order_by_gas_price();
+
+        // 按顺序取出交易，直到数量达到上限或下一笔交易的gas消耗会超过区块的gas上限，
+        // 放不下的交易留在交易池中，保持同一账户的nonce顺序
+        let mut gas_used = U256::zero();
+        let count = self
+            .mempool
+            .iter()
+            .take(count)
+            .take_while(|transaction| {
+                gas_used = gas_used.saturating_add(transaction.gas_used());
+                gas_used <= gas_limit
+            })
+            .count();
+        let transactions = self.mempool.drain(0..count).collect::<VecDeque<_>>();
+
+        self.pending_nonces.clear();
+        for transaction in self.mempool.iter() {
+            if let Some(nonce) = transaction.nonce {
+                let pending_nonce = self.pending_nonces.entry(transaction.from).or_default();
+                *pending_nonce = (*pending_nonce).max(nonce);
+            }
+        }
+
+        transactions
+    }
+
+    // 清空交易池，同时清除各账户的最高nonce，返回丢弃的交易数量
+    pub(crate) fn clear_mempool(&mut self) -> usize {
+        let num_transactions = self.mempool.len();
+
+        self.mempool.clear();
+        self.pending_nonces.clear();
+
+        num_transactions
+    }
+
+    // 根据交易哈希获取交易收据，内存中没有时再从被淘汰收据的存储中查找
     pub(crate) fn get_transaction_receipt(&self, hash: &H256) -> Result<TransactionReceipt> {
-        let transaction_receipt = self
-            .receipts
-            .get(hash)
-            .ok_or_else(|| ChainError::TransactionNotFound(hash.to_string()))?
-            .value()
-            .clone();
+        if let Some(transaction_receipt) = self.receipts.get(hash) {
+            return Ok(transaction_receipt.value().clone());
+        }
+
+        let archived = match self.receipt_archive.as_ref() {
+            Some(archive) => archive.get(&Self::receipt_key(hash))?,
+            None => None,
+        };
+        let value = archived.ok_or_else(|| ChainError::TransactionNotFound(hash.to_string()))?;
 
-        Ok(transaction_receipt)
+        serde_json::from_slice(&value).map_err(|e| ChainError::DeserializeError(e.to_string()))
     }
 }
 
@@ -45,10 +200,10 @@ impl TransactionStorage {
 #[cfg(test)]
 mod tests {
     use crate::blockchain::tests::{assert_receipt, new_transaction};
-    use crate::helpers::tests::setup;
+    use crate::helpers::tests::{setup, STORAGE};
+    use ethereum_types::U64;
 
     use super::*;
-    use types::account::Account;
 
     // 测试发送交易功能
     #[tokio::test]
@@ -80,4 +235,106 @@ mod tests {
 
         assert_receipt(blockchain, transaction_hash).await;
     }
+
+    // 测试取出交易后根据剩余交易更新最高nonce
+    #[tokio::test]
+    async fn tracks_the_highest_pending_nonce() {
+        let (blockchain, from, _) = setup().await;
+        let mut transaction_storage = TransactionStorage::new();
+        let transaction_1 = new_transaction(Account::random(), blockchain.clone()).await;
+        let mut transaction_2 = transaction_1.clone();
+        transaction_2.nonce = transaction_1.nonce.map(|nonce| nonce + 1);
+        assert_eq!(transaction_storage.highest_pending_nonce(&from), None);
+
+        transaction_storage.send_transaction(transaction_1.clone());
+        transaction_storage.send_transaction(transaction_2.clone());
+        assert_eq!(
+            transaction_storage.highest_pending_nonce(&from),
+            transaction_2.nonce
+        );
+
+        transaction_storage.take_transactions(2, U256::MAX);
+        assert_eq!(transaction_storage.highest_pending_nonce(&from), None);
+    }
+
+    // 测试交易池按gas价格从高到低排列，同一账户的交易保持nonce顺序
+    #[tokio::test]
+    async fn orders_transactions_by_gas_price_within_nonce_order() {
+        let (blockchain, _, _) = setup().await;
+        let mut transaction_storage = TransactionStorage::new();
+        let mut low_fee = new_transaction(Account::random(), blockchain.clone()).await;
+        low_fee.gas_price = U256::from(1);
+        let mut high_fee = low_fee.clone();
+        high_fee.from = Account::random();
+        high_fee.gas_price = U256::from(100);
+        let mut next_nonce = low_fee.clone();
+        next_nonce.nonce = low_fee.nonce.map(|nonce| nonce + 1);
+        next_nonce.gas_price = U256::from(1_000);
+
+        transaction_storage.send_transaction(next_nonce.clone());
+        transaction_storage.send_transaction(low_fee.clone());
+        transaction_storage.send_transaction(high_fee.clone());
+
+        assert_eq!(
+            transaction_storage.take_transactions(3, U256::MAX),
+            VecDeque::from([high_fee, low_fee, next_nonce])
+        );
+    }
+
+    // 测试取出的交易的gas消耗之和不超过区块的gas上限
+    #[tokio::test]
+    async fn takes_transactions_up_to_the_gas_limit() {
+        let (blockchain, _, _) = setup().await;
+        let mut transaction_storage = TransactionStorage::new();
+        let transaction_1 = new_transaction(Account::random(), blockchain.clone()).await;
+        let mut transaction_2 = transaction_1.clone();
+        transaction_2.nonce = transaction_1.nonce.map(|nonce| nonce + 1);
+        let gas_limit = transaction_1.gas_used() + transaction_2.gas_used() - 1;
+
+        transaction_storage.send_transaction(transaction_1.clone());
+        transaction_storage.send_transaction(transaction_2.clone());
+
+        assert_eq!(
+            transaction_storage.take_transactions(2, gas_limit),
+            VecDeque::from([transaction_1])
+        );
+        assert_eq!(transaction_storage.mempool, VecDeque::from([transaction_2]));
+    }
+
+    // 测试收据数量超过上限时淘汰最早的收据，并可以从存储中取回
+    #[tokio::test]
+    async fn caps_the_number_of_receipts() {
+        let config = NodeConfig {
+            max_receipts: Some(2),
+            persist_evicted_receipts: true,
+            ..NodeConfig::default()
+        };
+        let mut transaction_storage = TransactionStorage::from_config(&config, &STORAGE);
+        let hashes = (0..5).map(|_| H256::random()).collect::<Vec<_>>();
+
+        for hash in hashes.iter() {
+            transaction_storage
+                .insert_receipt(TransactionReceipt {
+                    block_hash: None,
+                    block_number: None,
+                    contract_address: None,
+                    transaction_hash: *hash,
+                    logs: vec![],
+                    status: U64::one(),
+                    cumulative_gas_used: U256::zero(),
+                    effective_gas_price: U256::zero(),
+                    revert_reason: None,
+                })
+                .unwrap();
+        }
+
+        assert_eq!(transaction_storage.receipts.len(), 2);
+        assert!(!transaction_storage.receipts.contains_key(&hashes[0]));
+        assert!(transaction_storage.receipts.contains_key(&hashes[4]));
+
+        let evicted = transaction_storage
+            .get_transaction_receipt(&hashes[0])
+            .unwrap();
+        assert_eq!(evicted.transaction_hash, hashes[0]);
+    }
 }
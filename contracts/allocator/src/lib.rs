@@ -0,0 +1,14 @@
+wit_bindgen::generate!("allocator");
+
+pub struct Allocator;
+
+export_contract!(Allocator);
+
+impl Contract for Allocator {
+    /// 分配指定字节数的内存并返回分配的大小，用于验证运行时的内存限制
+    fn allocate(size: u64) -> u64 {
+        let data = std::hint::black_box(vec![1u8; size as usize]);
+
+        data.len() as u64
+    }
+}
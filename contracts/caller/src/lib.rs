@@ -0,0 +1,25 @@
+wit_bindgen::generate!("caller");
+
+pub struct Caller;
+
+export_contract!(Caller);
+
+impl Contract for Caller {
+    /// 将调用转发给另一个合约，参数与erc20的`mint`/`transfer`一致
+    fn forward(address: String, function: String, account: String, amount: u64) {
+        let amount = amount.to_string();
+        let params = ["String", account.as_str(), "U64", amount.as_str()];
+
+        call(&address, &function, &params);
+
+        println!(
+            "forward called successfully, params: [String, {}, String, {}, String, {}, U64, {}]",
+            address, function, account, amount
+        );
+    }
+
+    /// 通过宿主递归调用自身，用于验证调用深度限制
+    fn recurse(address: String) {
+        call(&address, "recurse", &["String", address.as_str()]);
+    }
+}
@@ -0,0 +1,27 @@
+wit_bindgen::generate!("environment");
+
+pub struct Environment;
+
+export_contract!(Environment);
+
+impl Contract for Environment {
+    /// 返回合约执行时所在区块的区块号
+    fn get_block_number() -> u64 {
+        block_number()
+    }
+
+    /// 返回合约执行时所在区块的时间戳
+    fn get_block_timestamp() -> u64 {
+        block_timestamp()
+    }
+
+    /// 返回调用合约的账户地址
+    fn get_caller() -> String {
+        caller()
+    }
+
+    /// 返回随调用转入合约的金额
+    fn get_value() -> u64 {
+        value()
+    }
+}
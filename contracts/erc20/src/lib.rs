@@ -1,37 +1,123 @@
-use std::collections::HashMap;
-
 wit_bindgen::generate!("erc20");
 
 pub struct Erc20;
 
-#[allow(dead_code)]
-pub struct State {
-    name: String,
-    symbol: String,
-    balances: HashMap<String, u64>,
+/// 合约状态，通过宿主提供的`sload`/`sstore`持久化在合约账户的存储中，
+/// 因此在多次调用（以及多笔交易）之间保持
+pub struct State;
+
+impl State {
+    const NAME: &'static str = "name";
+    const SYMBOL: &'static str = "symbol";
+    /// 铸币时作为转出方记录在日志中的零地址
+    const ZERO_ADDRESS: &'static str = "0x0000000000000000000000000000000000000000";
+
+    /// 账户余额在存储中的键
+    fn balance_key(account: &str) -> String {
+        format!("balances/{}", account)
+    }
+
+    fn set_metadata(name: &str, symbol: &str) {
+        sstore(Self::NAME, name.as_bytes());
+        sstore(Self::SYMBOL, symbol.as_bytes());
+    }
+
+    fn balance_of(account: &str) -> u64 {
+        sload(&Self::balance_key(account))
+            .and_then(|value| value.try_into().ok())
+            .map(u64::from_le_bytes)
+            .unwrap_or_default()
+    }
+
+    fn set_balance(account: &str, balance: u64) {
+        sstore(&Self::balance_key(account), &balance.to_le_bytes());
+    }
+
+    /// 增加账户的余额，溢出时返回false且不修改状态
+    fn credit(account: &str, amount: u64) -> bool {
+        match Self::balance_of(account).checked_add(amount) {
+            Some(balance) => {
+                Self::set_balance(account, balance);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 减少账户的余额，余额不足时返回false且不修改状态
+    fn debit(account: &str, amount: u64) -> bool {
+        match Self::balance_of(account).checked_sub(amount) {
+            Some(balance) => {
+                Self::set_balance(account, balance);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 发出一条`Transfer`日志，主题为补零到32字节的事件名，数据为`from:to:amount`
+    fn emit_transfer(from: &str, to: &str, amount: u64) {
+        let mut topic = [0; 32];
+        topic[..8].copy_from_slice(b"Transfer");
+
+        log(&[&topic], format!("{}:{}:{}", from, to, amount).as_bytes());
+    }
 }
 
 export_contract!(Erc20);
 
 impl Contract for Erc20 {
     fn construct(name: String, symbol: String) {
+        State::set_metadata(&name, &symbol);
+
         println!(
             "construct called successfully, params: [ String, {}, String, {}]",
             name, symbol
         );
     }
 
-    fn mint(account: String, amount: u64) {
+    fn mint(account: String, amount: u64) -> bool {
         println!(
             "mint called successfully, params: [String, {}, U64, {}]",
             account, amount
         );
+
+        let minted = State::credit(&account, amount);
+
+        if minted {
+            State::emit_transfer(State::ZERO_ADDRESS, &account, amount);
+        }
+
+        minted
     }
 
-    fn transfer(to: String, amount: u64) {
+    /// 从调用方的余额中转出`amount`给`to`，余额不足或接收方余额溢出时返回false
+    fn transfer(to: String, amount: u64) -> bool {
+        let from = caller();
+
         println!(
-            "transfer called successfully, params: [String, {}, U64, {}]",
-            to, amount
+            "transfer called successfully, params: [String, {}, U64, {}], caller: {}",
+            to, amount, from
         );
+
+        if from == to {
+            return State::balance_of(&from) >= amount;
+        }
+
+        if State::balance_of(&to).checked_add(amount).is_none() {
+            return false;
+        }
+
+        let transferred = State::debit(&from, amount) && State::credit(&to, amount);
+
+        if transferred {
+            State::emit_transfer(&from, &to, amount);
+        }
+
+        transferred
+    }
+
+    fn balance_of(account: String) -> u64 {
+        State::balance_of(&account)
     }
 }
@@ -0,0 +1,17 @@
+wit_bindgen::generate!("reverter");
+
+pub struct Reverter;
+
+export_contract!(Reverter);
+
+impl Contract for Reverter {
+    /// 以给定的原因回滚执行
+    fn fail(reason: String) {
+        revert(&reason);
+    }
+
+    /// 触发合约panic，运行时将其视为回滚
+    fn trap() {
+        panic!("trap called");
+    }
+}
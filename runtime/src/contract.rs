@@ -1,52 +1,386 @@
 use crate::error::{Result, RuntimeError};
+use crate::host::{Context, ContractLog, Host, MemoryHost, TraceStep, MAX_LOG_TOPICS};
+use std::path::Path;
+use std::sync::OnceLock;
 use tracing::trace;
 use wasmtime::{
     self,
     component::{Component, Instance, Linker, Val},
-    Config, Engine, Store,
+    AsContextMut, Config, Engine, Store, StoreContextMut, Trap,
 };
-use wit_component::ComponentEncoder;
+use wit_component::{ComponentEncoder, DecodedWasm};
+use wit_parser::{Resolve, Type, TypeDefKind, WorldId, WorldItem};
 
-/// 加载WebAssembly合约
+/// 从文件中读取WebAssembly合约的字节码
 ///
-/// 该函数接受一个字节切片作为输入，尝试将这些字节作为WebAssembly模块进行解析和加载。
-/// 它首先配置WebAssembly引擎，然后创建一个存储和链接器，最后实例化WebAssembly模块。
+/// 与编译期的`include_bytes!`不同，文件不存在时返回错误而不是无法编译，
+/// 读取到的字节码可以传给`call_function`等函数执行
 ///
 /// # 参数
 ///
-/// * `bytes`: &[u8] - WebAssembly模块的字节表示。
+/// * `path`: 合约文件的路径，例如`target/wasm32-unknown-unknown/release/erc20.wasm`
 ///
 /// # 返回
 ///
-/// * `Result<(Store<i32>, Instance)>` - 返回一个结果类型，包含WebAssembly存储和实例。
-fn load_contract(bytes: &[u8]) -> Result<(Store<i32>, Instance)> {
-    // 创建并配置WebAssembly配置对象
-    let mut config = Config::new();
+/// * `Result<Vec<u8>>` - 合约的字节码；文件不存在时返回`RuntimeError::ContractNotFound`
+pub fn load_contract_from_path<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
+    let path = path.as_ref();
 
-    // 启用WebAssembly组件模型
-    Config::wasm_component_model(&mut config, true);
-
-    // 根据配置创建WebAssembly引擎
-    let engine = Engine::new(&config)?;
-    // 创建WebAssembly存储，初始值为0
-    let mut store = Store::new(&engine, 0);
-    // 创建WebAssembly链接器
-    let linker = Linker::new(&engine);
+    std::fs::read(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => RuntimeError::ContractNotFound(path.display().to_string()),
+        _ => RuntimeError::ContractReadError(path.display().to_string(), e.to_string()),
+    })
+}
 
-    // 将字节编码为WebAssembly组件
+/// 将合约的核心Wasm模块编码为WebAssembly组件
+///
+/// 合约由wit-bindgen生成，模块中的自定义段记录了WIT接口，编码时据此生成组件的类型
+fn encode_component(bytes: &[u8]) -> Result<Vec<u8>> {
     let component_bytes = ComponentEncoder::default()
         .module(bytes)?
         .validate(true)
         .encode()?;
+
+    Ok(component_bytes)
+}
+
+/// 链接器为合约提供的宿主函数，合约只能导入这些函数
+///
+/// `link_host_functions`按这张表注册宿主函数，新增宿主函数时只需在表中加入名称并实现对应的分支
+pub const HOST_FUNCTIONS: &[&str] = &[
+    "call",
+    "sload",
+    "sstore",
+    "log",
+    "block-number",
+    "block-timestamp",
+    "caller",
+    "value",
+    "revert",
+];
+
+/// 将合约编码为组件后解码出其WIT接口
+fn decode_contract(bytes: &[u8]) -> Result<(Resolve, WorldId)> {
+    let component_bytes = encode_component(bytes)?;
+    let decoded = wit_component::decode("contract", &component_bytes)
+        .map_err(|e| RuntimeError::ContractDecodeError(e.to_string()))?;
+
+    match decoded {
+        DecodedWasm::Component(resolve, world) => Ok((resolve, world)),
+        DecodedWasm::WitPackage(..) => Err(RuntimeError::ContractDecodeError(
+            "expected a component, found a WIT package".into(),
+        )),
+    }
+}
+
+/// 获取合约导入的所有宿主函数的名称，按WIT中声明的顺序排列
+///
+/// # 参数
+///
+/// * `bytes`: 合约的字节码
+///
+/// # 返回
+///
+/// * `Result<Vec<String>>` - 合约执行时需要宿主提供的导入
+pub fn component_imports(bytes: &[u8]) -> Result<Vec<String>> {
+    let (resolve, world) = decode_contract(bytes)?;
+
+    Ok(resolve.worlds[world].imports.keys().cloned().collect())
+}
+
+/// 检查合约的导入是否都是宿主支持的函数，存在不支持的导入时返回`RuntimeError::UnsupportedImports`
+pub fn check_imports(bytes: &[u8]) -> Result<()> {
+    let unsupported = component_imports(bytes)?
+        .into_iter()
+        .filter(|import| !HOST_FUNCTIONS.contains(&import.as_str()))
+        .collect::<Vec<_>>();
+
+    if !unsupported.is_empty() {
+        return Err(RuntimeError::UnsupportedImports(unsupported.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// 获取合约导出的所有函数及其参数类型，按WIT中声明的顺序排列
+///
+/// 参数类型使用调用合约时的类型名称，例如`String`、`U64`，
+/// 调用方可以据此构造`call_function`的参数
+///
+/// # 参数
+///
+/// * `bytes`: 合约的字节码
+///
+/// # 返回
+///
+/// * `Result<Vec<(String, Vec<String>)>>` - 每个导出函数的名称和参数类型名称
+pub fn contract_function_signatures(bytes: &[u8]) -> Result<Vec<(String, Vec<String>)>> {
+    let (resolve, world) = decode_contract(bytes)?;
+    let signatures = resolve.worlds[world]
+        .exports
+        .values()
+        .filter_map(|item| match item {
+            WorldItem::Function(function) => Some((
+                function.name.clone(),
+                function
+                    .params
+                    .iter()
+                    .map(|(_, ty)| type_name(&resolve, ty))
+                    .collect(),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    Ok(signatures)
+}
+
+/// 将WIT类型转换为调用合约时使用的类型名称
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Bool => "Bool".into(),
+        Type::U8 => "U8".into(),
+        Type::U16 => "U16".into(),
+        Type::U32 => "U32".into(),
+        Type::U64 => "U64".into(),
+        Type::S8 => "S8".into(),
+        Type::S16 => "S16".into(),
+        Type::S32 => "S32".into(),
+        Type::S64 => "S64".into(),
+        Type::Float32 => "Float32".into(),
+        Type::Float64 => "Float64".into(),
+        Type::Char => "Char".into(),
+        Type::String => "String".into(),
+        Type::Id(id) => {
+            let type_def = &resolve.types[*id];
+
+            match &type_def.kind {
+                TypeDefKind::List(ty) => format!("List<{}>", type_name(resolve, ty)),
+                TypeDefKind::Option(ty) => format!("Option<{}>", type_name(resolve, ty)),
+                TypeDefKind::Type(ty) => type_name(resolve, ty),
+                kind => type_def
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("{:?}", kind)),
+            }
+        }
+    }
+}
+
+/// 获取共享的WebAssembly引擎
+///
+/// 创建引擎的开销较大，所有合约执行（包括嵌套的合约调用）复用同一个引擎，
+/// fuel计量是引擎级别的配置，因此跟踪模式和限制fuel的执行使用另一个开启了fuel计量的引擎
+fn engine(consume_fuel: bool) -> Result<Engine> {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    static FUEL_ENGINE: OnceLock<Engine> = OnceLock::new();
+
+    let cell = if consume_fuel { &FUEL_ENGINE } else { &ENGINE };
+    if let Some(engine) = cell.get() {
+        return Ok(engine.clone());
+    }
+
+    // 启用WebAssembly组件模型
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    config.consume_fuel(consume_fuel);
+    let engine = Engine::new(&config)?;
+
+    Ok(cell.get_or_init(|| engine).clone())
+}
+
+/// 加载WebAssembly合约
+///
+/// 该函数接受一个字节切片作为输入，尝试将这些字节作为WebAssembly模块进行解析和加载。
+/// 它首先获取共享的WebAssembly引擎，然后创建一个存储和链接器，最后实例化WebAssembly模块。
+///
+/// # 参数
+///
+/// * `bytes`: &[u8] - WebAssembly模块的字节表示。
+/// * `context`: Context<H> - 合约执行上下文，保存在存储中供宿主函数使用。
+///
+/// # 返回
+///
+/// * `Result<(Store<Context<H>>, Instance)>` - 返回一个结果类型，包含WebAssembly存储和实例。
+fn load_contract<H: Host + 'static>(
+    bytes: &[u8],
+    context: Context<H>,
+) -> Result<(Store<Context<H>>, Instance)> {
+    // 跟踪模式下开启fuel计量，用于记录每一步消耗的fuel，设置了fuel上限时同样需要计量
+    let max_fuel = context.limiter.limits.max_fuel;
+    let metered = context.trace.is_some() || max_fuel.is_some();
+    let engine = engine(metered)?;
+    // 创建WebAssembly存储，保存合约执行上下文，并按上下文中的资源限制约束内存和表的大小
+    let mut store = Store::new(&engine, context);
+    store.limiter(|context| &mut context.limiter);
+    if metered {
+        store.add_fuel(max_fuel.unwrap_or(u64::MAX))?;
+    }
+    // 创建WebAssembly链接器，并注册宿主函数
+    let mut linker = Linker::new(&engine);
+    link_host_functions(&mut linker)?;
+
+    // 将字节编码为WebAssembly组件
+    let component_bytes = encode_component(bytes)?;
     // 从二进制创建WebAssembly组件
     let component = Component::from_binary(&engine, &component_bytes)?;
-    // 实例化WebAssembly组件
-    let instance = linker.instantiate(&mut store, &component)?;
+    // 实例化WebAssembly组件，初始内存超过限制时返回`RuntimeError::ResourceLimitExceeded`
+    let instance = linker.instantiate(&mut store, &component).map_err(|e| {
+        match store.data_mut().limiter.take_exceeded() {
+            Some(error) => error,
+            None => e.into(),
+        }
+    })?;
 
     // 返回WebAssembly存储和实例
     Ok((store, instance))
 }
 
+/// 在跟踪模式下记录一步执行，附带到目前为止消耗的fuel，未开启跟踪时不做任何事
+fn record_step<H>(
+    mut store: impl AsContextMut<Data = Context<H>>,
+    operation: impl FnOnce() -> String,
+) {
+    let mut store = store.as_context_mut();
+    let fuel_consumed = store.fuel_consumed().unwrap_or_default();
+    let depth = store.data().call_depth.depth;
+
+    if let Some(trace) = store.data_mut().trace.as_mut() {
+        trace.push(TraceStep {
+            depth,
+            operation: operation(),
+            fuel_consumed,
+        });
+    }
+}
+
+/// 向链接器注册合约可以导入的宿主函数
+///
+/// - `call`: 调用另一个合约中的函数，由上下文中的宿主完成实际的调用
+/// - `sload`/`sstore`: 读写当前合约的持久化存储
+/// - `log`: 发出一条日志，最多`MAX_LOG_TOPICS`个主题，每个主题固定为32字节
+/// - `block-number`/`block-timestamp`/`caller`/`value`: 读取上下文中的执行环境
+/// - `revert`: 以给定的原因中止执行，返回`RuntimeError::Revert`
+fn link_host_functions<H: Host + 'static>(linker: &mut Linker<Context<H>>) -> Result<()> {
+    let mut root = linker.root();
+
+    // 按`HOST_FUNCTIONS`逐个注册，表中的函数没有实现时返回错误，
+    // 因此链接的宿主函数与`check_imports`接受的导入始终一致
+    for &name in HOST_FUNCTIONS {
+        match name {
+            "call" => root.func_wrap(
+                name,
+                |mut store: StoreContextMut<'_, Context<H>>,
+                 (address, function, params): (String, String, Vec<String>)| {
+                    trace!(
+                        "Calling {} on {} with params {:?}",
+                        function,
+                        address,
+                        params
+                    );
+                    record_step(&mut store, || format!("call {} on {}", function, address));
+                    // 进入下一层调用，超过最大调用深度时中止
+                    let call_depth = store.data().call_depth.enter()?;
+                    // 被调用合约沿用当前合约的资源限制，看到的调用方为当前合约
+                    let limits = store.data().limiter.limits;
+                    let env = store.data().env.call(&address);
+                    store
+                        .data_mut()
+                        .host
+                        .call(call_depth, limits, env, &address, &function, &params)?;
+
+                    Ok(())
+                },
+            )?,
+            "sload" => root.func_wrap(
+                name,
+                |mut store: StoreContextMut<'_, Context<H>>, (key,): (String,)| {
+                    trace!("Loading storage key {}", key);
+                    record_step(&mut store, || format!("sload {}", key));
+                    let value = store.data_mut().host.sload(&key)?;
+
+                    Ok((value,))
+                },
+            )?,
+            "sstore" => root.func_wrap(
+                name,
+                |mut store: StoreContextMut<'_, Context<H>>, (key, value): (String, Vec<u8>)| {
+                    trace!("Storing storage key {}", key);
+                    record_step(&mut store, || format!("sstore {}", key));
+                    store.data_mut().host.sstore(&key, &value)?;
+
+                    Ok(())
+                },
+            )?,
+            "log" => root.func_wrap(
+                name,
+                |mut store: StoreContextMut<'_, Context<H>>,
+                 (topics, data): (Vec<Vec<u8>>, Vec<u8>)| {
+                    trace!("Logging {} topics", topics.len());
+                    record_step(&mut store, || format!("log {} topics", topics.len()));
+
+                    if topics.len() > MAX_LOG_TOPICS {
+                        return Err(RuntimeError::HostError(format!(
+                            "a log can have at most {} topics, got {}",
+                            MAX_LOG_TOPICS,
+                            topics.len()
+                        ))
+                        .into());
+                    }
+
+                    let topics = topics
+                        .into_iter()
+                        .map(|topic| {
+                            <[u8; 32]>::try_from(topic.as_slice()).map_err(|_| {
+                                RuntimeError::HostError(format!(
+                                    "a log topic must be 32 bytes, got {}",
+                                    topic.len()
+                                ))
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    store.data_mut().host.log(ContractLog { topics, data })?;
+
+                    Ok(())
+                },
+            )?,
+            "block-number" => {
+                root.func_wrap(name, |store: StoreContextMut<'_, Context<H>>, (): ()| {
+                    Ok((store.data().env.block_number,))
+                })?
+            }
+            "block-timestamp" => {
+                root.func_wrap(name, |store: StoreContextMut<'_, Context<H>>, (): ()| {
+                    Ok((store.data().env.block_timestamp,))
+                })?
+            }
+            "caller" => {
+                root.func_wrap(name, |store: StoreContextMut<'_, Context<H>>, (): ()| {
+                    Ok((store.data().env.caller.clone(),))
+                })?
+            }
+            "value" => root.func_wrap(name, |store: StoreContextMut<'_, Context<H>>, (): ()| {
+                Ok((store.data().env.value,))
+            })?,
+            "revert" => root.func_wrap(
+                name,
+                |_store: StoreContextMut<'_, Context<H>>, (reason,): (String,)| {
+                    trace!("Reverting with reason {}", reason);
+                    Err::<(), _>(RuntimeError::Revert { reason }.into())
+                },
+            )?,
+            _ => {
+                return Err(RuntimeError::HostError(format!(
+                    "host function {} is not implemented",
+                    name
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// 解析参数字符串并将其转换为指定类型的值
 ///
 /// 此函数根据提供的字符串切片确定预期的类型和值
@@ -71,6 +405,92 @@ fn parse_params(chunk: &[&str]) -> Result<Val> {
         _ => Err(RuntimeError::InvalidParamType(chunk[0].into())),
     }
 }
+
+/// 已加载的Wasm合约实例
+///
+/// 同一个实例上可以依次调用多个函数，合约在实例内存中的状态在这些调用之间保持
+pub struct ContractInstance<H> {
+    store: Store<Context<H>>,
+    instance: Instance,
+}
+
+impl<H: Host + 'static> ContractInstance<H> {
+    /// 加载Wasm合约并创建实例
+    pub fn new(bytes: &[u8], context: Context<H>) -> Result<Self> {
+        let (store, instance) = load_contract(bytes, context)?;
+
+        Ok(Self { store, instance })
+    }
+
+    /// 调用合约实例中的指定函数
+    ///
+    /// # Parameters
+    ///
+    /// - `function`: &str类型，要调用的函数名
+    /// - `params`: &[&str]类型，函数调用参数列表，每两个元素表示一个键值对
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Val>>`: 调用成功时返回函数的返回值；如果失败，返回错误类型
+    pub fn call(&mut self, function: &str, params: &[&str]) -> Result<Vec<Val>> {
+        // 解析参数，每两个元素表示一个键值对，并将它们转换为函数所需的格式
+        let parsed: Result<Vec<Val>> = params.chunks_exact(2).map(parse_params).collect();
+
+        // 记录函数名和解析后的参数
+        tracing::info!("{} params {:?}", function, parsed);
+
+        record_step(&mut self.store, || format!("enter {}", function));
+
+        // 获取指定名称的函数导出
+        let func = self
+            .instance
+            .get_func(&mut self.store, function)
+            .ok_or_else(|| RuntimeError::ExportFunctionError(function.into()))?;
+
+        // 按照函数签名准备返回值的位置，调用时会被实际的返回值覆盖
+        let mut results = vec![Val::Bool(false); func.results(&self.store).len()];
+
+        // 调用函数，并处理可能的错误，宿主函数返回的运行时错误保持原样向上传递，
+        // 超过资源限制（包括耗尽fuel）导致的失败返回`RuntimeError::ResourceLimitExceeded`，
+        // 合约执行中的其他trap（例如合约panic）视为回滚
+        let result = func.call(&mut self.store, &parsed?, &mut results);
+        let exceeded = self.store.data_mut().limiter.take_exceeded();
+        let max_fuel = self.store.data().limiter.limits.max_fuel;
+        result.map_err(|e| match (exceeded, e.downcast::<RuntimeError>()) {
+            (_, Ok(error)) => error,
+            (Some(error), Err(_)) => error,
+            (None, Err(e)) => match e.downcast_ref::<Trap>() {
+                Some(Trap::OutOfFuel) => RuntimeError::ResourceLimitExceeded(format!(
+                    "fuel limit of {} exhausted",
+                    max_fuel.unwrap_or(u64::MAX)
+                )),
+                Some(trap) => RuntimeError::Revert {
+                    reason: trap.to_string(),
+                },
+                None => RuntimeError::CallFunctionError(e.to_string()),
+            },
+        })?;
+
+        // 完成调用后的清理，之后才能在同一个实例上进行下一次调用
+        func.post_return(&mut self.store)?;
+        record_step(&mut self.store, || format!("exit {}", function));
+
+        tracing::info!(
+            "{:?} called successfully, params: {:?}, results: {:?}",
+            function,
+            params,
+            results
+        );
+
+        Ok(results)
+    }
+
+    /// 销毁实例并取回执行上下文
+    pub fn into_context(self) -> Context<H> {
+        self.store.into_data()
+    }
+}
+
 /// 调用Wasm合约中的指定函数
 ///
 /// 此函数负责加载Wasm合约，解析参数，并调用指定的函数
@@ -84,41 +504,107 @@ fn parse_params(chunk: &[&str]) -> Result<Val> {
 ///
 /// # Returns
 ///
-/// - `Result<()>`: 表示函数调用是否成功如果成功，返回Ok(())；如果失败，返回错误类型
-pub fn call_function(bytes: &[u8], function: &str, params: &[&str]) -> Result<()> {
-    // 加载Wasm合约
-    let (mut store, instance) = load_contract(bytes)?;
-
-    // 解析参数，每两个元素表示一个键值对，并将它们转换为函数所需的格式
-    let parsed: Result<Vec<Val>> = params.chunks_exact(2).map(parse_params).collect();
-
-    // 记录函数名和解析后的参数
-    tracing::info!("{} params {:?}", function, parsed);
+/// - `Result<Vec<Val>>`: 调用成功时返回函数的返回值；如果失败，返回错误类型
+pub fn call_function(bytes: &[u8], function: &str, params: &[&str]) -> Result<Vec<Val>> {
+    let (results, _) =
+        call_function_in_context(bytes, function, params, Context::new(MemoryHost::default()))?;
 
-    // 获取指定名称的函数导出
-    let func = instance
-        .get_func(&mut store, function)
-        .ok_or_else(|| RuntimeError::ExportFunctionError(function.into()))?;
-
-    // 调用函数，并处理可能的错误
-    let r = func
-        .call(&mut store, &parsed?, &mut [])
-        .map_err(|e| RuntimeError::CallFunctionError(e.to_string()));
+    Ok(results)
+}
 
-    if r.is_ok() {
-        tracing::info!("{:?} called successfully, params: {:?}", function, params);
-    }
+/// 在给定的执行上下文中调用Wasm合约中的指定函数
+///
+/// 与`call_function`相同，但合约可以通过上下文中的宿主调用其他合约，
+/// 调用结束后返回上下文，以便宿主取回执行过程中产生的状态
+///
+/// # Parameters
+///
+/// - `bytes`: &[u8]类型，Wasm合约的字节码
+/// - `function`: &str类型，要调用的函数名
+/// - `params`: &[&str]类型，函数调用参数列表，每两个元素表示一个键值对
+/// - `context`: Context<H>类型，合约执行上下文
+///
+/// # Returns
+///
+/// - `Result<(Vec<Val>, Context<H>)>`: 调用成功时返回函数的返回值和执行后的上下文；如果失败，返回错误类型
+pub fn call_function_in_context<H: Host + 'static>(
+    bytes: &[u8],
+    function: &str,
+    params: &[&str],
+    context: Context<H>,
+) -> Result<(Vec<Val>, Context<H>)> {
+    let mut contract = ContractInstance::new(bytes, context)?;
+    let results = contract.call(function, params)?;
 
-    r
+    Ok((results, contract.into_context()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::host::{CallDepth, Environment, ResourceLimits, DEFAULT_MAX_CALL_DEPTH};
+    use std::collections::HashMap;
     use test_log::test;
     use types::account::Account;
 
     const PARAMS_1: &[&str] = &["String", "Rust Coin", "String", "RustCoin"];
+    const ERC20: &[u8] = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20.wasm");
+    const ALLOCATOR: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/allocator.wasm");
+    const CALLER: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/caller.wasm");
+    const ENVIRONMENT: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/environment.wasm");
+    const REVERTER: &[u8] =
+        include_bytes!("./../../target/wasm32-unknown-unknown/release/reverter.wasm");
+
+    /// 测试用的宿主，按地址分发合约调用并记录调用历史
+    #[derive(Clone)]
+    struct TestHost {
+        contracts: HashMap<String, &'static [u8]>,
+        calls: Vec<(String, String)>,
+        storage: MemoryHost,
+    }
+
+    impl Host for TestHost {
+        fn call(
+            &mut self,
+            call_depth: CallDepth,
+            limits: ResourceLimits,
+            env: Environment,
+            address: &str,
+            function: &str,
+            params: &[String],
+        ) -> Result<()> {
+            let bytes = self
+                .contracts
+                .get(address)
+                .ok_or_else(|| RuntimeError::HostError(format!("unknown contract {}", address)))?;
+            let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+            let context = Context::new(self.clone())
+                .with_call_depth(call_depth)
+                .with_limits(limits)
+                .with_environment(env);
+            let (_, context) = call_function_in_context(bytes, function, &params, context)?;
+
+            *self = context.host;
+            self.calls.push((address.into(), function.into()));
+
+            Ok(())
+        }
+
+        fn sload(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+            self.storage.sload(key)
+        }
+
+        fn sstore(&mut self, key: &str, value: &[u8]) -> Result<()> {
+            self.storage.sstore(key, value)
+        }
+
+        fn log(&mut self, log: ContractLog) -> Result<()> {
+            self.storage.log(log)
+        }
+    }
 
     fn params_2<'a>(address: &'a String) -> [&'a str; 4] {
         ["String", &address, "U64", "10"]
@@ -127,7 +613,52 @@ mod tests {
     #[test]
     fn it_loads_a_contract() {
         let bytes = include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20.wasm");
-        let _loaded = load_contract(bytes).unwrap();
+        let _loaded = load_contract(bytes, Context::new(MemoryHost::default())).unwrap();
+    }
+
+    #[test]
+    fn it_loads_a_contract_from_a_path() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../target/wasm32-unknown-unknown/release/erc20.wasm"
+        );
+        let bytes = load_contract_from_path(path).unwrap();
+
+        assert_eq!(bytes, ERC20);
+    }
+
+    #[test]
+    fn it_returns_an_error_for_a_missing_contract_file() {
+        let result = load_contract_from_path("missing/contract.wasm");
+
+        assert!(matches!(result, Err(RuntimeError::ContractNotFound(_))));
+    }
+
+    #[test]
+    fn it_gets_contract_function_signatures() {
+        let signatures = contract_function_signatures(ERC20).unwrap();
+        let transfer = signatures
+            .iter()
+            .find(|(name, _)| name == "transfer")
+            .unwrap();
+
+        assert_eq!(transfer.1, vec!["String", "U64"]);
+        assert_eq!(
+            signatures
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["construct", "mint", "transfer", "balance-of"]
+        );
+    }
+
+    #[test]
+    fn it_lists_component_imports() {
+        let imports = component_imports(ERC20).unwrap();
+
+        assert_eq!(imports, vec!["sload", "sstore", "caller", "log"]);
+        assert!(check_imports(ERC20).is_ok());
+        assert!(check_imports(CALLER).is_ok());
     }
 
     #[test]
@@ -139,6 +670,27 @@ mod tests {
         call_function(bytes, "mint", &params_2(&address)).unwrap();
     }
 
+    #[test]
+    fn it_records_logs_emitted_by_a_contract() {
+        let address = Account::random().to_string();
+        let mut contract =
+            ContractInstance::new(ERC20, Context::new(MemoryHost::default())).unwrap();
+
+        contract.call("construct", PARAMS_1).unwrap();
+        contract.call("mint", &params_2(&address)).unwrap();
+
+        let logs = &contract.store.data().host.logs;
+        let mut topic = [0; 32];
+        topic[..8].copy_from_slice(b"Transfer");
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].topics, vec![topic]);
+        assert_eq!(
+            logs[0].data,
+            format!("0x0000000000000000000000000000000000000000:{}:10", address).into_bytes()
+        );
+    }
+
     #[test]
     fn it_parses_string_params() {
         let parsed = parse_params(&[PARAMS_1[0], PARAMS_1[1]]).unwrap();
@@ -152,4 +704,310 @@ mod tests {
         let parsed = parse_params(&[params[2], params[3]]).unwrap();
         assert_eq!(parsed, Val::U64(10));
     }
+
+    #[test]
+    fn it_calls_a_contract_from_a_contract() {
+        let erc20_address = Account::random().to_string();
+        let account = Account::random().to_string();
+        let host = TestHost {
+            contracts: HashMap::from([(erc20_address.clone(), ERC20)]),
+            calls: vec![],
+            storage: MemoryHost::default(),
+        };
+        let params = [
+            "String",
+            &erc20_address,
+            "String",
+            "mint",
+            "String",
+            &account,
+            "U64",
+            "10",
+        ];
+
+        let (_, context) =
+            call_function_in_context(CALLER, "forward", &params, Context::new(host)).unwrap();
+
+        assert_eq!(
+            context.host.calls,
+            vec![(erc20_address, "mint".to_string())]
+        );
+    }
+
+    #[test]
+    fn it_rejects_contract_calls_without_a_host() {
+        let params = [
+            "String",
+            "0x0000000000000000000000000000000000000000",
+            "String",
+            "mint",
+            "String",
+            "account",
+            "U64",
+            "10",
+        ];
+
+        assert!(call_function(CALLER, "forward", &params).is_err());
+    }
+
+    #[test]
+    fn it_stops_recursive_calls_at_the_call_depth_limit() {
+        let address = Account::random().to_string();
+        let host = TestHost {
+            contracts: HashMap::from([(address.clone(), CALLER)]),
+            calls: vec![],
+            storage: MemoryHost::default(),
+        };
+        let context = Context::new(host).with_call_depth(CallDepth::new(8));
+        let result = call_function_in_context(CALLER, "recurse", &["String", &address], context);
+
+        assert!(matches!(result, Err(RuntimeError::CallDepthExceeded(8))));
+    }
+
+    #[test]
+    fn it_stops_recursive_calls_at_the_default_call_depth() {
+        let address = Account::random().to_string();
+        let host = TestHost {
+            contracts: HashMap::from([(address.clone(), CALLER)]),
+            calls: vec![],
+            storage: MemoryHost::default(),
+        };
+        let result =
+            call_function_in_context(CALLER, "recurse", &["String", &address], Context::new(host));
+
+        assert!(matches!(
+            result,
+            Err(RuntimeError::CallDepthExceeded(DEFAULT_MAX_CALL_DEPTH))
+        ));
+    }
+
+    #[test]
+    fn it_stops_execution_when_the_fuel_runs_out() {
+        let limits = ResourceLimits {
+            max_fuel: Some(1_000),
+            ..Default::default()
+        };
+        let context = Context::new(MemoryHost::default()).with_limits(limits);
+        let mut contract = ContractInstance::new(ERC20, context).unwrap();
+        let address = Account::random().to_string();
+
+        let result = contract.call("mint", &params_2(&address));
+        assert!(matches!(
+            result,
+            Err(RuntimeError::ResourceLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn it_links_every_host_function() {
+        let mut linker = Linker::<Context<MemoryHost>>::new(&engine(false).unwrap());
+        link_host_functions(&mut linker).unwrap();
+
+        // 表中的每个函数都已注册，再次注册同名函数会失败
+        for name in HOST_FUNCTIONS {
+            let result = linker.root().func_wrap(
+                name,
+                |_store: StoreContextMut<'_, Context<MemoryHost>>, (): ()| Ok(()),
+            );
+
+            assert!(result.is_err(), "{} is not linked", name);
+        }
+    }
+
+    #[test]
+    fn it_reuses_the_engine() {
+        assert!(Engine::same(
+            &engine(false).unwrap(),
+            &engine(false).unwrap()
+        ));
+        assert!(!Engine::same(
+            &engine(false).unwrap(),
+            &engine(true).unwrap()
+        ));
+    }
+
+    #[test]
+    fn it_returns_values_from_contract_functions() {
+        let address = Account::random().to_string();
+        let mut contract =
+            ContractInstance::new(ERC20, Context::new(MemoryHost::default())).unwrap();
+
+        contract.call("construct", PARAMS_1).unwrap();
+
+        let minted = contract.call("mint", &params_2(&address)).unwrap();
+        assert_eq!(minted, vec![Val::Bool(true)]);
+
+        let balance = contract.call("balance-of", &["String", &address]).unwrap();
+        assert_eq!(balance, vec![Val::U64(10)]);
+    }
+
+    #[test]
+    fn it_persists_contract_state_between_instances() {
+        let address = Account::random().to_string();
+
+        let mut contract =
+            ContractInstance::new(ERC20, Context::new(MemoryHost::default())).unwrap();
+        contract.call("mint", &params_2(&address)).unwrap();
+        let host = contract.into_context().host;
+
+        let mut contract = ContractInstance::new(ERC20, Context::new(host)).unwrap();
+        let balance = contract.call("balance-of", &["String", &address]).unwrap();
+        assert_eq!(balance, vec![Val::U64(10)]);
+    }
+
+    #[test]
+    fn it_stops_allocations_beyond_the_memory_limit() {
+        let limits = ResourceLimits {
+            max_memory_bytes: 4 * 1024 * 1024,
+            ..Default::default()
+        };
+        let context = Context::new(MemoryHost::default()).with_limits(limits);
+        let mut contract = ContractInstance::new(ALLOCATOR, context).unwrap();
+
+        let allocated = contract.call("allocate", &["U64", "1024"]).unwrap();
+        assert_eq!(allocated, vec![Val::U64(1024)]);
+
+        let result = contract.call("allocate", &["U64", "67108864"]);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::ResourceLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn it_reads_the_environment_from_the_context() {
+        let caller = Account::random().to_string();
+        let env = Environment {
+            block_number: 42,
+            block_timestamp: 1_700_000_000,
+            caller: caller.clone(),
+            value: 5,
+            ..Default::default()
+        };
+        let context = Context::new(MemoryHost::default()).with_environment(env);
+        let mut contract = ContractInstance::new(ENVIRONMENT, context).unwrap();
+
+        let block_number = contract.call("get-block-number", &[]).unwrap();
+        assert_eq!(block_number, vec![Val::U64(42)]);
+
+        let block_timestamp = contract.call("get-block-timestamp", &[]).unwrap();
+        assert_eq!(block_timestamp, vec![Val::U64(1_700_000_000)]);
+
+        let result = contract.call("get-caller", &[]).unwrap();
+        assert_eq!(result, vec![Val::String(caller.into())]);
+
+        let value = contract.call("get-value", &[]).unwrap();
+        assert_eq!(value, vec![Val::U64(5)]);
+    }
+
+    #[test]
+    fn it_transfers_from_the_caller() {
+        let caller = Account::random().to_string();
+        let account = Account::random().to_string();
+        let env = Environment {
+            caller: caller.clone(),
+            ..Default::default()
+        };
+        let context = Context::new(MemoryHost::default()).with_environment(env);
+        let mut contract = ContractInstance::new(ERC20, context).unwrap();
+        contract.call("mint", &params_2(&caller)).unwrap();
+
+        let transferred = contract.call("transfer", &["String", &account, "U64", "4"]);
+        assert_eq!(transferred.unwrap(), vec![Val::Bool(true)]);
+
+        let balance = contract.call("balance-of", &["String", &caller]).unwrap();
+        assert_eq!(balance, vec![Val::U64(6)]);
+        let balance = contract.call("balance-of", &["String", &account]).unwrap();
+        assert_eq!(balance, vec![Val::U64(4)]);
+
+        // 余额不足时转账失败
+        let transferred = contract.call("transfer", &["String", &account, "U64", "7"]);
+        assert_eq!(transferred.unwrap(), vec![Val::Bool(false)]);
+    }
+
+    #[test]
+    fn it_passes_the_calling_contract_as_the_caller() {
+        let erc20_address = Account::random().to_string();
+        let caller_address = Account::random().to_string();
+        let account = Account::random().to_string();
+        let host = TestHost {
+            contracts: HashMap::from([(erc20_address.clone(), ERC20)]),
+            calls: vec![],
+            storage: MemoryHost::default(),
+        };
+        let (_, context) = call_function_in_context(
+            ERC20,
+            "mint",
+            &params_2(&caller_address),
+            Context::new(host),
+        )
+        .unwrap();
+        let env = Environment {
+            caller: Account::random().to_string(),
+            address: caller_address.clone(),
+            ..Default::default()
+        };
+        let params = [
+            "String",
+            &erc20_address,
+            "String",
+            "transfer",
+            "String",
+            &account,
+            "U64",
+            "4",
+        ];
+
+        // 调用方合约把转账转发给erc20合约，扣减的是调用方合约的余额
+        let context = Context::new(context.host).with_environment(env);
+        let (_, context) = call_function_in_context(CALLER, "forward", &params, context).unwrap();
+        let mut contract = ContractInstance::new(ERC20, Context::new(context.host)).unwrap();
+
+        let balance = contract
+            .call("balance-of", &["String", &caller_address])
+            .unwrap();
+        assert_eq!(balance, vec![Val::U64(6)]);
+        let balance = contract.call("balance-of", &["String", &account]).unwrap();
+        assert_eq!(balance, vec![Val::U64(4)]);
+    }
+
+    #[test]
+    fn it_returns_the_revert_reason() {
+        let result = call_function(REVERTER, "fail", &["String", "insufficient balance"]);
+
+        assert!(matches!(
+            result,
+            Err(RuntimeError::Revert { reason }) if reason == "insufficient balance"
+        ));
+    }
+
+    #[test]
+    fn it_reverts_on_a_trap() {
+        let result = call_function(REVERTER, "trap", &[]);
+
+        assert!(matches!(result, Err(RuntimeError::Revert { .. })));
+    }
+
+    #[test]
+    fn it_traces_function_and_host_calls() {
+        let address = Account::random().to_string();
+        let context = Context::new(MemoryHost::default()).with_tracing();
+        let mut contract = ContractInstance::new(ERC20, context).unwrap();
+        contract.call("mint", &params_2(&address)).unwrap();
+
+        let trace = contract.into_context().trace.unwrap();
+        let operations = trace
+            .iter()
+            .map(|step| step.operation.as_str())
+            .collect::<Vec<_>>();
+        let balance_key = format!("balances/{}", address);
+
+        assert_eq!(operations.first(), Some(&"enter mint"));
+        assert_eq!(operations.last(), Some(&"exit mint"));
+        assert!(operations.contains(&format!("sstore {}", balance_key).as_str()));
+        assert!(trace
+            .windows(2)
+            .all(|steps| steps[0].fuel_consumed <= steps[1].fuel_consumed));
+        assert!(trace.last().unwrap().fuel_consumed > 0);
+    }
 }
@@ -2,18 +2,42 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum RuntimeError {
+    #[error("Maximum call depth of {0} exceeded")]
+    CallDepthExceeded(usize),
+
     #[error("Error invoking function {0}")]
     CallFunctionError(String),
 
+    #[error("Could not decode the contract interface: {0}")]
+    ContractDecodeError(String),
+
+    #[error("Contract file {0} not found")]
+    ContractNotFound(String),
+
+    #[error("Could not read contract file {0}: {1}")]
+    ContractReadError(String, String),
+
     #[error("Error executing {0}")]
     ExecutionError(String),
 
     #[error("Error exporting function {0}")]
     ExportFunctionError(String),
 
+    #[error("Host error: {0}")]
+    HostError(String),
+
     #[error("Invalid parameter type {0}")]
     InvalidParamType(String),
 
+    #[error("Resource limit exceeded: {0}")]
+    ResourceLimitExceeded(String),
+
+    #[error("Execution reverted: {reason}")]
+    Revert { reason: String },
+
+    #[error("Contract imports unsupported host functions: {0}")]
+    UnsupportedImports(String),
+
     #[error("Wasmtime error {0}")]
     WasmtimeError(String),
 }
@@ -22,6 +46,10 @@ pub type Result<T> = std::result::Result<T, RuntimeError>;
 
 impl From<anyhow::Error> for RuntimeError {
     fn from(error: anyhow::Error) -> Self {
-        RuntimeError::WasmtimeError(error.to_string())
+        // 宿主（例如资源限制）产生的运行时错误保持原有的类型
+        match error.downcast::<RuntimeError>() {
+            Ok(error) => error,
+            Err(error) => RuntimeError::WasmtimeError(error.to_string()),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wasmtime::ResourceLimiter;
+
+use crate::error::{Result, RuntimeError};
+
+/// 默认的最大合约调用深度
+///
+/// 每一层合约间调用都会在宿主的栈上嵌套一次合约执行，深度过大时会先耗尽宿主的栈
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 64;
+
+/// 默认的合约最大内存，单位为字节
+pub const DEFAULT_MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+
+/// 默认的合约最大表元素数量
+pub const DEFAULT_MAX_TABLE_ELEMENTS: u32 = 10_000;
+
+/// 一条合约日志最多包含的主题数量
+pub const MAX_LOG_TOPICS: usize = 4;
+
+/// 合约调用深度计数器
+///
+/// 每进入一层合约间调用深度加一，超过最大深度时中止执行，
+/// 防止合约无限递归耗尽栈空间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallDepth {
+    pub depth: usize,
+    pub max_depth: usize,
+}
+
+impl CallDepth {
+    /// 创建一个指定最大深度的计数器，初始深度为0
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            depth: 0,
+            max_depth,
+        }
+    }
+
+    /// 进入下一层调用，超过最大深度时返回`RuntimeError::CallDepthExceeded`
+    pub fn enter(self) -> Result<Self> {
+        if self.depth >= self.max_depth {
+            return Err(RuntimeError::CallDepthExceeded(self.max_depth));
+        }
+
+        Ok(Self {
+            depth: self.depth + 1,
+            ..self
+        })
+    }
+}
+
+impl Default for CallDepth {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CALL_DEPTH)
+    }
+}
+
+/// 合约执行时的资源限制
+///
+/// 每个合约在独立的Store中执行，Store通过该限制约束合约可以使用的内存和表大小，
+/// 设置了`max_fuel`时还会限制每个Store可以消耗的fuel，
+/// 超过限制时中止执行并返回`RuntimeError::ResourceLimitExceeded`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub max_memory_bytes: usize,
+    pub max_table_elements: u32,
+    pub max_fuel: Option<u64>,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES,
+            max_table_elements: DEFAULT_MAX_TABLE_ELEMENTS,
+            max_fuel: None,
+        }
+    }
+}
+
+/// Store使用的资源限制器
+///
+/// wasmtime拒绝内存或表的增长时只会让增长失败，合约随后因分配失败而trap，
+/// 限制器记录第一次超过限制的原因，执行失败后据此返回`RuntimeError::ResourceLimitExceeded`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limiter {
+    pub limits: ResourceLimits,
+    pub exceeded: Option<String>,
+}
+
+impl Limiter {
+    /// 使用给定的资源限制创建限制器
+    pub fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            exceeded: None,
+        }
+    }
+
+    /// 取出记录的超限原因，没有超过限制时返回None
+    pub fn take_exceeded(&mut self) -> Option<RuntimeError> {
+        self.exceeded
+            .take()
+            .map(RuntimeError::ResourceLimitExceeded)
+    }
+
+    /// 拒绝一次增长并记录原因，只保留第一次超限的原因
+    fn reject(&mut self, reason: String) -> bool {
+        self.exceeded.get_or_insert(reason);
+        false
+    }
+}
+
+impl ResourceLimiter for Limiter {
+    fn memory_growing(&mut self, _current: usize, desired: usize, _maximum: Option<usize>) -> bool {
+        if desired > self.limits.max_memory_bytes {
+            return self.reject(format!(
+                "memory of {} bytes exceeds the limit of {} bytes",
+                desired, self.limits.max_memory_bytes
+            ));
+        }
+
+        true
+    }
+
+    fn table_growing(&mut self, _current: u32, desired: u32, _maximum: Option<u32>) -> bool {
+        if desired > self.limits.max_table_elements {
+            return self.reject(format!(
+                "table of {} elements exceeds the limit of {} elements",
+                desired, self.limits.max_table_elements
+            ));
+        }
+
+        true
+    }
+}
+
+/// 合约执行时所处的区块和交易环境，合约通过宿主函数读取
+///
+/// 字段:
+/// - block_number: 交易所在区块的区块号
+/// - block_timestamp: 交易所在区块的时间戳，单位为秒
+/// - caller: 发起调用的账户地址，合约间调用时为调用方合约的地址
+/// - address: 当前执行的合约地址
+/// - value: 随调用转入当前合约的金额
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub caller: String,
+    pub address: String,
+    pub value: u64,
+}
+
+impl Environment {
+    /// 调用另一个合约时，被调用合约的执行环境
+    ///
+    /// 区块信息保持不变，调用方变为当前合约，合约间调用不转移金额
+    pub fn call(&self, address: &str) -> Self {
+        Self {
+            caller: self.address.clone(),
+            address: address.into(),
+            value: 0,
+            ..self.clone()
+        }
+    }
+}
+
+/// 跟踪模式下记录的一步合约执行
+///
+/// 字段:
+/// - depth: 执行所处的调用深度
+/// - operation: 执行的操作，例如调用的函数或宿主函数
+/// - fuel_consumed: 到这一步为止当前合约消耗的fuel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceStep {
+    pub depth: usize,
+    pub operation: String,
+    pub fuel_consumed: u64,
+}
+
+/// 合约执行时通过`log`宿主函数发出的日志
+///
+/// 字段:
+/// - topics: 日志主题，每个主题固定为32字节，最多`MAX_LOG_TOPICS`个
+/// - data: 日志数据
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractLog {
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+/// 合约执行时由宿主提供的能力
+///
+/// 运行时本身并不了解区块链的状态，合约间调用、合约存储等需要访问链上数据的操作，
+/// 都通过该trait回调给宿主（例如区块链节点）来完成
+pub trait Host: Send {
+    /// 调用另一个合约中的函数
+    ///
+    /// # 参数
+    ///
+    /// * `call_depth`: 被调用合约所处的调用深度，宿主需要将其传递给被调用合约的上下文
+    /// * `limits`: 当前合约的资源限制，宿主需要将其传递给被调用合约的上下文
+    /// * `env`: 被调用合约的执行环境，宿主需要将其传递给被调用合约的上下文
+    /// * `address`: 目标合约的地址
+    /// * `function`: 要调用的函数名
+    /// * `params`: 函数调用参数列表，每两个元素表示一个类型和值
+    fn call(
+        &mut self,
+        call_depth: CallDepth,
+        limits: ResourceLimits,
+        env: Environment,
+        address: &str,
+        function: &str,
+        params: &[String],
+    ) -> Result<()>;
+
+    /// 读取当前合约存储中指定键的值，不存在时返回None
+    fn sload(&mut self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// 写入当前合约存储中指定键的值
+    fn sstore(&mut self, key: &str, value: &[u8]) -> Result<()>;
+
+    /// 记录当前合约发出的一条日志
+    ///
+    /// 宿主只应在合约执行成功后将日志写入交易收据，执行失败时丢弃
+    fn log(&mut self, log: ContractLog) -> Result<()>;
+}
+
+/// 只在内存中保存合约存储的宿主，不支持合约间调用，适用于独立执行的合约
+#[derive(Debug, Default, Clone)]
+pub struct MemoryHost {
+    pub storage: HashMap<String, Vec<u8>>,
+    pub logs: Vec<ContractLog>,
+}
+
+impl Host for MemoryHost {
+    fn call(
+        &mut self,
+        _call_depth: CallDepth,
+        _limits: ResourceLimits,
+        _env: Environment,
+        address: &str,
+        function: &str,
+        _params: &[String],
+    ) -> Result<()> {
+        Err(RuntimeError::HostError(format!(
+            "cannot call {} on {}: contract calls are not supported",
+            function, address
+        )))
+    }
+
+    fn sload(&mut self, key: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.storage.get(key).cloned())
+    }
+
+    fn sstore(&mut self, key: &str, value: &[u8]) -> Result<()> {
+        self.storage.insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn log(&mut self, log: ContractLog) -> Result<()> {
+        self.logs.push(log);
+        Ok(())
+    }
+}
+
+/// 合约执行期间保存在Store中的上下文
+pub struct Context<H> {
+    pub host: H,
+    pub call_depth: CallDepth,
+    pub limiter: Limiter,
+    pub env: Environment,
+    pub trace: Option<Vec<TraceStep>>,
+}
+
+impl<H: Host> Context<H> {
+    /// 使用给定的宿主创建执行上下文
+    pub fn new(host: H) -> Self {
+        Self {
+            host,
+            call_depth: CallDepth::default(),
+            limiter: Limiter::default(),
+            env: Environment::default(),
+            trace: None,
+        }
+    }
+
+    /// 设置上下文所处的调用深度
+    pub fn with_call_depth(mut self, call_depth: CallDepth) -> Self {
+        self.call_depth = call_depth;
+        self
+    }
+
+    /// 设置合约执行的资源限制
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limiter = Limiter::new(limits);
+        self
+    }
+
+    /// 设置合约执行时所处的区块和交易环境
+    pub fn with_environment(mut self, env: Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// 开启跟踪模式，执行过程中记录每次函数调用、宿主函数调用以及消耗的fuel
+    pub fn with_tracing(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+}
@@ -1,16 +1,115 @@
 use crate::bytes::Bytes;
-use ethereum_types::{Address, U256};
-use serde::{Deserialize, Serialize};
+use crate::error::{Result, TypeError};
+use ethereum_types::{Address, H256, U256};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::str::FromStr;
+use utils::crypto::hash;
 pub type Account = Address;
 
+/// `Account`的便捷方法，`Account`是`ethereum_types`中地址类型的别名
+pub trait AccountExt {
+    /// 由种子确定性地生成一个账户，用于需要可复现结果的测试
+    ///
+    /// 对种子的大端字节做keccak哈希，取最后20个字节作为地址
+    ///
+    /// 参数:
+    ///   - seed: 任意种子，相同的种子总是得到相同的账户
+    fn from_seed(seed: u64) -> Self;
+}
+
+impl AccountExt for Account {
+    fn from_seed(seed: u64) -> Self {
+        Account::from_slice(&hash(&seed.to_be_bytes())[12..])
+    }
+}
+
+/// 解析十六进制地址，并在地址为大小写混合时校验EIP-55校验和
+///
+/// 全小写或全大写的地址不包含校验和，直接接受
+///
+/// 参数:
+///   - s: 带或不带 0x 前缀的十六进制地址
+///
+/// 返回值:
+///   解析得到的账户；地址格式不合法或校验和不匹配时返回 `TypeError::InvalidAddress`
+pub fn parse_checked(s: &str) -> Result<Account> {
+    let hex = s.strip_prefix("0x").unwrap_or(s);
+    let account =
+        Account::from_str(hex).map_err(|e| TypeError::InvalidAddress(format!("{}: {}", s, e)))?;
+
+    let is_mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+
+    if is_mixed_case && to_checksum_string(&account)[2..] != *hex {
+        return Err(TypeError::InvalidAddress(format!(
+            "{}: invalid checksum",
+            s
+        )));
+    }
+
+    Ok(account)
+}
+
+/// 将账户转换为带EIP-55校验和的十六进制字符串
+///
+/// 对小写十六进制地址做keccak哈希，哈希中对应半字节大于等于8的字母转换为大写
+pub fn to_checksum_string(account: &Account) -> String {
+    let address = hex::encode(account.as_bytes());
+    let address_hash = hash(address.as_bytes());
+    let checksummed: String = address
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let shift = if index % 2 == 0 { 4 } else { 0 };
+            let nibble = (address_hash[index / 2] >> shift) & 0x0f;
+
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    format!("0x{}", checksummed)
+}
+
+/// JSON-RPC参数中的账户地址
+///
+/// 以太坊客户端以十六进制字符串传递地址，反序列化时接受带或不带 0x 前缀的地址，
+/// 大小写混合的地址会校验EIP-55校验和，校验规则与 `parse_checked` 相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressParam(pub Account);
+
+impl<'de> Deserialize<'de> for AddressParam {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let address = String::deserialize(deserializer)?;
+
+        parse_checked(&address)
+            .map(AddressParam)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl From<AddressParam> for Account {
+    fn from(param: AddressParam) -> Self {
+        param.0
+    }
+}
+
 /// AccountData 结构体用于存储账户的相关数据
 /// 包括 nonce（用于防止重放攻击的计数器），
-/// balance（账户余额），以及 code_hash（账户代码的哈希值，用于识别合约账户）
+/// balance（账户余额），code_hash（账户代码的哈希值，用于识别合约账户），
+/// 以及 storage_root（合约存储trie的根哈希，没有写入过存储时为空）
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct AccountData {
     pub nonce: U256,
     pub balance: U256,
     pub code_hash: Option<Bytes>,
+    pub storage_root: Option<H256>,
 }
 
 impl AccountData {
@@ -26,9 +125,17 @@ impl AccountData {
             nonce: U256::zero(),
             balance: U256::zero(),
             code_hash,
+            storage_root: None,
         }
     }
 
+    /// 创建一个存在但为空的普通账户，nonce 和 balance 都为零
+    ///
+    /// 与账户不存在不同，空账户可以被查询，例如第一次收到转账时创建的接收方账户
+    pub fn empty() -> Self {
+        Self::new(None)
+    }
+
     /// 判断账户是否为合约账户
     ///
     /// 返回值:
@@ -38,3 +145,50 @@ impl AccountData {
         self.code_hash.is_some()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECKSUMMED: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[test]
+    fn it_deserializes_an_address_param() {
+        let expected = parse_checked(CHECKSUMMED).unwrap();
+        let lowercase = CHECKSUMMED.to_lowercase();
+
+        for address in [CHECKSUMMED, &lowercase, &lowercase[2..]] {
+            let param: AddressParam = serde_json::from_value(address.into()).unwrap();
+            assert_eq!(Account::from(param), expected);
+        }
+
+        let invalid_checksum = CHECKSUMMED.replace('a', "A");
+        assert!(serde_json::from_value::<AddressParam>(invalid_checksum.into()).is_err());
+        assert!(serde_json::from_value::<AddressParam>("0x1234".into()).is_err());
+    }
+
+    #[test]
+    fn it_derives_an_account_from_a_seed() {
+        assert_eq!(Account::from_seed(1), Account::from_seed(1));
+        assert_ne!(Account::from_seed(1), Account::from_seed(2));
+        assert!(!Account::from_seed(0).is_zero());
+    }
+
+    #[test]
+    fn it_parses_a_valid_checksum() {
+        let account = parse_checked(CHECKSUMMED).unwrap();
+        assert_eq!(to_checksum_string(&account), CHECKSUMMED);
+    }
+
+    #[test]
+    fn it_rejects_an_invalid_checksum() {
+        let result = parse_checked("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD");
+        assert!(matches!(result, Err(TypeError::InvalidAddress(_))));
+    }
+
+    #[test]
+    fn it_accepts_an_all_lowercase_address() {
+        let account = parse_checked(&CHECKSUMMED.to_lowercase()).unwrap();
+        assert_eq!(to_checksum_string(&account), CHECKSUMMED);
+    }
+}
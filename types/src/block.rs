@@ -1,8 +1,9 @@
+use std::cmp::Ordering;
 use std::ops::Deref;
 
-use ethereum_types::{H256, U64};
+use ethereum_types::{Bloom, H256, U256, U64};
 use serde::{Deserialize, Serialize};
-use utils::crypto::{hash, is_valid_hash};
+use utils::crypto::{hash, meets_difficulty};
 
 use crate::{
     error::{Result, TypeError},
@@ -10,6 +11,18 @@ use crate::{
     transaction::Transaction,
 };
 
+/// 创世块的默认难度，平均需要尝试256次才能找到满足难度的哈希
+pub const GENESIS_DIFFICULTY: u64 = 256;
+
+/// 每个区块难度调整的幅度为父区块难度的`1 / DIFFICULTY_ADJUSTMENT_DIVISOR`
+pub const DIFFICULTY_ADJUSTMENT_DIVISOR: u64 = 8;
+
+/// 难度的下限
+pub const MINIMUM_DIFFICULTY: u64 = 1;
+
+/// 难度的上限，连续快速出块时难度不会无限增长，挖出一个区块平均最多尝试65536次
+pub const MAX_DIFFICULTY: u64 = 65_536;
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename = "block_number")]
 pub struct BlockNumber(pub U64);
@@ -28,6 +41,18 @@ impl From<i32> for BlockNumber {
     }
 }
 
+impl From<u64> for BlockNumber {
+    fn from(value: u64) -> Self {
+        BlockNumber(U64::from(value))
+    }
+}
+
+impl From<U64> for BlockNumber {
+    fn from(value: U64) -> Self {
+        BlockNumber(value)
+    }
+}
+
 impl TryFrom<&str> for BlockNumber {
     type Error = TypeError;
 
@@ -36,11 +61,23 @@ impl TryFrom<&str> for BlockNumber {
     }
 }
 
+/// 区块标签，用于指定查询所基于的状态
+///
+/// - `Latest`: 最新打包的区块之后的状态
+/// - `Pending`: 在最新状态上应用交易池中尚未打包的交易之后的状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockTag {
+    #[default]
+    Latest,
+    Pending,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all(serialize = "snake_case", deserialize = "snake_case"))]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 // 定义一个Block结构体，用于表示区块链中的一个区块
 // 该结构体派生了Serialize、Deserialize、Debug和Clone trait，分别用于序列化、反序列化、调试打印和深拷贝
-// 使用serde属性，指定在序列化时将所有字段名转换为snake_case格式，在反序列化时也使用snake_case格式
+// 使用serde属性，指定在序列化和反序列化时都使用camelCase格式，与区块中的交易保持一致
 pub struct Block {
     // 区块编号，使用U64类型表示
     pub number: U64,
@@ -56,18 +93,61 @@ pub struct Block {
     pub transactions_root: H256,
     // 状态根哈希值，用于快速验证区块状态的完整性
     pub state_root: H256,
+    // 区块内所有交易日志的地址和主题组成的布隆过滤器，用于快速跳过不可能匹配的区块
+    #[serde(default)]
+    pub logs_bloom: Bloom,
+    // 区块中所有交易的固有gas之和，用于展示区块的填充程度
+    #[serde(default)]
+    pub gas_used: U256,
+    // 完整区块序列化后的字节数，不参与区块哈希的计算
+    #[serde(default)]
+    pub size: u64,
+    // 出块时间，Unix时间戳，单位为毫秒
+    #[serde(default)]
+    pub timestamp: u64,
+    // 区块哈希需要满足的难度
+    #[serde(default)]
+    pub difficulty: U256,
     /// number used once，工作量证明
     pub nonce: u128,
 }
 
 impl Block {
+    /// 使用创世难度创建一个时间戳为0的区块
     pub fn new(
         number: U64,
         parent_hash: H256,
         transactions: Vec<Transaction>,
         state_root: H256,
+        logs_bloom: Bloom,
+    ) -> Result<Block> {
+        Self::new_with_difficulty(
+            number,
+            parent_hash,
+            transactions,
+            state_root,
+            logs_bloom,
+            0,
+            U256::from(GENESIS_DIFFICULTY),
+        )
+    }
+
+    /// 创建一个区块，并不断递增nonce直到区块哈希满足给定的难度
+    pub fn new_with_difficulty(
+        number: U64,
+        parent_hash: H256,
+        transactions: Vec<Transaction>,
+        state_root: H256,
+        logs_bloom: Bloom,
+        timestamp: u64,
+        difficulty: U256,
     ) -> Result<Block> {
         let transactions_root = Transaction::root_hash(&transactions)?;
+        let gas_used = transactions
+            .iter()
+            .fold(U256::zero(), |gas_used, transaction| {
+                gas_used.saturating_add(transaction.gas_used())
+            });
         let mut block = Block {
             number,
             hash: None,
@@ -75,22 +155,72 @@ impl Block {
             transactions,
             transactions_root,
             state_root,
+            logs_bloom,
+            gas_used,
+            size: 0,
+            timestamp,
+            difficulty,
             nonce: 0,
         };
 
         loop {
             let serialized = bincode::serialize(&block)?;
             let hash: H256 = hash(&serialized).into();
-            if is_valid_hash(hash) {
+            if meets_difficulty(hash, difficulty) {
                 block.hash = Some(hash);
                 break;
             }
             block.nonce += 1;
         }
 
+        // size是定长字段，填入后不会改变序列化的长度
+        block.size = bincode::serialized_size(&block)?;
+
         Ok(block)
     }
 
+    /// 校验区块内部的一致性
+    ///
+    /// 重新计算交易根哈希和区块哈希，并与区块中记录的值比较，
+    /// 交易列表或区块内容被篡改时返回 `TypeError::InvalidBlock`。
+    /// 区块哈希覆盖了交易列表，区块体被裁剪的区块无法校验，返回 `TypeError::BlockPruned`
+    pub fn verify(&self) -> Result<()> {
+        let transactions_root = Transaction::root_hash(&self.transactions)?;
+        if transactions_root != self.transactions_root {
+            if self.transactions.is_empty() {
+                return Err(TypeError::BlockPruned(self.number.to_string()));
+            }
+
+            return Err(TypeError::InvalidBlock(format!(
+                "transactions root {:?} does not match {:?}",
+                transactions_root, self.transactions_root
+            )));
+        }
+
+        // 区块哈希在填入hash和size之前计算
+        let unhashed = Block {
+            hash: None,
+            size: 0,
+            ..self.clone()
+        };
+        let block_hash: H256 = hash(&bincode::serialize(&unhashed)?).into();
+        if Some(block_hash) != self.hash {
+            return Err(TypeError::InvalidBlock(format!(
+                "block hash {:?} does not match {:?}",
+                block_hash, self.hash
+            )));
+        }
+
+        if !meets_difficulty(block_hash, self.difficulty) {
+            return Err(TypeError::InvalidBlock(format!(
+                "block hash {:?} does not meet difficulty {}",
+                block_hash, self.difficulty
+            )));
+        }
+
+        Ok(())
+    }
+
     pub fn block_hash(&self) -> Result<H256> {
         self.hash.ok_or(TypeError::MissingBlockHash)
     }
@@ -106,6 +236,243 @@ impl Block {
     /// 返回值:
     /// - Result<Self>: 返回一个结果，包含成功创建的创世块实例或错误
     pub fn genesis() -> Result<Self> {
-        Self::new(U64::zero(), H256::zero(), vec![], H256::zero())
+        Self::genesis_with_state_root(H256::zero())
+    }
+
+    /// 使用给定的状态根创建创世块
+    ///
+    /// 当创世状态中预先分配了账户时，状态根需要由这些账户构建的状态树计算得出，
+    /// 使创世块能够承诺初始状态
+    pub fn genesis_with_state_root(state_root: H256) -> Result<Self> {
+        Self::genesis_with_difficulty(state_root, U256::from(GENESIS_DIFFICULTY))
+    }
+
+    /// 使用给定的状态根和难度创建创世块，创世块的时间戳为0
+    pub fn genesis_with_difficulty(state_root: H256, difficulty: U256) -> Result<Self> {
+        Self::new_with_difficulty(
+            U64::zero(),
+            H256::zero(),
+            vec![],
+            state_root,
+            Bloom::zero(),
+            0,
+            difficulty,
+        )
+    }
+
+    /// 根据父区块的出块时间计算下一个区块的难度
+    ///
+    /// 出块间隔小于目标间隔时难度上调，大于目标间隔时难度下调，每次调整父区块难度的
+    /// `1 / DIFFICULTY_ADJUSTMENT_DIVISOR`，难度限制在`MINIMUM_DIFFICULTY`和`MAX_DIFFICULTY`之间
+    ///
+    /// # 参数
+    ///
+    /// * `parent_difficulty`: 父区块的难度
+    /// * `parent_timestamp`: 父区块的时间戳，单位为毫秒
+    /// * `block_timestamp`: 新区块的时间戳，单位为毫秒
+    /// * `target_time`: 目标出块间隔，单位为毫秒
+    pub fn next_difficulty(
+        parent_difficulty: U256,
+        parent_timestamp: u64,
+        block_timestamp: u64,
+        target_time: u64,
+    ) -> U256 {
+        let block_time = block_timestamp.saturating_sub(parent_timestamp);
+        let adjustment =
+            (parent_difficulty / U256::from(DIFFICULTY_ADJUSTMENT_DIVISOR)).max(U256::one());
+        let difficulty = match block_time.cmp(&target_time) {
+            Ordering::Less => parent_difficulty.saturating_add(adjustment),
+            Ordering::Greater => parent_difficulty.saturating_sub(adjustment),
+            Ordering::Equal => parent_difficulty,
+        };
+
+        difficulty.clamp(U256::from(MINIMUM_DIFFICULTY), U256::from(MAX_DIFFICULTY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::assert_camel_case_round_trip;
+    use crate::transaction::tests::new_transaction;
+
+    #[test]
+    fn it_converts_a_u64_into_a_block_number() {
+        assert_eq!(BlockNumber::from(42_u64), BlockNumber(U64::from(42)));
+    }
+
+    #[test]
+    fn it_converts_a_u64_wrapper_into_a_block_number() {
+        assert_eq!(BlockNumber::from(U64::from(42)), BlockNumber(U64::from(42)));
+    }
+
+    #[test]
+    fn it_converts_an_i32_into_a_block_number() {
+        assert_eq!(BlockNumber::from(42), BlockNumber(U64::from(42)));
+    }
+
+    #[test]
+    fn it_serializes_a_block_with_transactions_in_camel_case() {
+        let mut transaction = new_transaction();
+        transaction.hash().unwrap();
+        let block = Block::new(
+            U64::from(1),
+            H256::zero(),
+            vec![transaction],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+
+        assert_camel_case_round_trip(&block);
+    }
+
+    /// 测试包含已签名和未签名交易的区块可以通过bincode序列化后还原
+    #[test]
+    fn it_round_trips_a_block_with_signed_and_unsigned_transactions_through_bincode() {
+        let (secret_key, _) = utils::crypto::keypair();
+        let unsigned = new_transaction();
+        let signed = new_transaction()
+            .sign(secret_key)
+            .unwrap()
+            .transaction()
+            .clone();
+        let block = Block::new(
+            U64::from(1),
+            H256::zero(),
+            vec![signed, unsigned],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+
+        let serialized = bincode::serialize(&block).unwrap();
+        let deserialized: Block = bincode::deserialize(&serialized).unwrap();
+
+        assert_eq!(deserialized.transactions, block.transactions);
+        assert!(deserialized.transactions[0].signature.is_some());
+        assert!(deserialized.transactions[1].signature.is_none());
+        assert_eq!(
+            deserialized.block_hash().unwrap(),
+            block.block_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn it_reports_gas_used_and_size() {
+        let transaction = new_transaction();
+        let gas = transaction.gas_used();
+        let block = Block::new(
+            U64::from(1),
+            H256::zero(),
+            vec![transaction],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+        let empty_block = Block::genesis().unwrap();
+
+        assert_eq!(block.gas_used, gas);
+        assert!(!block.gas_used.is_zero());
+        assert_eq!(empty_block.gas_used, U256::zero());
+        assert!(block.size > empty_block.size);
+        assert_eq!(block.size, bincode::serialized_size(&block).unwrap());
+    }
+
+    #[test]
+    fn it_verifies_a_block() {
+        let block = Block::new(
+            U64::from(1),
+            H256::zero(),
+            vec![new_transaction()],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+
+        assert!(block.verify().is_ok());
+        assert!(Block::genesis().unwrap().verify().is_ok());
+    }
+
+    #[test]
+    fn it_rejects_a_block_with_tampered_transactions() {
+        let mut block = Block::new(
+            U64::from(1),
+            H256::zero(),
+            vec![new_transaction()],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+        block.transactions[0].value = U256::from(1_000);
+
+        assert!(matches!(block.verify(), Err(TypeError::InvalidBlock(_))));
+    }
+
+    #[test]
+    fn it_reports_a_pruned_block_body() {
+        let mut block = Block::new(
+            U64::from(1),
+            H256::zero(),
+            vec![new_transaction()],
+            H256::zero(),
+            Bloom::zero(),
+        )
+        .unwrap();
+        block.transactions.clear();
+
+        assert!(matches!(block.verify(), Err(TypeError::BlockPruned(_))));
+    }
+
+    #[test]
+    fn it_adjusts_the_difficulty_to_the_block_time() {
+        let parent_difficulty = U256::from(GENESIS_DIFFICULTY);
+
+        // 出块过快时难度上调
+        let difficulty = Block::next_difficulty(parent_difficulty, 1_000, 1_200, 1_000);
+        assert_eq!(difficulty, U256::from(288));
+
+        // 出块过慢时难度下调
+        let difficulty = Block::next_difficulty(parent_difficulty, 1_000, 5_000, 1_000);
+        assert_eq!(difficulty, U256::from(224));
+
+        // 出块间隔与目标一致时难度不变
+        let difficulty = Block::next_difficulty(parent_difficulty, 1_000, 2_000, 1_000);
+        assert_eq!(difficulty, parent_difficulty);
+
+        // 难度不会低于下限
+        let difficulty = Block::next_difficulty(U256::one(), 1_000, 5_000, 1_000);
+        assert_eq!(difficulty, U256::from(MINIMUM_DIFFICULTY));
+    }
+
+    #[test]
+    fn it_bounds_the_difficulty_of_rapid_blocks() {
+        let mut difficulty = U256::from(GENESIS_DIFFICULTY);
+
+        // 连续快速出块时难度逐步上调，但不会超过上限
+        for timestamp in 0..1_000 {
+            difficulty = Block::next_difficulty(difficulty, timestamp, timestamp, 1_000);
+            assert!(difficulty <= U256::from(MAX_DIFFICULTY));
+        }
+
+        assert_eq!(difficulty, U256::from(MAX_DIFFICULTY));
+    }
+
+    #[test]
+    fn it_mines_a_block_that_meets_its_difficulty() {
+        let block = Block::new_with_difficulty(
+            U64::from(1),
+            H256::zero(),
+            vec![new_transaction()],
+            H256::zero(),
+            Bloom::zero(),
+            1_000,
+            U256::from(1_024),
+        )
+        .unwrap();
+
+        assert_eq!(block.difficulty, U256::from(1_024));
+        assert!(meets_difficulty(block.hash.unwrap(), block.difficulty));
+        assert!(block.verify().is_ok());
     }
 }
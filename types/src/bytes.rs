@@ -1 +1,65 @@
-pub use bytes::Bytes;
\ No newline at end of file
+pub use bytes::Bytes;
+
+use crate::error::{Result, TypeError};
+
+/// `Bytes`的便捷方法，`len`和`is_empty`由`Bytes`本身提供
+///
+/// `Bytes`实现了`Deref<Target = [u8]>`和`AsRef<[u8]>`，可以直接切片、传给接受`&[u8]`的函数，
+/// 不需要先通过`to_vec`复制出一个`Vec`
+pub trait BytesExt: Sized {
+    /// 从十六进制字符串创建，`0x`前缀可有可无
+    fn from_hex(hex: &str) -> Result<Self>;
+
+    /// 以字节切片的形式访问
+    fn as_slice(&self) -> &[u8];
+}
+
+impl BytesExt for Bytes {
+    fn from_hex(hex: &str) -> Result<Self> {
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        let bytes =
+            hex::decode(hex).map_err(|e| TypeError::EncodingDecodingError(e.to_string()))?;
+
+        Ok(bytes.into())
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::crypto::hash;
+
+    #[test]
+    fn it_parses_hex_with_and_without_a_prefix() {
+        let with_prefix = Bytes::from_hex("0x0102ff").unwrap();
+        let without_prefix = Bytes::from_hex("0102ff").unwrap();
+
+        assert_eq!(with_prefix.as_slice(), &[0x01, 0x02, 0xff]);
+        assert_eq!(with_prefix, without_prefix);
+        assert!(Bytes::from_hex("0xzz").is_err());
+    }
+
+    #[test]
+    fn it_reports_the_length() {
+        let bytes = Bytes::from_hex("0x0102ff").unwrap();
+        let empty = Bytes::from_hex("0x").unwrap();
+
+        assert_eq!(bytes.len(), 3);
+        assert!(!bytes.is_empty());
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn it_hashes_bytes_without_copying() {
+        let bytes = Bytes::from_static(b"contract data");
+
+        assert_eq!(hash(&bytes), hash(b"contract data"));
+        assert_eq!(hash(&bytes[..8]), hash(b"contract"));
+        assert_eq!(bytes.as_ref().as_ptr(), bytes.as_slice().as_ptr());
+    }
+}
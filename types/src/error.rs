@@ -8,6 +8,21 @@ pub enum TypeError {
     #[error("Error converting a hex to U64: {0}")]
     HexToU64Error(String),
 
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
+
+    #[error("Block {0} has been pruned")]
+    BlockPruned(String),
+
+    #[error("Invalid call data: {0}")]
+    InvalidCallData(String),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Invalid quantity: {0}")]
+    InvalidQuantity(String),
+
     #[error("Invalid transaction: {0}")]
     InvalidTransaction(String),
 
@@ -20,6 +35,9 @@ pub enum TypeError {
     #[error("Missing transaction hash")]
     MissingTransactionHash,
 
+    #[error("Missing transaction signature")]
+    MissingTransactionSignature,
+
     #[error("{0}")]
     TrieError(String),
 
@@ -1,5 +1,5 @@
-
-use ethereum_types::U64;
+use ethereum_types::{U256, U64};
+use serde::{de, Deserialize, Deserializer};
 use std::fmt::{Display, LowerHex};
 
 use crate::error::TypeError;
@@ -14,3 +14,166 @@ where
 {
     format!("{:#x}", num)
 }
+
+/// 解析JSON-RPC中的数量，支持`0x`前缀的十六进制（允许前导零）和十进制字符串
+pub fn parse_quantity(quantity: &str) -> Result<U256, TypeError> {
+    match quantity
+        .strip_prefix("0x")
+        .or_else(|| quantity.strip_prefix("0X"))
+    {
+        Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| e.to_string()),
+        None => U256::from_dec_str(quantity).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| TypeError::InvalidQuantity(format!("{}: {}", quantity, e)))
+}
+
+/// 1 ether对应的wei的小数位数
+pub const ETHER_DECIMALS: usize = 18;
+
+fn wei_per_ether() -> U256 {
+    U256::exp10(ETHER_DECIMALS)
+}
+
+/// 将以wei为单位的数量格式化为以ether为单位的十进制字符串，省略小数部分末尾的零
+///
+/// 例如`1500000000000000000`格式化为`"1.5"`
+pub fn to_ether_string(wei: U256) -> String {
+    let (ether, remainder) = wei.div_mod(wei_per_ether());
+
+    if remainder.is_zero() {
+        return ether.to_string();
+    }
+
+    let fraction = format!("{:0>width$}", remainder.to_string(), width = ETHER_DECIMALS);
+
+    format!("{}.{}", ether, fraction.trim_end_matches('0'))
+}
+
+/// 将以ether为单位的十进制字符串解析为以wei为单位的数量
+///
+/// 小数部分最多18位，超出精度、包含非数字字符或数值溢出时返回`TypeError::InvalidQuantity`
+pub fn from_ether_string(ether: &str) -> Result<U256, TypeError> {
+    let invalid = |reason: &str| TypeError::InvalidQuantity(format!("{}: {}", ether, reason));
+    let (integer, fraction) = ether.split_once('.').unwrap_or((ether, ""));
+    let is_digits = |digits: &str| digits.chars().all(|c| c.is_ascii_digit());
+
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return Err(invalid("not a decimal number"));
+    }
+
+    if fraction.len() > ETHER_DECIMALS {
+        return Err(invalid("more than 18 decimal places"));
+    }
+
+    let fraction = format!("{:0<width$}", fraction, width = ETHER_DECIMALS);
+    let integer = U256::from_dec_str(integer).map_err(|e| invalid(&e.to_string()))?;
+    let fraction = U256::from_dec_str(&fraction).map_err(|e| invalid(&e.to_string()))?;
+
+    integer
+        .checked_mul(wei_per_ether())
+        .and_then(|wei| wei.checked_add(fraction))
+        .ok_or_else(|| invalid("overflow"))
+}
+
+/// JSON中的数量可以是字符串或数字
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Quantity {
+    Number(u64),
+    String(String),
+}
+
+impl Quantity {
+    fn into_u256<E: de::Error>(self) -> Result<U256, E> {
+        match self {
+            Quantity::Number(number) => Ok(U256::from(number)),
+            Quantity::String(quantity) => parse_quantity(&quantity).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// 反序列化数量字段，用于`#[serde(deserialize_with = "...")]`
+pub fn deserialize_quantity<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Quantity::deserialize(deserializer)?.into_u256()
+}
+
+/// 反序列化可选的数量字段，用于`#[serde(deserialize_with = "...")]`
+pub fn deserialize_optional_quantity<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Quantity>::deserialize(deserializer)?
+        .map(Quantity::into_u256)
+        .transpose()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde::{de::DeserializeOwned, Serialize};
+    use serde_json::Value;
+
+    /// 断言JSON中所有对象的字段名都是camelCase
+    fn assert_camel_case_keys(value: &Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    assert!(
+                        !key.contains('_') && key.starts_with(|c: char| c.is_ascii_lowercase()),
+                        "field {} is not camelCase",
+                        key
+                    );
+                    assert_camel_case_keys(value);
+                }
+            }
+            Value::Array(values) => values.iter().for_each(assert_camel_case_keys),
+            _ => {}
+        }
+    }
+
+    /// 面向JSON-RPC的结构体共用的序列化测试
+    ///
+    /// 断言序列化后的字段名都是camelCase，并且反序列化后再次序列化得到相同的JSON
+    pub(crate) fn assert_camel_case_round_trip<T: Serialize + DeserializeOwned>(value: &T) {
+        let json = serde_json::to_value(value).unwrap();
+        assert_camel_case_keys(&json);
+
+        let round_tripped: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(round_tripped).unwrap(), json);
+    }
+
+    #[test]
+    fn it_converts_ether_to_wei_and_back() {
+        let one_ether = U256::from(1_000_000_000_000_000_000_u64);
+
+        assert_eq!(from_ether_string("1").unwrap(), one_ether);
+        assert_eq!(to_ether_string(one_ether), "1");
+        assert_eq!(to_ether_string(U256::zero()), "0");
+    }
+
+    #[test]
+    fn it_converts_fractional_ether() {
+        let wei = U256::from(1_500_000_000_000_000_000_u64);
+
+        assert_eq!(from_ether_string("1.5").unwrap(), wei);
+        assert_eq!(to_ether_string(wei), "1.5");
+        assert_eq!(
+            from_ether_string("0.000000000000000001").unwrap(),
+            U256::one()
+        );
+        assert_eq!(to_ether_string(U256::one()), "0.000000000000000001");
+    }
+
+    #[test]
+    fn it_rejects_invalid_ether_strings() {
+        for ether in ["", ".5", "1.2.3", "-1", "abc", "0.0000000000000000001"] {
+            assert!(matches!(
+                from_ether_string(ether),
+                Err(TypeError::InvalidQuantity(_))
+            ));
+        }
+    }
+}
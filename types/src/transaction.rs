@@ -1,11 +1,15 @@
-use std::sync::Arc;
+#[cfg(test)]
+use std::cell::Cell;
+use std::sync::{Arc, OnceLock};
 
 use crate::account::Account;
 use crate::block::BlockNumber;
 use crate::bytes::Bytes;
 use crate::error::{Result, TypeError};
+use crate::helpers::{deserialize_optional_quantity, deserialize_quantity};
 use eth_trie::{EthTrie, MemoryDB, Trie};
-use ethereum_types::{Address, H160, H256, U256, U64};
+use ethereum_types::{Address, Bloom, BloomInput, H160, H256, U256, U64};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use utils::crypto::{
@@ -25,7 +29,11 @@ use utils::{PublicKey, RecoverableSignature, RecoveryId, SecretKey};
 /// - `value`: 交易中转移的金额值。
 /// - `data`: 可选字段，代表交易的数据部分，通常用于合约调用或创建。
 /// - `gas`: 交易中使用的gas量。
-/// - `gas_price`: 交易中使用的gas价格。
+/// - `gas_price`: 交易中使用的gas价格，legacy交易按此价格支付。
+/// - `max_fee_per_gas`: 可选字段，EIP-1559交易愿意支付的最高gas价格。
+/// - `max_priority_fee_per_gas`: 可选字段，EIP-1559交易在基础费用之上支付给出块者的小费上限。
+/// - `chain_id`: 可选字段，按EIP-155签名时绑定的链ID，签名和验证时都会使用。
+/// - `signature`: 可选字段，代表交易的签名。未签名时为空，交易哈希使用不包含签名的编码计算，因此签名不影响交易哈希。
 pub struct Transaction {
     pub from: Address,
     pub to: Option<Address>,
@@ -37,8 +45,47 @@ pub struct Transaction {
     pub data: Option<Bytes>,
     pub gas: U256,
     pub gas_price: U256,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+    #[serde(default)]
+    pub signature: Option<Signature>,
 }
 
+/// 计算交易哈希和签名时使用的交易编码，不包含交易哈希和签名
+///
+/// 交易本身的序列化结果始终包含签名字段，以便bincode等非自描述格式能够正确反序列化，
+/// 因此哈希和签名使用这个单独的编码，签名前后得到相同的交易哈希
+#[derive(Serialize)]
+struct UnsignedTransaction<'a> {
+    from: &'a Address,
+    to: &'a Option<Address>,
+    nonce: &'a Option<U256>,
+    value: &'a U256,
+    data: &'a Option<Bytes>,
+    gas: &'a U256,
+    gas_price: &'a U256,
+    max_fee_per_gas: &'a Option<U256>,
+    max_priority_fee_per_gas: &'a Option<U256>,
+    chain_id: &'a Option<u64>,
+}
+
+/// 普通转账默认的gas量，等于其固有gas，带数据的交易默认使用各自的固有gas
+pub const DEFAULT_GAS: u64 = TRANSACTION_GAS;
+/// 交易默认的gas价格
+pub const DEFAULT_GAS_PRICE: u64 = 10;
+/// 每笔交易的基础gas消耗
+pub const TRANSACTION_GAS: u64 = 21_000;
+/// 部署合约额外的gas消耗
+pub const CONTRACT_CREATION_GAS: u64 = 32_000;
+/// 交易数据中每个零字节的gas消耗
+pub const ZERO_BYTE_GAS: u64 = 4;
+/// 交易数据中每个非零字节的gas消耗
+pub const NON_ZERO_BYTE_GAS: u64 = 16;
+
 /// 交易类型枚举，用于区分不同的交易种类
 pub enum TransactionKind {
     /// 普通交易，包含交易双方地址和交易金额
@@ -49,6 +96,49 @@ pub enum TransactionKind {
     ContractExecution(Address, Address, Bytes),
 }
 
+impl TransactionKind {
+    /// 解码合约执行交易的数据，得到调用的函数名和参数
+    ///
+    /// 参数:
+    /// - `data`: 合约执行交易的数据，由`(函数名, 参数列表)`经bincode编码得到
+    ///
+    /// 返回值:
+    /// - `Result<(String, Vec<String>)>`: 函数名和参数列表，数据无法解码时返回`InvalidCallData`
+    pub fn decode_call_data(data: &Bytes) -> Result<(String, Vec<String>)> {
+        bincode::deserialize(data).map_err(|e| TypeError::InvalidCallData(e.to_string()))
+    }
+}
+
+/// EIP-155签名中加到恢复ID上的偏移量`chain_id * 2 + 35`
+fn eip155_offset(chain_id: u64) -> Result<u64> {
+    chain_id
+        .checked_mul(2)
+        .and_then(|offset| offset.checked_add(35))
+        .ok_or_else(|| TypeError::InvalidTransaction(format!("chain id {} is too large", chain_id)))
+}
+
+#[cfg(test)]
+thread_local! {
+    // 测试中统计当前线程进行的公钥恢复次数，用于比较不同调用方式的开销
+    static KEY_RECOVERIES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// 根据消息、签名字节和恢复ID恢复公钥，这是签名相关操作中代价最高的一步
+fn recover_key(
+    message: &[u8],
+    signature_bytes: &[u8],
+    recovery_id: RecoveryId,
+) -> Result<PublicKey> {
+    #[cfg(test)]
+    KEY_RECOVERIES.with(|count| count.set(count.get() + 1));
+
+    Ok(recover_public_key(
+        message,
+        signature_bytes,
+        recovery_id.to_i32(),
+    )?)
+}
+
 impl Transaction {
     pub fn new(
         from: Account,
@@ -71,13 +161,12 @@ impl Transaction {
             if data.is_empty() {
                 None
             } else {
-                let decoded_str = String::from_utf8(data.to_vec())
+                let decoded_str = std::str::from_utf8(&data)
                     .map_err(|e| TypeError::EncodingDecodingError(e.to_string()))?;
-                match decoded_str.as_str() {
-                    "Erc20" | "erc20" => Some(Bytes::from(
-                        include_bytes!("./../../target/wasm32-unknown-unknown/release/erc20.wasm")
-                            .to_vec(),
-                    )),
+                match decoded_str {
+                    "Erc20" | "erc20" => Some(Bytes::from_static(include_bytes!(
+                        "./../../target/wasm32-unknown-unknown/release/erc20.wasm"
+                    ))),
                     _ => {
                         let params = decoded_str.trim().split(',').collect::<Vec<&str>>();
                         let to_encode = (params[0], params[1..].to_vec());
@@ -96,23 +185,95 @@ impl Transaction {
             nonce,
             hash: None,
             data,
-            gas: U256::from(10),
-            gas_price: U256::from(10),
+            gas: U256::from(DEFAULT_GAS),
+            gas_price: U256::from(DEFAULT_GAS_PRICE),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            chain_id: None,
+            signature: None,
         };
 
+        // 默认的gas上限刚好覆盖固有gas，保证未指定gas的交易不会因gas不足被拒绝
+        transaction.gas = transaction.intrinsic_gas();
         transaction.hash()?;
 
         Ok(transaction)
     }
 
+    /// 创建一个交易构建器
+    pub fn builder() -> TransactionBuilder {
+        TransactionBuilder::default()
+    }
+
     pub fn hash(&mut self) -> Result<H256> {
-        let serialized = bincode::serialize(&self)?;
+        let serialized = self.signing_message()?;
         let hash: H256 = hash(&serialized).into();
         self.hash = Some(hash);
 
         self.transaction_hash()
     }
 
+    /// 获取交易被签名的消息，即不包含交易哈希和签名的交易编码
+    ///
+    /// 签名后的交易与未签名的交易得到相同的消息，因此两者的交易哈希一致
+    pub fn signing_message(&self) -> Result<Vec<u8>> {
+        let unsigned = UnsignedTransaction {
+            from: &self.from,
+            to: &self.to,
+            nonce: &self.nonce,
+            value: &self.value,
+            data: &self.data,
+            gas: &self.gas,
+            gas_price: &self.gas_price,
+            max_fee_per_gas: &self.max_fee_per_gas,
+            max_priority_fee_per_gas: &self.max_priority_fee_per_gas,
+            chain_id: &self.chain_id,
+        };
+
+        Ok(bincode::serialize(&unsigned)?)
+    }
+
+    /// 交易在执行前的固有gas消耗
+    ///
+    /// 基础消耗为21000，交易数据中每个零字节4 gas、非零字节16 gas，部署合约额外消耗32000
+    pub fn intrinsic_gas(&self) -> U256 {
+        let data_gas: u64 = self
+            .data
+            .iter()
+            .flat_map(|data| data.iter())
+            .map(|byte| match byte {
+                0 => ZERO_BYTE_GAS,
+                _ => NON_ZERO_BYTE_GAS,
+            })
+            .sum();
+        let creation_gas = match self.to {
+            None => CONTRACT_CREATION_GAS,
+            Some(_) => 0,
+        };
+
+        U256::from(TRANSACTION_GAS) + U256::from(data_gas) + U256::from(creation_gas)
+    }
+
+    /// 交易执行实际消耗的gas，即交易的固有gas
+    ///
+    /// gas上限低于固有gas的交易在进入交易池和执行前就被拒绝，因此不会超过gas上限
+    pub fn gas_used(&self) -> U256 {
+        self.intrinsic_gas()
+    }
+
+    /// 交易在给定基础费用下实际支付的gas价格
+    ///
+    /// EIP-1559交易支付`min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)`，
+    /// legacy交易支付`gas_price`
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            (Some(max_fee), Some(max_priority_fee)) => {
+                max_fee.min(base_fee.saturating_add(max_priority_fee))
+            }
+            _ => self.gas_price,
+        }
+    }
+
     pub fn transaction_hash(&self) -> Result<H256> {
         self.hash.ok_or(TypeError::MissingTransactionHash)
     }
@@ -128,39 +289,46 @@ impl Transaction {
 
     /// 使用给定的密钥对交易进行签名
     ///
-    /// 该方法首先将交易信息序列化为字节流，然后使用密钥对其进行签名
-    /// 签名过程产生一个可恢复的签名，从中我们可以提取出签名的v、r、s值
-    /// 最后，将这些签名值连同原始交易数据一起封装成一个签名交易对象，并返回
+    /// 对不包含签名的交易序列化结果进行签名，产生一个可恢复的签名，
+    /// 签名保存在交易自身的`signature`字段中，签名交易只是该交易的一个视图
     ///
     /// # 参数
     /// * `key` - 用于签名交易的密钥
     ///
     /// # 返回
-    /// 如果签名成功，返回一个`SignedTransaction`对象，包含签名信息和原始交易数据
+    /// 如果签名成功，返回一个`SignedTransaction`对象，包含带有签名的交易
     /// 如果签名过程中出现错误，返回相应的错误
     pub fn sign(&self, key: SecretKey) -> Result<SignedTransaction> {
-        // 将交易信息序列化为字节流
-        let encoded = bincode::serialize(&self)?;
-        // 使用密钥对序列化的交易信息进行签名，产生一个可恢复的签名
-        let recoverable_signature = sign_recovery(&encoded, &key)?;
-        // 将可恢复的签名序列化为紧凑形式，获取签名的字节表示
-        let (_, signature_bytes) = recoverable_signature.serialize_compact();
-        // 从可恢复的签名中提取出v、r、s值
-        let Signature { v, r, s } = recoverable_signature.into();
-        // 计算签名的哈希值，作为交易的标识
-        let transaction_hash = hash(&signature_bytes).into();
-
-        // 创建签名交易对象
-        let signed_transaction = SignedTransaction {
-            v,
-            r,
-            s,
-            raw_transaction: encoded.into(),
-            transaction_hash,
-        };
+        // 对不包含签名的交易序列化结果进行签名，产生一个可恢复的签名
+        let recoverable_signature = sign_recovery(&self.signing_message()?, &key)?;
+
+        // 将签名保存到交易中
+        let mut transaction = self.clone();
+        transaction.signature = Some(recoverable_signature.into());
 
-        // 返回签名交易对象
-        Ok(signed_transaction)
+        SignedTransaction::try_from(transaction)
+    }
+
+    /// 按EIP-155使用给定的密钥和链ID对交易进行签名
+    ///
+    /// 链ID保存在交易的`chain_id`字段中并参与签名的消息，签名的`v`为`恢复ID + chain_id * 2 + 35`，
+    /// 因此签名不能在其他链上重放，验证和恢复发送者时会根据`chain_id`还原恢复ID
+    ///
+    /// # 参数
+    /// * `key` - 用于签名交易的密钥
+    /// * `chain_id` - 交易所在链的ID
+    ///
+    /// # 返回
+    /// 如果签名成功，返回一个`SignedTransaction`对象，包含带有签名和链ID的交易
+    pub fn sign_eip155(&self, key: SecretKey, chain_id: u64) -> Result<SignedTransaction> {
+        let mut transaction = self.clone();
+        transaction.chain_id = Some(chain_id);
+
+        let mut signature: Signature = sign_recovery(&transaction.signing_message()?, &key)?.into();
+        signature.v += eip155_offset(chain_id)?;
+        transaction.signature = Some(signature);
+
+        SignedTransaction::try_from(transaction)
     }
 
     /// 验证签名的交易是否合法
@@ -172,20 +340,60 @@ impl Transaction {
     /// # 返回值
     /// 返回一个布尔值，表示交易的合法性（`true` 表示合法，`false` 表示不合法）
     pub fn verify(signed_transaction: SignedTransaction, address: Address) -> Result<bool> {
+        // 只进行一次公钥恢复，同时得到恢复的地址和签名验证结果
+        let (_, recovered_address, verified) = Self::recover(signed_transaction)?;
+
+        // 检查恢复的公钥地址是否与提供的发送方地址匹配
+        let addresses_match = address == recovered_address;
+
+        // 返回签名验证和地址匹配的逻辑与结果
+        Ok(verified && addresses_match)
+    }
+
+    /// 并行验证一批已签名的交易
+    ///
+    /// 每笔交易对应`addresses`中相同位置的发送方地址，返回每笔交易的验证结果，
+    /// 签名无效、发送方不匹配或缺少对应的地址时结果为`false`
+    ///
+    /// # 参数
+    /// * `transactions` - 已签名的交易
+    /// * `addresses` - 每笔交易的发送方地址
+    /// # 返回值
+    /// 与`transactions`顺序一致的验证结果
+    pub fn verify_batch(transactions: &[SignedTransaction], addresses: &[Address]) -> Vec<bool> {
+        transactions
+            .par_iter()
+            .enumerate()
+            .map(|(index, transaction)| match addresses.get(index) {
+                Some(address) => Self::verify(transaction.clone(), *address).unwrap_or(false),
+                None => false,
+            })
+            .collect()
+    }
+
+    /// 从已签名的交易中一次性恢复公钥、地址并验证签名
+    ///
+    /// `verify`、`recover_address` 和 `recover_public_key` 各自都需要进行一次代价较高的
+    /// ECDSA公钥恢复，需要多个结果的调用方可以使用该函数，只付出一次恢复的开销
+    ///
+    /// # 参数
+    ///
+    /// * `signed_transaction` - 已签名的交易
+    ///
+    /// # 返回
+    ///
+    /// 返回一个元组：恢复的公钥、公钥对应的地址，以及签名是否能被该公钥验证
+    pub fn recover(signed_transaction: SignedTransaction) -> Result<(PublicKey, Address, bool)> {
         // 从已签名的交易中提取消息、恢复ID和签名字节
         let (message, recovery_id, signature_bytes) = Self::recover_pieces(signed_transaction)?;
 
         // 根据消息、签名字节和恢复ID恢复公钥
-        let key = recover_public_key(&message, &signature_bytes, recovery_id.to_i32())?;
+        let key = recover_key(&message, &signature_bytes, recovery_id)?;
 
         // 验证消息的签名是否与恢复的公钥匹配
         let verified = verify(&message, &signature_bytes, &key)?;
 
-        // 检查恢复的公钥地址是否与提供的发送方地址匹配
-        let addresses_match = address == public_key_address(&key);
-
-        // 返回签名验证和地址匹配的逻辑与结果
-        Ok(verified && addresses_match)
+        Ok((key, public_key_address(&key), verified))
     }
 
     /// 从已签名的交易中恢复发送者的地址
@@ -224,7 +432,7 @@ impl Transaction {
         let (message, recovery_id, signature_bytes) = Self::recover_pieces(signed_transaction)?;
 
         // 使用提取的信息来恢复公钥
-        let key = recover_public_key(&message, &signature_bytes, recovery_id.to_i32())?;
+        let key = recover_key(&message, &signature_bytes, recovery_id)?;
 
         // 返回恢复的公钥
         Ok(key)
@@ -252,11 +460,23 @@ impl Transaction {
     fn recover_pieces(
         signed_transaction: SignedTransaction,
     ) -> Result<(Vec<u8>, RecoveryId, [u8; 64])> {
-        // 获取原始消息，这里是签名交易的原始交易信息
-        let message = signed_transaction.raw_transaction.to_owned();
+        // 获取原始消息，这里是不包含签名的交易序列化结果
+        let message = signed_transaction.transaction().signing_message()?;
 
-        // 将签名交易转换为签名对象
-        let signature: Signature = signed_transaction.into();
+        // 获取交易的签名，按EIP-155签名的交易需要先从v中去掉链ID得到恢复ID
+        let mut signature = signed_transaction.signature();
+
+        if let Some(chain_id) = signed_transaction.transaction().chain_id {
+            signature.v = signature
+                .v
+                .checked_sub(eip155_offset(chain_id)?)
+                .ok_or_else(|| {
+                    TypeError::InvalidTransaction(format!(
+                        "signature v {} does not match chain id {}",
+                        signature.v, chain_id
+                    ))
+                })?;
+        }
 
         // 尝试将签名转换为可恢复的签名，这可能失败，因此使用try_into并返回可能的错误
         let recoverable_signature: RecoverableSignature = signature.try_into()?;
@@ -265,7 +485,7 @@ impl Transaction {
         let (recovery_id, signature_bytes) = recoverable_signature.serialize_compact();
 
         // 返回包含消息、恢复ID和签名字节的结果
-        Ok((message.to_vec(), recovery_id, signature_bytes))
+        Ok((message, recovery_id, signature_bytes))
     }
 
     fn to_trie(transactions: &[Transaction]) -> Result<EthTrie<MemoryDB>> {
@@ -293,41 +513,170 @@ impl Transaction {
     }
 }
 
+/// 交易构建器，通过链式调用设置交易字段
+///
+/// 未设置的字段使用默认值：`value` 为0，`gas` 为交易的固有gas，`gas_price` 为
+/// `DEFAULT_GAS_PRICE`，`from` 为必填字段。
+///
+/// ```ignore
+/// let transaction = Transaction::builder()
+///     .from(from)
+///     .to(to)
+///     .value(U256::from(10))
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TransactionBuilder {
+    from: Option<Address>,
+    to: Option<Address>,
+    value: Option<U256>,
+    nonce: Option<U256>,
+    data: Option<Bytes>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+}
+
+impl TransactionBuilder {
+    /// 设置交易发起者的地址
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// 设置交易接收者的地址
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// 设置交易转移的金额
+    pub fn value(mut self, value: U256) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// 设置交易的nonce值
+    pub fn nonce(mut self, nonce: U256) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// 设置交易的数据部分
+    pub fn data(mut self, data: Bytes) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// 设置交易的gas量
+    pub fn gas(mut self, gas: U256) -> Self {
+        self.gas = Some(gas);
+        self
+    }
+
+    /// 设置交易的gas价格
+    pub fn gas_price(mut self, gas_price: U256) -> Self {
+        self.gas_price = Some(gas_price);
+        self
+    }
+
+    /// 构建交易，填充默认值并计算交易哈希
+    ///
+    /// 如果没有设置 `from`，返回 `TypeError::InvalidTransaction`
+    pub fn build(self) -> Result<Transaction> {
+        let from = self
+            .from
+            .ok_or_else(|| TypeError::InvalidTransaction("missing from".into()))?;
+        let value = self.value.unwrap_or_else(U256::zero);
+        let mut transaction = Transaction::new(from, self.to, value, self.nonce, self.data)?;
+
+        transaction.gas = self.gas.unwrap_or(transaction.gas);
+        transaction.gas_price = self.gas_price.unwrap_or(transaction.gas_price);
+
+        // gas相关字段可能发生变化，需要重新计算交易哈希
+        transaction.hash = None;
+        transaction.hash()?;
+
+        Ok(transaction)
+    }
+}
+
 /// 表示一个已签名的交易。
 ///
-/// 这个结构体包含了签名交易的所有必要信息，包括签名的v、r、s值，原始交易数据以及交易的哈希值。
+/// 签名保存在交易自身的`signature`字段中，这个结构体只是带有签名的交易的一个视图，
+/// 保证其中的交易一定包含签名。序列化时与其中的交易完全相同，反序列化时会检查签名是否存在。
 ///
 /// 字段说明：
-/// - `v`: 签名的恢复ID。
-/// - `r`: ECDSA签名的一部分,它是由随机数 k 和交易数据的哈希值共同决定的。
-/// - `s`: ECDSA签名的另一部分,是通过私钥 d、随机数 k、交易数据的哈希值 z 以及 r 计算得出的。
-/// - `raw_transaction`: 交易的原始字节数据。
-/// - `transaction_hash`: 交易的哈希值，用于唯一标识该交易。
+/// - `transaction`: 带有签名的交易。
+/// - `signature`: 交易的签名，与交易中的签名相同，便于直接访问。
+/// - `sender`: 从签名中恢复出的发送者地址的缓存，不参与序列化。
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "Transaction", into = "Transaction")]
 pub struct SignedTransaction {
-    pub v: u64,
-    pub r: H256,
-    pub s: H256,
-    pub raw_transaction: Bytes,
-    pub transaction_hash: H256,
+    transaction: Transaction,
+    signature: Signature,
+    sender: SenderCache,
 }
 
-impl From<SignedTransaction> for Signature {
-    fn from(value: SignedTransaction) -> Self {
-        Signature {
-            v: value.v,
-            r: value.r,
-            s: value.s,
+/// 发送者地址的缓存，缓存是否已填充不影响交易的相等比较
+#[derive(Clone, Debug, Default)]
+struct SenderCache(OnceLock<Address>);
+
+impl PartialEq for SenderCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl SignedTransaction {
+    /// 获取带有签名的交易
+    pub fn transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+
+    /// 获取交易的签名
+    pub fn signature(&self) -> Signature {
+        self.signature
+    }
+
+    /// 获取交易的哈希值，与未签名时的交易哈希相同
+    pub fn transaction_hash(&self) -> Result<H256> {
+        self.transaction.transaction_hash()
+    }
+
+    /// 获取交易的发送者地址
+    ///
+    /// 第一次调用时从签名中恢复地址并缓存，之后的调用直接返回缓存的地址，
+    /// 避免重复进行代价较高的公钥恢复
+    pub fn sender(&self) -> Result<Address> {
+        if let Some(sender) = self.sender.0.get() {
+            return Ok(*sender);
         }
+
+        let sender = Transaction::recover_address(self.clone())?;
+
+        Ok(*self.sender.0.get_or_init(|| sender))
     }
 }
 
-impl TryInto<Transaction> for SignedTransaction {
+impl TryFrom<Transaction> for SignedTransaction {
     type Error = TypeError;
 
-    fn try_into(self) -> Result<Transaction> {
-        bincode::deserialize(&self.raw_transaction)
-            .map_err(|e| TypeError::EncodingDecodingError(e.to_string()))
+    fn try_from(transaction: Transaction) -> Result<Self> {
+        let signature = transaction
+            .signature
+            .ok_or(TypeError::MissingTransactionSignature)?;
+
+        Ok(SignedTransaction {
+            transaction,
+            signature,
+            sender: SenderCache::default(),
+        })
+    }
+}
+
+impl From<SignedTransaction> for Transaction {
+    fn from(value: SignedTransaction) -> Self {
+        value.transaction
     }
 }
 
@@ -336,14 +685,33 @@ impl TryInto<Transaction> for SignedTransaction {
 #[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct TransactionRequest {
     pub data: Option<Bytes>,
+    #[serde(deserialize_with = "deserialize_quantity")]
     pub gas: U256,
+    #[serde(deserialize_with = "deserialize_quantity")]
     pub gas_price: U256,
     pub from: Option<Address>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub to: Option<Address>,
+    #[serde(default, deserialize_with = "deserialize_optional_quantity")]
     pub value: Option<U256>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_quantity"
+    )]
     pub nonce: Option<U256>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_quantity"
+    )]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_quantity"
+    )]
+    pub max_priority_fee_per_gas: Option<U256>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub r: Option<U256>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -360,6 +728,8 @@ impl From<Transaction> for TransactionRequest {
             gas: value.gas,
             gas_price: value.gas_price,
             nonce: value.nonce,
+            max_fee_per_gas: value.max_fee_per_gas,
+            max_priority_fee_per_gas: value.max_priority_fee_per_gas,
             r: None,
             s: None,
         }
@@ -371,8 +741,22 @@ impl TryInto<Transaction> for TransactionRequest {
 
     fn try_into(self) -> Result<Transaction> {
         let value = self.value.unwrap_or(U256::zero());
-        let from = self.from.unwrap_or(H160::zero());
-        Transaction::new(from, self.to, value, self.nonce, self.data)
+        // 缺少发送方时直接报错，而不是使用零地址后在查找账户时失败
+        let from = self
+            .from
+            .ok_or_else(|| TypeError::InvalidTransaction("missing from".into()))?;
+        let mut transaction = Transaction::new(from, self.to, value, self.nonce, self.data)?;
+
+        // 保留请求中的gas和gas价格，交易池依据gas价格决定打包顺序，
+        // EIP-1559交易同时保留最高gas价格和小费上限
+        transaction.gas = self.gas;
+        transaction.gas_price = self.gas_price;
+        transaction.max_fee_per_gas = self.max_fee_per_gas;
+        transaction.max_priority_fee_per_gas = self.max_priority_fee_per_gas;
+        transaction.hash = None;
+        transaction.hash()?;
+
+        Ok(transaction)
     }
 }
 
@@ -383,10 +767,59 @@ pub struct TransactionReceipt {
     pub block_number: Option<BlockNumber>,
     pub contract_address: Option<H160>,
     pub transaction_hash: H256,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    /// 交易执行结果，1表示成功，0表示合约执行回滚
+    #[serde(default)]
+    pub status: U64,
+    /// 区块中截至该交易（含）所有交易消耗的gas之和
+    #[serde(default)]
+    pub cumulative_gas_used: U256,
+    /// 交易实际支付的gas价格
+    #[serde(default)]
+    pub effective_gas_price: U256,
+    /// 合约执行回滚的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all(serialize = "snake_case", deserialize = "camelCase"))]
+/// 模拟执行过程中单个账户的状态变化
+///
+/// 记录账户在模拟执行前后的余额与nonce，便于调用方在提交交易前预览其影响
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct AccountDiff {
+    pub address: Address,
+    pub balance_before: U256,
+    pub balance_after: U256,
+    pub nonce_before: U256,
+    pub nonce_after: U256,
+}
+
+/// 交易模拟执行的结果
+///
+/// 模拟执行基于当前状态的快照，不会持久化任何状态变化。
+///
+/// 字段说明：
+/// - `success`: 交易是否执行成功。
+/// - `gas_used`: 交易消耗的gas量。
+/// - `error`: 执行失败时的错误原因。
+/// - `logs`: 执行过程中产生的日志。
+/// - `state_diff`: 交易涉及的账户在执行前后的状态变化。
+/// - `contract_address`: 合约部署交易创建的合约地址。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct SimulationResult {
+    pub success: bool,
+    pub gas_used: U256,
+    pub error: Option<String>,
+    pub logs: Vec<Log>,
+    pub state_diff: Vec<AccountDiff>,
+    pub contract_address: Option<H160>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
 pub struct Log {
     pub address: H160,
     pub block_hash: Option<H256>,
@@ -401,9 +834,81 @@ pub struct Log {
     pub transaction_log_index: Option<U256>,
 }
 
+/// 日志过滤条件，对应`eth_getLogs`的过滤对象
+///
+/// 字段说明：
+/// - `from_block`: 起始区块号（包含），为空时从创世块开始。
+/// - `to_block`: 结束区块号（包含），为空时到最新区块为止。
+/// - `address`: 产生日志的合约地址。
+/// - `topics`: 按位置匹配的日志主题。
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all(serialize = "camelCase", deserialize = "camelCase"))]
+pub struct LogFilter {
+    pub from_block: Option<U64>,
+    pub to_block: Option<U64>,
+    pub address: Option<Address>,
+    pub topics: Option<Vec<H256>>,
+}
+
+/// 计算一组日志的布隆过滤器，包含每条日志的地址和所有主题
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    let mut bloom = Bloom::zero();
+
+    for log in logs {
+        bloom.accrue(BloomInput::Raw(log.address.as_bytes()));
+
+        for topic in log.topics.iter() {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+        }
+    }
+
+    bloom
+}
+
+impl LogFilter {
+    /// 判断区块号是否在过滤范围内
+    pub fn matches_block(&self, block_number: U64) -> bool {
+        self.from_block.is_none_or(|from| block_number >= from)
+            && self.to_block.is_none_or(|to| block_number <= to)
+    }
+
+    /// 判断布隆过滤器是否可能包含满足条件的日志
+    ///
+    /// 返回false时区块中一定没有匹配的日志，返回true时仍需逐条检查日志
+    pub fn matches_bloom(&self, bloom: &Bloom) -> bool {
+        let address_matches = self
+            .address
+            .is_none_or(|address| bloom.contains_input(BloomInput::Raw(address.as_bytes())));
+        let topics_match = self.topics.as_ref().is_none_or(|topics| {
+            topics
+                .iter()
+                .all(|topic| bloom.contains_input(BloomInput::Raw(topic.as_bytes())))
+        });
+
+        address_matches && topics_match
+    }
+
+    /// 判断日志是否满足过滤条件
+    ///
+    /// 地址需要完全一致，主题按位置逐一比较
+    pub fn matches(&self, log: &Log) -> bool {
+        let address_matches = self.address.is_none_or(|address| address == log.address);
+        let topics_match = self.topics.as_ref().is_none_or(|topics| {
+            topics
+                .iter()
+                .enumerate()
+                .all(|(index, topic)| log.topics.get(index) == Some(topic))
+        });
+
+        address_matches && topics_match
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::helpers::tests::assert_camel_case_round_trip;
     use ethereum_types::U256;
     use std::{convert::From, str::FromStr};
     use utils::crypto::{keypair, public_key_address};
@@ -461,6 +966,74 @@ mod tests {
         assert!(verifies);
     }
 
+    /// 测试批量验证交易时返回每笔交易各自的验证结果
+    #[test]
+    fn it_verifies_a_batch_of_signed_transactions() {
+        let mut transactions = vec![];
+        let mut addresses = vec![];
+
+        for _ in 0..4 {
+            let (secret_key, public_key) = keypair();
+            let mut transaction = new_transaction();
+            transaction.from = public_key_address(&public_key);
+            transactions.push(transaction.sign(secret_key).unwrap());
+            addresses.push(transaction.from);
+        }
+
+        // 第三笔交易的发送方与签名者不一致
+        let (_, other_public_key) = keypair();
+        addresses[2] = public_key_address(&other_public_key);
+
+        assert_eq!(
+            Transaction::verify_batch(&transactions, &addresses),
+            vec![true, true, false, true]
+        );
+        assert_eq!(
+            Transaction::verify_batch(&transactions, &addresses[..3]),
+            vec![true, true, false, false]
+        );
+    }
+
+    /// 测试签名保存在交易自身中，签名前后交易哈希保持一致
+    #[test]
+    fn it_stores_the_signature_on_the_transaction() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        let signed = transaction.sign(secret_key).unwrap();
+
+        assert_eq!(
+            signed.transaction_hash().unwrap(),
+            transaction.hash.unwrap()
+        );
+        assert_eq!(signed.transaction().signature, Some(signed.signature()));
+        assert_eq!(
+            signed.transaction().signing_message().unwrap(),
+            transaction.signing_message().unwrap()
+        );
+    }
+
+    /// 测试签名交易经过序列化和反序列化后仍然可以验证和恢复
+    #[test]
+    fn it_round_trips_a_signed_transaction() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        let signed = transaction.sign(secret_key).unwrap();
+
+        let encoded = bincode::serialize(&signed).unwrap();
+        let decoded: SignedTransaction = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded, signed);
+        assert!(Transaction::verify(decoded.clone(), transaction.from).unwrap());
+        assert_eq!(
+            Transaction::recover_address(decoded).unwrap(),
+            transaction.from
+        );
+
+        let unsigned = serde_json::to_string(&transaction).unwrap();
+        assert!(serde_json::from_str::<SignedTransaction>(&unsigned).is_err());
+    }
+
     /// 测试计算交易树的根哈希值
     ///
     /// 该测试函数验证了给定一组交易后计算出的Merkle树根哈希值是否符合预期
@@ -473,9 +1046,296 @@ mod tests {
         let root = Transaction::root_hash(&vec![transaction_1, transaction_2]).unwrap();
         // 预期的根哈希值
         let expected =
-            H256::from_str("0xa3b8c35bab6501806ed681220afe26a0d46774a6aa56d044b0f6aef0f3f0d682")
+            H256::from_str("0x0a3608d2198636152e65c0c0edb5f587edcb47b91ef28fb31c0beb302501ee65")
                 .unwrap();
         // 验证计算出的根哈希值与预期值是否一致
         assert_eq!(root, expected);
     }
+
+    /// 测试使用构建器创建转账交易
+    #[test]
+    fn it_builds_a_transfer() {
+        let transaction = new_transaction();
+        let built = Transaction::builder()
+            .from(transaction.from)
+            .to(transaction.to.unwrap())
+            .value(transaction.value)
+            .build()
+            .unwrap();
+
+        assert_eq!(built, transaction);
+    }
+
+    /// 测试使用构建器创建合约调用交易，并覆盖默认的gas设置
+    #[test]
+    fn it_builds_a_contract_call() {
+        let from = H160::from_str("0x4a0d457e884ebd9b9773d172ed687417caac4f14").unwrap();
+        let contract = H160::from_str("0x6b78fa07883d5c5b527da9828ac77f5aa5a61d3b").unwrap();
+        let data = Bytes::from("mint,String,0x4a0d457e884ebd9b9773d172ed687417caac4f14,U64,10");
+        let nonce = U256::from(1);
+
+        let mut expected = Transaction::new(
+            from,
+            Some(contract),
+            U256::zero(),
+            Some(nonce),
+            Some(data.clone()),
+        )
+        .unwrap();
+        expected.gas = U256::from(1_000);
+        expected.gas_price = U256::from(20);
+        expected.hash = None;
+        expected.hash().unwrap();
+
+        let built = Transaction::builder()
+            .from(from)
+            .to(contract)
+            .nonce(nonce)
+            .data(data)
+            .gas(U256::from(1_000))
+            .gas_price(U256::from(20))
+            .build()
+            .unwrap();
+
+        assert_eq!(built, expected);
+    }
+
+    /// 测试发送者地址在多次调用之间保持一致并与恢复的地址相同
+    #[test]
+    fn it_caches_the_recovered_sender() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        let signed = transaction.sign(secret_key).unwrap();
+
+        let sender = signed.sender().unwrap();
+
+        assert_eq!(sender, signed.sender().unwrap());
+        assert_eq!(sender, Transaction::recover_address(signed).unwrap());
+        assert_eq!(sender, transaction.from);
+    }
+
+    /// 测试一次性恢复的结果与单独调用各个方法的结果一致
+    #[test]
+    fn it_recovers_key_address_and_verification_at_once() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        let signed = transaction.sign(secret_key).unwrap();
+
+        let (key, address, verified) = Transaction::recover(signed.clone()).unwrap();
+
+        assert_eq!(
+            key,
+            Transaction::recover_public_key(signed.clone()).unwrap()
+        );
+        assert_eq!(
+            address,
+            Transaction::recover_address(signed.clone()).unwrap()
+        );
+        assert_eq!(
+            verified,
+            Transaction::verify(signed, transaction.from).unwrap()
+        );
+    }
+
+    /// 比较一次性恢复与分别验证、恢复地址所需的公钥恢复次数，
+    /// 以恢复次数代替耗时，结果不受机器负载的影响
+    #[test]
+    fn recovering_once_does_less_work_than_verifying_and_recovering() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        let signed = transaction.sign(secret_key).unwrap();
+        let recoveries = |f: &dyn Fn()| {
+            let before = KEY_RECOVERIES.with(Cell::get);
+            f();
+            KEY_RECOVERIES.with(Cell::get) - before
+        };
+
+        let combined = recoveries(&|| {
+            Transaction::recover(signed.clone()).unwrap();
+        });
+        let separate = recoveries(&|| {
+            Transaction::verify(signed.clone(), transaction.from).unwrap();
+            Transaction::recover_address(signed.clone()).unwrap();
+        });
+
+        assert_eq!(combined, 1);
+        assert_eq!(separate, 2);
+    }
+
+    /// 测试legacy交易和EIP-1559交易实际支付的gas价格
+    #[test]
+    fn it_computes_the_effective_gas_price() {
+        let mut legacy = new_transaction();
+        legacy.gas_price = U256::from(20);
+        assert_eq!(legacy.effective_gas_price(U256::from(7)), U256::from(20));
+
+        let mut eip1559 = new_transaction();
+        eip1559.max_fee_per_gas = Some(U256::from(20));
+        eip1559.max_priority_fee_per_gas = Some(U256::from(2));
+        // 基础费用加小费低于最高gas价格时支付两者之和
+        assert_eq!(eip1559.effective_gas_price(U256::from(7)), U256::from(9));
+        // 超过最高gas价格时按最高gas价格支付
+        assert_eq!(eip1559.effective_gas_price(U256::from(19)), U256::from(20));
+    }
+
+    #[test]
+    fn it_deserializes_quantities_in_any_form() {
+        let request = |quantity: &str| -> TransactionRequest {
+            let json = format!(
+                r#"{{"gas": {0}, "gasPrice": {0}, "value": {0}, "nonce": {0}}}"#,
+                quantity
+            );
+            serde_json::from_str(&json).unwrap()
+        };
+
+        for quantity in [r#""0x1a""#, r#""0x001a""#, r#""26""#, "26"] {
+            let request = request(quantity);
+
+            assert_eq!(request.gas, U256::from(26));
+            assert_eq!(request.gas_price, U256::from(26));
+            assert_eq!(request.value, Some(U256::from(26)));
+            assert_eq!(request.nonce, Some(U256::from(26)));
+        }
+    }
+
+    #[test]
+    fn it_rejects_a_request_without_a_sender() {
+        let json = r#"{"to": "0x6b78fa07883d5c5b527da9828ac77f5aa5a61d3b", "gas": "0x1", "gasPrice": "0x1"}"#;
+        let request: TransactionRequest = serde_json::from_str(json).unwrap();
+        let result: Result<Transaction> = request.try_into();
+
+        assert!(matches!(
+            result,
+            Err(TypeError::InvalidTransaction(message)) if message == "missing from"
+        ));
+    }
+
+    #[test]
+    fn it_rejects_invalid_quantities() {
+        let json = r#"{"gas": "0xzz", "gasPrice": "0x1"}"#;
+
+        assert!(serde_json::from_str::<TransactionRequest>(json).is_err());
+    }
+
+    #[test]
+    fn it_serializes_a_receipt_with_logs_in_camel_case() {
+        let log = Log {
+            address: H160::zero(),
+            block_hash: Some(H256::zero()),
+            block_number: Some(U64::from(1)),
+            data: Bytes::from_static(b"data"),
+            log_index: Some(U256::zero()),
+            log_type: None,
+            removed: Some(false),
+            topics: vec![H256::zero()],
+            transaction_hash: Some(H256::zero()),
+            transaction_index: Some("0x0".into()),
+            transaction_log_index: Some(U256::zero()),
+        };
+        let receipt = TransactionReceipt {
+            block_hash: Some(H256::zero()),
+            block_number: Some(BlockNumber::from(1_u64)),
+            contract_address: None,
+            transaction_hash: H256::zero(),
+            logs: vec![log],
+            status: U64::from(1),
+            cumulative_gas_used: U256::from(21_000),
+            effective_gas_price: U256::from(DEFAULT_GAS_PRICE),
+            revert_reason: None,
+        };
+
+        assert_camel_case_round_trip(&receipt);
+    }
+
+    #[test]
+    fn it_calculates_the_intrinsic_gas_of_a_transfer() {
+        assert_eq!(
+            new_transaction().intrinsic_gas(),
+            U256::from(TRANSACTION_GAS)
+        );
+    }
+
+    #[test]
+    fn it_calculates_the_intrinsic_gas_of_transaction_data() {
+        let mut transaction = new_transaction();
+        transaction.data = Some(Bytes::from_static(&[0, 0, 1, 2]));
+
+        assert_eq!(
+            transaction.intrinsic_gas(),
+            U256::from(TRANSACTION_GAS + 2 * ZERO_BYTE_GAS + 2 * NON_ZERO_BYTE_GAS)
+        );
+
+        transaction.to = None;
+
+        assert_eq!(
+            transaction.intrinsic_gas(),
+            U256::from(
+                TRANSACTION_GAS + 2 * ZERO_BYTE_GAS + 2 * NON_ZERO_BYTE_GAS + CONTRACT_CREATION_GAS
+            )
+        );
+    }
+
+    /// 测试解码合约调用的数据
+    #[test]
+    fn it_decodes_call_data() {
+        let from = H160::from_str("0x4a0d457e884ebd9b9773d172ed687417caac4f14").unwrap();
+        let contract = H160::from_str("0x6b78fa07883d5c5b527da9828ac77f5aa5a61d3b").unwrap();
+        let data = Bytes::from("mint,String,0x4a0d457e884ebd9b9773d172ed687417caac4f14,U64,10");
+        let transaction =
+            Transaction::new(from, Some(contract), U256::zero(), None, Some(data)).unwrap();
+
+        let (function, params) =
+            TransactionKind::decode_call_data(&transaction.data.unwrap()).unwrap();
+
+        assert_eq!(function, "mint");
+        assert_eq!(
+            params,
+            vec![
+                "String",
+                "0x4a0d457e884ebd9b9773d172ed687417caac4f14",
+                "U64",
+                "10"
+            ]
+        );
+    }
+
+    /// 测试无法解码的合约调用数据返回`InvalidCallData`
+    #[test]
+    fn it_rejects_malformed_call_data() {
+        let result = TransactionKind::decode_call_data(&Bytes::from(vec![0xff; 3]));
+
+        assert!(matches!(result, Err(TypeError::InvalidCallData(_))));
+    }
+
+    /// 测试按EIP-155签名的交易在序列化和反序列化后仍能恢复发送者
+    #[test]
+    fn it_round_trips_an_eip155_signed_transaction() {
+        let (secret_key, public_key) = keypair();
+        let mut transaction = new_transaction();
+        transaction.from = public_key_address(&public_key);
+        let signed = transaction.sign_eip155(secret_key, 1337).unwrap();
+
+        assert_eq!(signed.transaction().chain_id, Some(1337));
+        assert!(signed.signature().v >= 1337 * 2 + 35);
+
+        let encoded = bincode::serialize(&signed).unwrap();
+        let decoded: SignedTransaction = bincode::deserialize(&encoded).unwrap();
+        let json = serde_json::to_value(&signed).unwrap();
+        let from_json: SignedTransaction = serde_json::from_value(json).unwrap();
+
+        for round_tripped in [decoded, from_json] {
+            assert_eq!(round_tripped.transaction().chain_id, Some(1337));
+            assert_eq!(round_tripped.sender().unwrap(), transaction.from);
+            assert!(Transaction::verify(round_tripped, transaction.from).unwrap());
+        }
+
+        // 修改链ID后签名不再对应原来的发送者
+        let mut replayed: Transaction = signed.into();
+        replayed.chain_id = Some(1);
+        let replayed = SignedTransaction::try_from(replayed).unwrap();
+        assert!(!Transaction::verify(replayed, transaction.from).unwrap_or(false));
+    }
 }
@@ -1,11 +1,15 @@
 use ethereum_types::{Address, H160, H256, U256};
 use lazy_static::lazy_static;
-use rlp::{Encodable, RlpStream};
+use rlp::{Encodable, Rlp, RlpStream};
 pub use secp256k1::{
     ecdsa::{RecoverableSignature, RecoveryId, Signature as EcdsaSignature},
     generate_keypair, rand, All, Message, PublicKey, Secp256k1, SecretKey,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sha3::{Digest, Keccak256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
 
 use crate::error::{Result, UtilsError};
 
@@ -22,6 +26,7 @@ lazy_static! {
 /// Signature结构体用于表示一个数字签名。
 /// 它包含三个字段：v, r, 和 s，这些字段共同构成了一个完整的数字签名。
 /// 数字签名在区块链技术中常用于验证交易的完整性和 authenticity。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Signature {
     /// v是一个64位无符号整数，代表签名的版本信息。
     /// 这个字段帮助在ECDSA签名算法中确定正确的公钥恢复方法。
@@ -34,6 +39,36 @@ pub struct Signature {
     pub s: H256,
 }
 
+impl Signature {
+    /// 转换为EIP-2098紧凑表示
+    ///
+    /// 紧凑表示为64字节：前32字节为r，后32字节为s，
+    /// 其中s的最高位存放yParity（即v的奇偶性），比分开存放v、r、s节省空间
+    pub fn to_compact_2098(&self) -> [u8; 64] {
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(self.r.as_bytes());
+        compact[32..].copy_from_slice(self.s.as_bytes());
+
+        if self.v % 2 == 1 {
+            compact[32] |= 0x80;
+        }
+
+        compact
+    }
+
+    /// 从EIP-2098紧凑表示恢复签名
+    ///
+    /// s的最高位为yParity，清除该位后得到原始的s，v为0或1
+    pub fn from_compact_2098(compact: &[u8; 64]) -> Self {
+        let v = (compact[32] >> 7) as u64;
+        let r = H256::from_slice(&compact[..32]);
+        let mut s = H256::from_slice(&compact[32..]);
+        s.0[0] &= 0x7f;
+
+        Signature { v, r, s }
+    }
+}
+
 impl From<RecoverableSignature> for Signature {
     fn from(value: RecoverableSignature) -> Self {
         let (recovery_id, signature) = value.serialize_compact();
@@ -94,10 +129,31 @@ pub fn keypair() -> (SecretKey, PublicKey) {
     generate_keypair(&mut rand::thread_rng())
 }
 
+/// 使用给定的随机数生成器生成密钥对
+///
+/// 传入固定种子的生成器可以得到可复现的密钥对，也可以传入由外部（例如HSM）提供熵的生成器
+pub fn keypair_from_rng<R: rand::Rng + ?Sized>(rng: &mut R) -> (SecretKey, PublicKey) {
+    generate_keypair(rng)
+}
+
+/// 从32字节的原始数据创建私钥
+///
+/// 数据为零或不小于曲线的阶时返回`UtilsError::ConversionError`
+pub fn secret_from_bytes(bytes: &[u8; 32]) -> Result<SecretKey> {
+    SecretKey::from_slice(bytes).map_err(|e| UtilsError::ConversionError(e.to_string()))
+}
+
 pub fn hash(bytes: &[u8]) -> [u8; 32] {
     Keccak256::digest(bytes).into()
 }
 
+/// 计算事件签名的keccak哈希，作为日志的第一个topic
+///
+/// 例如 `event_topic("Transfer(address,address,uint256)")`
+pub fn event_topic(signature: &str) -> H256 {
+    hash(signature.as_bytes()).into()
+}
+
 pub fn to_address(item: &[u8]) -> H160 {
     let hash = hash(&item[1..]);
     Address::from_slice(&hash[12..])
@@ -174,6 +230,259 @@ pub fn recover_address(message: &[u8], signature: &[u8], recovery_id: i32) -> Re
     Ok(public_key_address(&public_key))
 }
 
+/// 按EIP-712计算结构化数据的签名摘要
+///
+/// 摘要为`keccak256(0x19 0x01 || domainSeparator || hashStruct(message))`，
+/// 主类型是`types`中没有被其他类型引用的结构体类型（不包括`EIP712Domain`）。
+/// `types`中没有定义`EIP712Domain`时，按`domain`中出现的字段推导域的类型
+///
+/// # 参数
+/// * `domain` - 域，例如`{"name": ..., "version": ..., "chainId": ..., "verifyingContract": ...}`
+/// * `types` - 类型定义，键为类型名，值为`[{"name": ..., "type": ...}]`
+/// * `message` - 主类型的消息
+///
+/// # 返回值
+/// * `Result<H256>` - 签名摘要；类型定义或消息不合法时返回`UtilsError::TypedDataError`
+pub fn hash_typed_data(domain: &Value, types: &Value, message: &Value) -> Result<H256> {
+    let mut types: HashMap<String, Vec<TypedField>> = serde_json::from_value(types.clone())
+        .map_err(|e| UtilsError::TypedDataError(e.to_string()))?;
+
+    types
+        .entry(EIP712_DOMAIN.into())
+        .or_insert_with(|| domain_fields(domain));
+
+    let primary_type = primary_type(&types)?;
+    let domain_separator = hash_struct(&types, EIP712_DOMAIN, domain)?;
+    let message_hash = hash_struct(&types, &primary_type, message)?;
+
+    let mut digest = vec![0x19, 0x01];
+    digest.extend_from_slice(&domain_separator);
+    digest.extend_from_slice(&message_hash);
+
+    Ok(hash(&digest).into())
+}
+
+/// 按EIP-712签名结构化数据，直接对摘要签名而不再做哈希
+pub fn sign_typed_data(
+    domain: &Value,
+    types: &Value,
+    message: &Value,
+    key: &SecretKey,
+) -> Result<Signature> {
+    let digest = hash_typed_data(domain, types, message)?;
+    let message = Message::from_slice(digest.as_bytes())
+        .map_err(|e| UtilsError::CreateMessage(e.to_string()))?;
+
+    Ok(CONTEXT.sign_ecdsa_recoverable(&message, key).into())
+}
+
+// EIP-712域的类型名
+const EIP712_DOMAIN: &str = "EIP712Domain";
+
+/// EIP-712类型定义中的一个字段
+#[derive(Debug, Clone, Deserialize)]
+struct TypedField {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// 按EIP-712规定的顺序取domain中出现的字段作为域的类型
+fn domain_fields(domain: &Value) -> Vec<TypedField> {
+    [
+        ("name", "string"),
+        ("version", "string"),
+        ("chainId", "uint256"),
+        ("verifyingContract", "address"),
+        ("salt", "bytes32"),
+    ]
+    .into_iter()
+    .filter(|(name, _)| domain.get(name).is_some())
+    .map(|(name, kind)| TypedField {
+        name: name.into(),
+        kind: kind.into(),
+    })
+    .collect()
+}
+
+/// 找到没有被其他类型引用的结构体类型作为主类型
+fn primary_type(types: &HashMap<String, Vec<TypedField>>) -> Result<String> {
+    let referenced: HashSet<&str> = types
+        .values()
+        .flatten()
+        .map(|field| field.kind.trim_end_matches("[]"))
+        .collect();
+    let candidates: Vec<&String> = types
+        .keys()
+        .filter(|name| *name != EIP712_DOMAIN && !referenced.contains(name.as_str()))
+        .collect();
+
+    match candidates.as_slice() {
+        [primary_type] => Ok((*primary_type).clone()),
+        _ => Err(UtilsError::TypedDataError(format!(
+            "expected exactly one primary type, found {:?}",
+            candidates
+        ))),
+    }
+}
+
+/// 编码类型，主类型在前，引用的其他结构体类型按名称排序拼接在后
+fn encode_type(types: &HashMap<String, Vec<TypedField>>, primary_type: &str) -> Result<String> {
+    let mut dependencies = BTreeSet::new();
+    collect_dependencies(types, primary_type, &mut dependencies);
+    dependencies.remove(primary_type);
+
+    std::iter::once(primary_type)
+        .chain(dependencies.iter().map(String::as_str))
+        .map(|name| {
+            let fields = types
+                .get(name)
+                .ok_or_else(|| UtilsError::TypedDataError(format!("unknown type {}", name)))?
+                .iter()
+                .map(|field| format!("{} {}", field.kind, field.name))
+                .collect::<Vec<_>>()
+                .join(",");
+
+            Ok(format!("{}({})", name, fields))
+        })
+        .collect()
+}
+
+/// 递归收集一个类型引用的所有结构体类型
+fn collect_dependencies(
+    types: &HashMap<String, Vec<TypedField>>,
+    name: &str,
+    dependencies: &mut BTreeSet<String>,
+) {
+    if dependencies.contains(name) {
+        return;
+    }
+
+    if let Some(fields) = types.get(name) {
+        dependencies.insert(name.into());
+
+        for field in fields {
+            collect_dependencies(types, field.kind.trim_end_matches("[]"), dependencies);
+        }
+    }
+}
+
+/// 计算结构体的哈希，即`keccak256(typeHash || encodeData(value))`
+fn hash_struct(
+    types: &HashMap<String, Vec<TypedField>>,
+    name: &str,
+    value: &Value,
+) -> Result<[u8; 32]> {
+    let fields = types
+        .get(name)
+        .ok_or_else(|| UtilsError::TypedDataError(format!("unknown type {}", name)))?;
+    let mut encoded = hash(encode_type(types, name)?.as_bytes()).to_vec();
+
+    for field in fields {
+        let field_value = value.get(&field.name).ok_or_else(|| {
+            UtilsError::TypedDataError(format!("missing field {} of {}", field.name, name))
+        })?;
+        encoded.extend_from_slice(&encode_value(types, &field.kind, field_value)?);
+    }
+
+    Ok(hash(&encoded))
+}
+
+/// 将一个字段的值编码为32字节
+fn encode_value(
+    types: &HashMap<String, Vec<TypedField>>,
+    kind: &str,
+    value: &Value,
+) -> Result<[u8; 32]> {
+    let invalid = || UtilsError::TypedDataError(format!("invalid {} value {}", kind, value));
+
+    if let Some(item_kind) = kind.strip_suffix("[]") {
+        let mut encoded = vec![];
+        for item in value.as_array().ok_or_else(invalid)? {
+            encoded.extend_from_slice(&encode_value(types, item_kind, item)?);
+        }
+
+        return Ok(hash(&encoded));
+    }
+
+    if types.contains_key(kind) {
+        return hash_struct(types, kind, value);
+    }
+
+    let mut word = [0u8; 32];
+
+    match kind {
+        "string" => word = hash(value.as_str().ok_or_else(invalid)?.as_bytes()),
+        "bytes" => word = hash(&decode_hex(value.as_str().ok_or_else(invalid)?)?),
+        "bool" => word[31] = u8::from(value.as_bool().ok_or_else(invalid)?),
+        "address" => {
+            let address =
+                Address::from_str(value.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?;
+            word[12..].copy_from_slice(address.as_bytes());
+        }
+        kind if kind.starts_with("uint") || kind.starts_with("int") => {
+            parse_integer(value)
+                .ok_or_else(invalid)?
+                .to_big_endian(&mut word);
+        }
+        kind if kind.starts_with("bytes") => {
+            let bytes = decode_hex(value.as_str().ok_or_else(invalid)?)?;
+            if bytes.len() > 32 {
+                return Err(invalid());
+            }
+            word[..bytes.len()].copy_from_slice(&bytes);
+        }
+        _ => return Err(UtilsError::TypedDataError(format!("unknown type {}", kind))),
+    }
+
+    Ok(word)
+}
+
+/// 解析JSON数字、十进制字符串或十六进制字符串表示的整数，负数使用补码表示
+fn parse_integer(value: &Value) -> Option<U256> {
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.clone(),
+        _ => return None,
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.as_str()),
+    };
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok()?,
+        None => U256::from_dec_str(digits).ok()?,
+    };
+
+    Some(if negative {
+        (!magnitude).overflowing_add(U256::one()).0
+    } else {
+        magnitude
+    })
+}
+
+/// 解码带`0x`前缀的十六进制字符串
+///
+/// 按字节而不是按字符切分，包含非ASCII字符的字符串直接返回错误
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    let invalid = || UtilsError::TypedDataError(format!("invalid hex {}", hex));
+
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
 /// 使用RLP编码给定的项和可选的签名
 ///
 /// RLP编码是一种用于编码任意数据的方案，主要用于以太坊网络
@@ -218,6 +527,38 @@ pub fn rlp_encode<T: Encodable>(items: Vec<T>, signature: Option<&Signature>) ->
     stream
 }
 
+/// 将RLP编码的列表解码为其中每一项的字节
+///
+/// 与`rlp_encode`互为逆操作，列表中的每一项都必须是字节串，不支持嵌套的列表
+///
+/// # 参数
+/// - `bytes`: RLP编码的列表
+///
+/// # 返回值
+/// 列表中每一项的字节；输入不是列表、长度与编码不符或包含嵌套列表时返回`UtilsError::RlpDecodeError`
+pub fn rlp_decode_list(bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let rlp = Rlp::new(bytes);
+    let decode_error = |e: rlp::DecoderError| UtilsError::RlpDecodeError(e.to_string());
+
+    if !rlp.is_list() {
+        return Err(UtilsError::RlpDecodeError("expected a list".into()));
+    }
+
+    // 拒绝列表之后还有多余字节或长度不足的输入
+    let payload_info = rlp.payload_info().map_err(decode_error)?;
+    if payload_info.total() != bytes.len() {
+        return Err(UtilsError::RlpDecodeError(format!(
+            "expected {} bytes, got {}",
+            payload_info.total(),
+            bytes.len()
+        )));
+    }
+
+    rlp.iter()
+        .map(|item| item.data().map(<[u8]>::to_vec).map_err(decode_error))
+        .collect()
+}
+
 /// 检查给定的哈希值是否有效
 ///
 /// 有效性是指哈希值的前`ZERO_COUNT`个字节是否全部为0
@@ -238,6 +579,19 @@ pub fn is_valid_hash(hash: H256) -> bool {
     hash.0.iter().take(ZERO_COUNT as usize).all(|&x| x == 0)
 }
 
+/// 检查给定的哈希值是否满足难度要求
+///
+/// 将哈希值视为一个256位的大端整数，当它不大于`U256::MAX / difficulty`时满足要求，
+/// 难度越高，满足要求的哈希值越少，平均需要尝试`difficulty`次才能找到
+///
+/// # 参数
+///
+/// * `hash` - 待验证的哈希
+/// * `difficulty` - 难度，为0时按1处理
+pub fn meets_difficulty(hash: H256, difficulty: U256) -> bool {
+    U256::from_big_endian(hash.as_bytes()) <= U256::MAX / difficulty.max(U256::one())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,6 +604,21 @@ mod tests {
         assert_eq!(private_key_address, public_key_address);
     }
 
+    #[test]
+    fn it_round_trips_eip2098_compact_signatures() {
+        let (secret_key, _) = keypair();
+
+        for message in [b"The message".as_slice(), b"Another message".as_slice()] {
+            let signature: Signature = sign_recovery(message, &secret_key).unwrap().into();
+            let compact = signature.to_compact_2098();
+            let decoded = Signature::from_compact_2098(&compact);
+
+            assert_eq!(decoded.v, signature.v);
+            assert_eq!(decoded.r, signature.r);
+            assert_eq!(decoded.s, signature.s);
+        }
+    }
+
     #[test]
     fn it_hashes() {
         let message = b"The message";
@@ -263,6 +632,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_hashes_an_event_topic() {
+        let topic = event_topic("Transfer(address,address,uint256)");
+        let expected: H256 = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+            .parse()
+            .unwrap();
+        assert_eq!(topic, expected);
+    }
+
     #[test]
     fn it_recovers() {
         let (secret_key, public_key) = keypair();
@@ -295,6 +673,90 @@ mod tests {
         assert!(verified);
     }
 
+    #[test]
+    fn it_generates_a_keypair_from_a_seeded_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let keypair_1 = keypair_from_rng(&mut StdRng::seed_from_u64(42));
+        let keypair_2 = keypair_from_rng(&mut StdRng::seed_from_u64(42));
+        let keypair_3 = keypair_from_rng(&mut StdRng::seed_from_u64(43));
+
+        assert_eq!(keypair_1, keypair_2);
+        assert_ne!(keypair_1, keypair_3);
+    }
+
+    #[test]
+    fn it_creates_a_secret_key_from_bytes() {
+        let (secret_key, public_key) = keypair();
+        let restored = secret_from_bytes(&secret_key.secret_bytes()).unwrap();
+
+        assert_eq!(restored, secret_key);
+        assert_eq!(
+            private_key_address(&restored),
+            public_key_address(&public_key)
+        );
+        assert!(matches!(
+            secret_from_bytes(&[0; 32]),
+            Err(UtilsError::ConversionError(_))
+        ));
+    }
+
+    #[test]
+    fn it_hashes_typed_data() {
+        // EIP-712规范中的示例
+        let types = serde_json::json!({
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        });
+        let domain = serde_json::json!({
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        });
+        let message = serde_json::json!({
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        });
+
+        let digest = hash_typed_data(&domain, &types, &message).unwrap();
+
+        assert_eq!(
+            digest,
+            H256::from_str("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+                .unwrap()
+        );
+
+        let (secret_key, public_key) = keypair();
+        let signature = sign_typed_data(&domain, &types, &message, &secret_key).unwrap();
+        let recoverable: RecoverableSignature = signature.try_into().unwrap();
+        let recovered = CONTEXT
+            .recover_ecdsa(
+                &Message::from_slice(digest.as_bytes()).unwrap(),
+                &recoverable,
+            )
+            .unwrap();
+
+        assert_eq!(recovered, public_key);
+    }
+
+    #[test]
+    fn it_rejects_non_ascii_hex() {
+        // 多字节字符使字符串长度为偶数，按字节切分时不能落在字符中间
+        assert!(decode_hex("0xé0").is_err());
+        assert!(decode_hex("0x0").is_err());
+        assert!(decode_hex("0xzz").is_err());
+        assert_eq!(decode_hex("0x0aff").unwrap(), vec![0x0a, 0xff]);
+    }
+
     #[test]
     fn it_rlp_encodes() {
         let items = vec!["a", "b", "c", "d", "e", "f"];
@@ -302,4 +764,36 @@ mod tests {
 
         assert_eq!(stream.out().to_vec(), b"\xc6abcdef".to_vec());
     }
+
+    #[test]
+    fn it_rlp_decodes_a_list() {
+        let items = rlp_decode_list(b"\xc6abcdef").unwrap();
+
+        assert_eq!(
+            items,
+            vec![b"a", b"b", b"c", b"d", b"e", b"f"]
+                .into_iter()
+                .map(|item| item.to_vec())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_rejects_malformed_rlp() {
+        // 不是列表
+        assert!(matches!(
+            rlp_decode_list(b"a"),
+            Err(UtilsError::RlpDecodeError(_))
+        ));
+        // 列表长度超过实际的字节数
+        assert!(matches!(
+            rlp_decode_list(b"\xc6abc"),
+            Err(UtilsError::RlpDecodeError(_))
+        ));
+        // 列表之后有多余的字节
+        assert!(matches!(
+            rlp_decode_list(b"\xc6abcdefg"),
+            Err(UtilsError::RlpDecodeError(_))
+        ));
+    }
 }
@@ -8,9 +8,18 @@ pub enum UtilsError {
     #[error("Could not create message: {0}")]
     CreateMessage(String),
 
+    #[error("Error deriving key: {0}")]
+    DerivationError(String),
+
     #[error("Error recovering key: {0}")]
     RecoverError(String),
 
+    #[error("Error decoding RLP: {0}")]
+    RlpDecodeError(String),
+
+    #[error("Invalid typed data: {0}")]
+    TypedDataError(String),
+
     #[error("Error verifying signature: {0}")]
     VerifyError(String),
 }
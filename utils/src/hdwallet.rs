@@ -0,0 +1,217 @@
+use hmac::{Hmac, Mac};
+use secp256k1::{PublicKey, Scalar, SecretKey};
+use sha2::Sha512;
+
+use crate::crypto::CONTEXT;
+use crate::error::{Result, UtilsError};
+
+type HmacSha512 = Hmac<Sha512>;
+
+// 主密钥HMAC使用的密钥
+const MASTER_KEY: &[u8] = b"Bitcoin seed";
+// 索引不小于该值的子密钥为强化派生
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+// BIP-39由助记词生成种子时PBKDF2的迭代次数
+const MNEMONIC_ROUNDS: u32 = 2048;
+
+/// 按BIP-39由助记词和口令生成64字节的种子
+///
+/// 种子为以助记词为密码、`"mnemonic"`加口令为盐的PBKDF2-HMAC-SHA512，
+/// 单词之间的多余空白会被规范为一个空格；不校验单词表和校验和，也不做NFKD规范化，
+/// 因此只适用于英文单词表生成的助记词
+pub fn mnemonic_to_seed(phrase: &str, passphrase: &str) -> Result<[u8; 64]> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let salt = format!("mnemonic{}", passphrase);
+
+    // 种子长度与SHA512的输出长度相同，只需要计算PBKDF2的第一个块
+    let mut block = hmac_sha512_bytes(phrase.as_bytes(), &[salt.as_bytes(), &1u32.to_be_bytes()])?;
+    let mut seed = block;
+
+    for _ in 1..MNEMONIC_ROUNDS {
+        block = hmac_sha512_bytes(phrase.as_bytes(), &[&block])?;
+        seed.iter_mut()
+            .zip(block.iter())
+            .for_each(|(seed, byte)| *seed ^= byte);
+    }
+
+    Ok(seed)
+}
+
+/// 按BIP-32从种子派生指定路径上的私钥
+///
+/// 路径以`m`开头，各级索引以`/`分隔，带`'`或`h`后缀的索引为强化派生，
+/// 例如以太坊账户常用的`m/44'/60'/0'/0/0`
+///
+/// # 参数
+/// * `seed` - 派生使用的种子，例如由助记词生成的64字节种子
+/// * `path` - 派生路径
+///
+/// # 返回值
+/// * `Result<SecretKey>` - 路径上的私钥；路径格式不合法或派生出无效密钥时返回`UtilsError::DerivationError`
+pub fn derive_child(seed: &[u8], path: &str) -> Result<SecretKey> {
+    let (mut secret_key, mut chain_code) = hmac_sha512(MASTER_KEY, &[seed])?;
+
+    for index in parse_path(path)? {
+        let index_bytes = index.to_be_bytes();
+        // 强化派生使用私钥，普通派生使用压缩格式的公钥
+        let (tweak, child_chain_code) = if index >= HARDENED_OFFSET {
+            hmac_sha512(
+                &chain_code,
+                &[&[0], &secret_key.secret_bytes(), &index_bytes],
+            )?
+        } else {
+            let public_key = PublicKey::from_secret_key(&CONTEXT, &secret_key).serialize();
+            hmac_sha512(&chain_code, &[&public_key, &index_bytes])?
+        };
+
+        secret_key = secret_key
+            .add_tweak(&to_scalar(&tweak)?)
+            .map_err(|e| UtilsError::DerivationError(e.to_string()))?;
+        chain_code = child_chain_code;
+    }
+
+    Ok(secret_key)
+}
+
+/// 计算HMAC-SHA512，data中的各部分依次拼接作为消息
+fn hmac_sha512_bytes(key: &[u8], data: &[&[u8]]) -> Result<[u8; 64]> {
+    let mut mac =
+        HmacSha512::new_from_slice(key).map_err(|e| UtilsError::DerivationError(e.to_string()))?;
+    data.iter().for_each(|data| mac.update(data));
+
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// 计算HMAC-SHA512，返回由前32字节得到的私钥和后32字节的链码
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> Result<(SecretKey, [u8; 32])> {
+    let output = hmac_sha512_bytes(key, data)?;
+
+    let secret_key = SecretKey::from_slice(&output[..32])
+        .map_err(|e| UtilsError::DerivationError(e.to_string()))?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&output[32..]);
+
+    Ok((secret_key, chain_code))
+}
+
+/// 将HMAC得到的私钥转换为用于相加的标量
+fn to_scalar(key: &SecretKey) -> Result<Scalar> {
+    Scalar::from_be_bytes(key.secret_bytes())
+        .map_err(|e| UtilsError::DerivationError(e.to_string()))
+}
+
+/// 解析派生路径，返回每一级的索引，强化派生的索引加上`HARDENED_OFFSET`
+fn parse_path(path: &str) -> Result<Vec<u32>> {
+    let mut components = path.split('/');
+
+    if components.next() != Some("m") {
+        return Err(UtilsError::DerivationError(format!(
+            "path {} must start with m",
+            path
+        )));
+    }
+
+    components
+        .map(|component| {
+            let (index, hardened) = match component.strip_suffix(['\'', 'h']) {
+                Some(index) => (index, true),
+                None => (component, false),
+            };
+            let index = index
+                .parse::<u32>()
+                .ok()
+                .filter(|index| *index < HARDENED_OFFSET)
+                .ok_or_else(|| {
+                    UtilsError::DerivationError(format!("invalid path component {}", component))
+                })?;
+
+            Ok(if hardened {
+                index + HARDENED_OFFSET
+            } else {
+                index
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP-32测试向量1的种子
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn derive_hex(path: &str) -> Vec<u8> {
+        derive_child(&from_hex(SEED), path)
+            .unwrap()
+            .secret_bytes()
+            .to_vec()
+    }
+
+    #[test]
+    fn it_derives_the_master_key() {
+        assert_eq!(
+            derive_hex("m"),
+            from_hex("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35")
+        );
+    }
+
+    #[test]
+    fn it_derives_bip32_test_vectors() {
+        assert_eq!(
+            derive_hex("m/0'"),
+            from_hex("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea")
+        );
+        assert_eq!(
+            derive_hex("m/0'/1"),
+            from_hex("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368")
+        );
+        assert_eq!(
+            derive_hex("m/0h/1/2h"),
+            from_hex("cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca")
+        );
+    }
+
+    #[test]
+    fn it_derives_distinct_ethereum_accounts() {
+        let seed = from_hex(SEED);
+        let account_0 = derive_child(&seed, "m/44'/60'/0'/0/0").unwrap();
+        let account_1 = derive_child(&seed, "m/44'/60'/0'/0/1").unwrap();
+
+        assert_ne!(account_0, account_1);
+        assert_eq!(account_0, derive_child(&seed, "m/44'/60'/0'/0/0").unwrap());
+    }
+
+    #[test]
+    fn it_rejects_invalid_paths() {
+        let seed = from_hex(SEED);
+
+        for path in ["44'/60'", "m/x", "m/2147483648", "m//0"] {
+            assert!(matches!(
+                derive_child(&seed, path),
+                Err(UtilsError::DerivationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn it_derives_a_seed_from_a_mnemonic() {
+        let seed = mnemonic_to_seed(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "TREZOR",
+        )
+        .unwrap();
+
+        assert_eq!(
+            seed.to_vec(),
+            from_hex("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04")
+        );
+    }
+}
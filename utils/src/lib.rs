@@ -7,3 +7,4 @@ pub use sha3::{Digest, Keccak256};
 
 pub mod crypto;
 pub mod error;
+pub mod hdwallet;
@@ -1,11 +1,23 @@
 use crate::error::{Result, Web3Error};
 use crate::Web3;
-use ethereum_types::U256;
+use ethereum_types::{Address, U256};
 use jsonrpsee::rpc_params;
 use types::account::Account;
+use types::block::BlockTag;
 use types::helpers::to_hex;
 use types::transaction::{SignedTransaction, Transaction};
-use utils::crypto::SecretKey;
+use utils::crypto::{recover_address, sign_recovery, SecretKey, Signature};
+
+/// 以太坊签名消息的前缀，避免签名的消息被当作交易使用
+const MESSAGE_PREFIX: &str = "\x19Ethereum Signed Message:\n";
+
+/// 在消息前加上以太坊签名消息的前缀和消息长度
+fn prefix_message(message: &[u8]) -> Vec<u8> {
+    let mut prefixed = format!("{}{}", MESSAGE_PREFIX, message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+
+    prefixed
+}
 
 impl Web3 {
     /// 获取指定地址的余额。
@@ -17,6 +29,20 @@ impl Web3 {
         Ok(balance)
     }
 
+    /// 在一次批量请求中获取多个地址的余额，按地址的顺序返回。
+    pub async fn batch_balances(&self, accounts: &[Account]) -> Result<Vec<U256>> {
+        let requests = accounts
+            .iter()
+            .map(|account| ("eth_getBalance", rpc_params![to_hex(*account)]))
+            .collect();
+        let responses = self.send_batch(requests).await?;
+
+        responses
+            .into_iter()
+            .map(|response| Ok(serde_json::from_value(response)?))
+            .collect()
+    }
+
     /// 签名交易。
     pub fn sign_transaction(
         &self,
@@ -29,12 +55,116 @@ impl Web3 {
         Ok(signed_transaction)
     }
 
+    /// 使用私钥签名任意消息，签名前会加上以太坊签名消息的前缀
+    pub fn sign_message(&self, message: &[u8], key: SecretKey) -> Result<Signature> {
+        let signature = sign_recovery(&prefix_message(message), &key)
+            .map_err(|e| Web3Error::MessageSigningError(e.to_string()))?;
+
+        Ok(signature.into())
+    }
+
+    /// 从消息和`sign_message`得到的签名中恢复签名者的地址
+    pub fn recover_signer(&self, message: &[u8], signature: Signature) -> Result<Address> {
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(signature.r.as_bytes());
+        compact[32..].copy_from_slice(signature.s.as_bytes());
+        let recovery_id = i32::try_from(signature.v)
+            .map_err(|e| Web3Error::MessageSigningError(e.to_string()))?;
+
+        recover_address(&prefix_message(message), &compact, recovery_id)
+            .map_err(|e| Web3Error::MessageSigningError(e.to_string()))
+    }
+
     /// 获取账户的交易数量
-    pub async fn get_transaction_count(&self, address: Account) -> Result<U256> {
-        let params = rpc_params![to_hex(address)];
+    ///
+    /// `block_tag`为`Pending`时计入交易池中尚未打包的交易，构造连续发送的交易时应使用该值，
+    /// 为None时由节点使用默认的`Latest`
+    pub async fn get_transaction_count(
+        &self,
+        address: Account,
+        block_tag: Option<BlockTag>,
+    ) -> Result<U256> {
+        let params = match block_tag {
+            Some(block_tag) => rpc_params![to_hex(address), block_tag],
+            None => rpc_params![to_hex(address)],
+        };
         let response = self.send_rpc("eth_getTransactionCount", params).await?;
         let balance: U256 = serde_json::from_value(response)?;
 
         Ok(balance)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::{increment_account_1_nonce, wait_for_receipt, web3, ACCOUNT_1};
+    use utils::crypto::{keypair, public_key_address};
+
+    /// 部署一个合约，返回新创建的合约账户
+    async fn contract_account() -> Account {
+        let nonce = increment_account_1_nonce().await;
+        let tx_hash = web3()
+            .deploy(*ACCOUNT_1, b"erc20", Some(nonce))
+            .await
+            .unwrap();
+
+        wait_for_receipt(tx_hash).await.contract_address.unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_signs_a_message_and_recovers_the_signer() {
+        let web3 = web3();
+        let (secret_key, public_key) = keypair();
+        let signature = web3.sign_message(b"hello", secret_key).unwrap();
+
+        assert_eq!(
+            web3.recover_signer(b"hello", signature).unwrap(),
+            public_key_address(&public_key)
+        );
+        assert_ne!(
+            web3.recover_signer(b"goodbye", signature).unwrap(),
+            public_key_address(&public_key)
+        );
+    }
+
+    #[tokio::test]
+    async fn it_counts_pending_transactions() {
+        let web3 = web3();
+        // 合约账户不会发送其他交易，nonce跳号的交易会一直留在交易池中而不会被打包
+        let sender = contract_account().await;
+        let transaction =
+            Transaction::new(sender, Some(*ACCOUNT_1), U256::zero(), Some(5.into()), None).unwrap();
+        web3.send(transaction.into()).await.unwrap();
+
+        let latest = web3
+            .get_transaction_count(sender, Some(BlockTag::Latest))
+            .await
+            .unwrap();
+        let pending = web3
+            .get_transaction_count(sender, Some(BlockTag::Pending))
+            .await
+            .unwrap();
+
+        assert_eq!(latest, U256::zero());
+        assert!(pending > latest);
+    }
+
+    #[tokio::test]
+    async fn it_gets_balances_in_a_batch() {
+        let web3 = web3();
+        let accounts = [
+            *ACCOUNT_1,
+            contract_account().await,
+            contract_account().await,
+        ];
+        let balances = web3.batch_balances(&accounts).await.unwrap();
+
+        let mut expected = vec![];
+        for account in accounts {
+            expected.push(web3.get_balance(account).await.unwrap());
+        }
+
+        assert_eq!(balances, expected);
+    }
+}
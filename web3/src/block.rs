@@ -1,7 +1,10 @@
-use crate::error::Result;
+use crate::error::{Result, Web3Error};
 use crate::Web3;
-use ethereum_types::U64;
+use ethereum_types::{H256, U64};
+use futures::{stream, Stream};
+use jsonrpsee::core::client::SubscriptionClientT;
 use jsonrpsee::rpc_params;
+use log::warn;
 use types::block::{Block, BlockNumber};
 use types::helpers::to_hex;
 
@@ -68,4 +71,139 @@ impl Web3 {
         // 返回解析后的区块信息
         Ok(block)
     }
-}
\ No newline at end of file
+
+    /// 异步获取指定哈希的区块信息
+    ///
+    /// 通过`eth_getBlockByHash`请求区块，`full`表示是否返回完整的交易对象
+    ///
+    /// # 参数
+    ///
+    /// * `hash: H256` - 区块哈希
+    /// * `full: bool` - 是否返回完整的交易对象
+    ///
+    /// # 返回值
+    ///
+    /// * `Result<Block>` - 返回一个Result类型，包含成功时的Block实例或错误信息
+    pub async fn get_block_by_hash(&self, hash: H256, full: bool) -> Result<Block> {
+        // 构造RPC请求参数
+        let params = rpc_params![hash, full];
+        // 发送RPC请求并等待响应
+        let response = self.send_rpc("eth_getBlockByHash", params).await?;
+        // 解析响应数据为Block类型
+        let block: Block = serde_json::from_value(response)?;
+
+        Ok(block)
+    }
+
+    /// 异步获取链头，即当前最新的区块
+    ///
+    /// 先通过`eth_blockNumber`获取当前区块号，再获取该区块，调用方可以一次拿到区块号、
+    /// 区块哈希等链头信息。链上没有基础费用，区块中不包含`base_fee`
+    ///
+    /// # 返回值
+    ///
+    /// * `Result<Block>` - 返回一个Result类型，包含成功时的最新区块或错误信息
+    pub async fn head(&self) -> Result<Block> {
+        let block_number = self.get_block_number().await?;
+
+        self.get_block(*block_number).await
+    }
+
+    /// 订阅新区块，返回节点每打包一个区块就产出该区块的流
+    ///
+    /// 通过WebSocket调用`eth_subscribeNewHeads`，流持有WebSocket连接，
+    /// 流被丢弃时连接随之关闭。无法解析的通知会被跳过
+    ///
+    /// # 返回值
+    ///
+    /// * `Result<impl Stream<Item = Block>>` - 新区块组成的流，连接或订阅失败时返回错误
+    pub async fn subscribe_new_heads(&self) -> Result<impl Stream<Item = Block>> {
+        let client = self.get_ws_client().await?;
+        let subscription = client
+            .subscribe::<Block, _>(
+                "eth_subscribeNewHeads",
+                rpc_params![],
+                "eth_unsubscribeNewHeads",
+            )
+            .await
+            .map_err(Web3Error::from)?;
+
+        // 将客户端和订阅一起放入流的状态中，使连接在流存活期间保持打开
+        let stream = stream::unfold(
+            (client, subscription),
+            |(client, mut subscription)| async move {
+                loop {
+                    match subscription.next().await? {
+                        Ok(block) => return Some((block, (client, subscription))),
+                        Err(error) => warn!("Skipping invalid new head notification {:?}", error),
+                    }
+                }
+            },
+        );
+
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::{increment_account_1_nonce, web3, ACCOUNT_1, ACCOUNT_2};
+    use ethereum_types::U256;
+    use futures::StreamExt;
+    use std::time::Duration;
+    use types::transaction::Transaction;
+
+    #[tokio::test]
+    async fn it_gets_a_block_by_hash() {
+        let web3 = web3();
+        let block = web3.get_block(U64::zero()).await.unwrap();
+        let block_hash = block.hash.unwrap();
+        let block_by_hash = web3.get_block_by_hash(block_hash, true).await.unwrap();
+
+        assert_eq!(block_by_hash.hash, Some(block_hash));
+        assert_eq!(block_by_hash.number, block.number);
+        assert_eq!(block_by_hash.parent_hash, block.parent_hash);
+        assert_eq!(block_by_hash.transactions_root, block.transactions_root);
+        assert_eq!(block_by_hash.state_root, block.state_root);
+    }
+
+    #[tokio::test]
+    async fn it_gets_the_chain_head() {
+        let web3 = web3();
+        let head = web3.head().await.unwrap();
+        let block_number = web3.get_block_number().await.unwrap();
+
+        // 两次请求之间可能打包了新的区块
+        assert!(head.number <= *block_number);
+        assert!(head.hash.is_some());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a running node"]
+    async fn it_subscribes_to_new_heads() {
+        let web3 = web3();
+        let mut new_heads = Box::pin(web3.subscribe_new_heads().await.unwrap());
+        let block_number = web3.get_block_number().await.unwrap();
+
+        // 发送一笔交易，使节点打包一个新区块
+        let nonce = increment_account_1_nonce().await;
+        let transaction = Transaction::new(
+            *ACCOUNT_1,
+            Some(*ACCOUNT_2),
+            U256::from(10),
+            Some(nonce),
+            None,
+        )
+        .unwrap();
+        web3.send(transaction.into()).await.unwrap();
+
+        let block = tokio::time::timeout(Duration::from_secs(10), new_heads.next())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(block.number > *block_number);
+        assert!(block.hash.is_some());
+    }
+}
@@ -1,12 +1,13 @@
-use crate::error::Result;
+use crate::error::{Result, Web3Error};
 use crate::Web3;
 use ethereum_types::Address;
 use ethereum_types::{H256, U256};
 use jsonrpsee::rpc_params;
+use types::account::Account;
 use types::block::BlockNumber;
 use types::bytes::Bytes;
 use types::helpers::to_hex;
-use types::transaction::TransactionRequest;
+use types::transaction::{Transaction, TransactionRequest};
 
 impl Web3 {
     // 部署智能合约的异步函数
@@ -29,9 +30,12 @@ impl Web3 {
         nonce: Option<U256>,
     ) -> Result<H256> {
         // 设置交易的基本参数
-        let gas = U256::from(1_000_000); // 设置Gas限制，用于限制交易执行所消耗的最大Gas量
         let gas_price = U256::from(1_000_000); // 设置Gas价格，用于指定每单位Gas的价格
         let data: Bytes = abi.to_vec().into(); // 将ABI字节码转换为交易数据
+                                               // 节点拒绝gas上限低于固有gas的交易，Gas限制使用部署交易的固有gas
+        let gas = Transaction::new(owner, None, U256::zero(), nonce, Some(data.clone()))
+            .map_err(|e| Web3Error::TransactionBuildError(e.to_string()))?
+            .intrinsic_gas();
 
         // 构建交易请求对象，包含所有必要的交易信息
         let transaction_request = TransactionRequest {
@@ -42,6 +46,8 @@ impl Web3 {
             gas_price,
             data: Some(data), // 交易数据，包含合约的字节码
             nonce,            // 交易的nonce值，用于保证交易顺序
+            max_fee_per_gas: None, // 按legacy交易的方式使用gas_price计价
+            max_priority_fee_per_gas: None,
             r: None,          // 交易的r签名值，此处不需要提供
             s: None,          // 交易的s签名值，此处不需要提供
         };
@@ -81,4 +87,40 @@ impl Web3 {
         // 返回解析后的字节码信息
         Ok(code)
     }
+
+    /// 判断指定地址是否为合约账户
+    ///
+    /// 调用`eth_getCode`获取最新区块的代码，代码不为空时返回true
+    ///
+    /// # 参数
+    ///
+    /// * `address` - 需要判断的账户地址
+    ///
+    /// # 返回值
+    ///
+    /// 返回一个Result类型，地址为合约账户时包含true，否则包含false
+    pub async fn is_contract(&self, address: Account) -> Result<bool> {
+        let code = self.code(address, None).await?;
+
+        Ok(!code.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::tests::{increment_account_1_nonce, wait_for_receipt, web3, ACCOUNT_1};
+
+    #[tokio::test]
+    async fn it_checks_whether_an_address_is_a_contract() {
+        let web3 = web3();
+        let nonce = increment_account_1_nonce().await;
+        let tx_hash = web3
+            .deploy(*ACCOUNT_1, b"erc20", Some(nonce))
+            .await
+            .unwrap();
+        let contract_address = wait_for_receipt(tx_hash).await.contract_address.unwrap();
+
+        assert!(web3.is_contract(contract_address).await.unwrap());
+        assert!(!web3.is_contract(*ACCOUNT_1).await.unwrap());
+    }
 }
@@ -1,3 +1,6 @@
+use jsonrpsee::core::Error as JsonRpseeError;
+use jsonrpsee::types::error::CallError;
+use serde_json::Value;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,12 +11,28 @@ pub enum Web3Error {
     #[error("Error serializing or deserializing JSON data: {0}")]
     JsonParseError(String),
 
+    #[error("Error signing or recovering a message: {0}")]
+    MessageSigningError(String),
+
+    #[error("JSON-RPC error {code}: {message}")]
+    RpcErrorObject {
+        code: i32,
+        message: String,
+        data: Option<Value>,
+    },
+
     #[error("Error sending a HTTP JSON-RPC call: {0}")]
     RpcRequestError(String),
 
     #[error("Error receiving a HTTP JSON-RPC response: {0}")]
     RpcResponseError(String),
 
+    #[error("Error building transaction: {0}")]
+    TransactionBuildError(String),
+
+    #[error("Error serializing transaction: {0}")]
+    TransactionSerializeError(String),
+
     #[error("Error signing transaction: {0}")]
     TransactionSigningError(String),
 }
@@ -25,3 +44,19 @@ impl From<serde_json::Error> for Web3Error {
         Web3Error::JsonParseError(error.to_string())
     }
 }
+
+// 节点返回的JSON-RPC错误对象保留错误码和数据，其他错误（如连接失败）转换为`RpcRequestError`
+impl From<JsonRpseeError> for Web3Error {
+    fn from(error: JsonRpseeError) -> Self {
+        match error {
+            JsonRpseeError::Call(CallError::Custom(error_object)) => Web3Error::RpcErrorObject {
+                code: error_object.code(),
+                message: error_object.message().to_string(),
+                data: error_object
+                    .data()
+                    .and_then(|data| serde_json::from_str(data.get()).ok()),
+            },
+            error => Web3Error::RpcRequestError(error.to_string()),
+        }
+    }
+}
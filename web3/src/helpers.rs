@@ -2,11 +2,14 @@
 pub(crate) mod tests {
     use std::str::FromStr;
 
+    use std::time::Duration;
+
     use crate::Web3;
-    use ethereum_types::{H160, U256};
+    use ethereum_types::{H160, H256, U256};
     use lazy_static::lazy_static;
     use tokio::sync::Mutex;
     use types::account::Account;
+    use types::transaction::TransactionReceipt;
 
     lazy_static! {
         pub(crate) static ref ACCOUNT_1: Account =
@@ -24,4 +27,15 @@ pub(crate) mod tests {
         *ACCOUNT_1_NONCE.lock().await = nonce;
         nonce
     }
+
+    /// 轮询等待交易被打包并返回交易收据
+    pub async fn wait_for_receipt(tx_hash: H256) -> TransactionReceipt {
+        loop {
+            if let Ok(receipt) = web3().transaction_receipt(tx_hash).await {
+                return receipt;
+            }
+
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
 }
@@ -1,7 +1,9 @@
 use crate::error::{Result, Web3Error};
 use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::params::BatchRequestBuilder;
 use jsonrpsee::core::traits::ToRpcParams;
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 use log::*;
 use serde_json::Value;
 
@@ -12,14 +14,39 @@ pub mod error;
 mod helpers;
 pub mod transaction;
 
+/// JSON-RPC客户端
+///
+/// 内部的`HttpClient`基于`Arc`共享连接，克隆`Web3`的开销很小，
+/// 克隆出的实例共用同一个连接池。`Web3`是`Send + Sync`的，可以直接克隆后在多个任务之间共享，
+/// 不需要再用`Arc`包装
+///
+/// 节点在同一地址上同时接受HTTP和WebSocket连接，订阅时使用相同地址的WebSocket连接
+#[derive(Clone)]
 pub struct Web3 {
     client: HttpClient,
+    url: String,
 }
 
 impl Web3 {
     pub fn new(url: &str) -> Result<Self> {
         let client = Web3::get_client(url)?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            url: url.to_string(),
+        })
+    }
+
+    /// 建立到节点的WebSocket连接，`http`和`https`分别替换为`ws`和`wss`
+    async fn get_ws_client(&self) -> Result<WsClient> {
+        let url = match self.url.strip_prefix("http") {
+            Some(rest) => format!("ws{}", rest),
+            None => self.url.clone(),
+        };
+
+        WsClientBuilder::default()
+            .build(url)
+            .await
+            .map_err(|e| Web3Error::ClientError(e.to_string()))
     }
 
     fn get_client(url: &str) -> Result<HttpClient> {
@@ -38,10 +65,122 @@ impl Web3 {
             .client
             .request(method, params)
             .await
-            .map_err(|e| Web3Error::RpcRequestError(e.to_string()));
+            .map_err(Web3Error::from);
 
         trace!("RPC Response {:?}", response);
 
         response
     }
-}
\ No newline at end of file
+    /// 在一次批量请求中发送多个RPC调用，按请求的顺序返回结果
+    ///
+    /// 任意一个调用失败时返回错误
+    pub async fn send_batch<Params>(&self, requests: Vec<(&str, Params)>) -> Result<Vec<Value>>
+    where
+        Params: ToRpcParams + Send + std::fmt::Debug,
+    {
+        trace!("Sending batch RPC {:?}", requests);
+
+        if requests.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut batch = BatchRequestBuilder::new();
+        for (method, params) in requests {
+            batch.insert(method, params)?;
+        }
+
+        let response = self
+            .client
+            .batch_request::<Value>(batch)
+            .await
+            .map_err(|e| Web3Error::RpcRequestError(e.to_string()))?;
+
+        trace!("Batch RPC Response {:?}", response);
+
+        let results = response.into_ok().map_err(|errors| {
+            let errors = errors.map(|e| format!("{:?}", e)).collect::<Vec<_>>();
+            Web3Error::RpcResponseError(errors.join(", "))
+        })?;
+
+        Ok(results.collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::web3;
+    use jsonrpsee::core::Error as JsonRpseeError;
+    use jsonrpsee::rpc_params;
+    use jsonrpsee::server::ServerBuilder;
+    use jsonrpsee::types::error::{CallError, ErrorObject};
+    use jsonrpsee::RpcModule;
+    use serde_json::json;
+    use types::block::BlockNumber;
+
+    #[tokio::test]
+    async fn it_issues_a_request_over_websocket() {
+        let client = WsClientBuilder::default()
+            .build("ws://127.0.0.1:8545")
+            .await
+            .unwrap();
+        let block_number: BlockNumber = client
+            .request("eth_blockNumber", rpc_params![])
+            .await
+            .unwrap();
+
+        // 两次请求之间可能打包了新的区块
+        assert!(block_number.0 <= web3().get_block_number().await.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn it_preserves_the_rpc_error_code() {
+        // 启动一个只返回固定错误的本地服务器，代替真实的节点
+        let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap();
+        let mut module = RpcModule::new(());
+        module
+            .register_method("eth_sendTransaction", |_, _| {
+                Err::<(), _>(JsonRpseeError::Call(CallError::Custom(ErrorObject::owned(
+                    -32000,
+                    "nonce too low",
+                    Some(json!({ "nonce": 1 })),
+                ))))
+            })
+            .unwrap();
+        let handle = server.start(module).unwrap();
+
+        let web3 = Web3::new(&format!("http://{}", address)).unwrap();
+        let response = web3.send_rpc("eth_sendTransaction", rpc_params![]).await;
+
+        match response {
+            Err(Web3Error::RpcErrorObject {
+                code,
+                message,
+                data,
+            }) => {
+                assert_eq!(code, -32000);
+                assert_eq!(message, "nonce too low");
+                assert_eq!(data, Some(json!({ "nonce": 1 })));
+            }
+            response => panic!("expected an error object, got {:?}", response),
+        }
+
+        handle.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_shares_a_cloned_client_across_tasks() {
+        let web3 = web3();
+        let handles = (0..4)
+            .map(|_| {
+                let web3 = web3.clone();
+                tokio::spawn(async move { web3.get_block_number().await })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert!(handle.await.unwrap().is_ok());
+        }
+    }
+}
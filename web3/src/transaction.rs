@@ -1,10 +1,15 @@
-use crate::error::Result;
+use crate::error::{Result, Web3Error};
 use crate::Web3;
-use ethereum_types::H256;
+use ethereum_types::{H256, U256};
 use jsonrpsee::rpc_params;
 use serde_json::to_value;
+use types::account::Account;
+use types::block::BlockTag;
 use types::bytes::Bytes;
-use types::transaction::{TransactionReceipt, TransactionRequest};
+use types::transaction::{
+    Log, LogFilter, SignedTransaction, Transaction, TransactionReceipt, TransactionRequest,
+};
+use utils::crypto::{private_key_address, SecretKey};
 
 impl Web3 {
     /// 异步发送交易请求
@@ -58,6 +63,85 @@ impl Web3 {
         Ok(tx_hash)
     }
 
+    /// 异步发送已签名的交易
+    ///
+    /// 在内部将已签名的交易序列化后通过`eth_sendRawTransaction`发送，
+    /// 调用方不需要自己序列化交易。nonce在签名的范围内，节点拒绝缺少nonce的已签名交易，
+    /// 因此这类交易在发送前就被拒绝
+    ///
+    /// 参数:
+    /// - `signed`: 已签名的交易
+    ///
+    /// 返回:
+    /// - `Result<H256>`: 发送成功时包含交易哈希，否则包含一个错误
+    pub async fn send_signed(&self, signed: SignedTransaction) -> Result<H256> {
+        if Transaction::from(signed.clone()).nonce.is_none() {
+            return Err(Web3Error::TransactionBuildError(
+                "signed transaction is missing a nonce".into(),
+            ));
+        }
+
+        let raw_transaction = bincode::serialize(&signed)
+            .map_err(|e| Web3Error::TransactionSerializeError(e.to_string()))?;
+
+        self.send_raw(raw_transaction.into()).await
+    }
+
+    /// 异步构造、签名并发送一笔交易
+    ///
+    /// 由私钥得到发送方地址，从节点获取包含交易池中交易的nonce，构造交易后使用私钥签名，
+    /// 再通过`eth_sendRawTransaction`发送，调用方不需要自己拼接这些步骤
+    ///
+    /// 参数:
+    /// - `from_key`: 发送方的私钥
+    /// - `to`: 接收方，为None时部署合约
+    /// - `value`: 转账金额
+    /// - `data`: 交易数据
+    ///
+    /// 返回:
+    /// - `Result<H256>`: 发送成功时包含交易哈希，否则包含一个错误
+    pub async fn build_sign_send(
+        &self,
+        from_key: SecretKey,
+        to: Option<Account>,
+        value: U256,
+        data: Option<Bytes>,
+    ) -> Result<H256> {
+        let from = private_key_address(&from_key);
+        let nonce = self
+            .get_transaction_count(from, Some(BlockTag::Pending))
+            .await?
+            + 1;
+        let transaction = Transaction::new(from, to, value, Some(nonce), data)
+            .map_err(|e| Web3Error::TransactionBuildError(e.to_string()))?;
+        let signed = self.sign_transaction(transaction, from_key)?;
+
+        self.send_signed(signed).await
+    }
+
+    /// 异步估算交易所需的gas
+    ///
+    /// 调用`eth_estimateGas`在节点上模拟执行交易，返回执行所需的gas，
+    /// 可以据此为交易设置合理的gas上限
+    ///
+    /// # 参数
+    /// * `request` - 需要估算的交易请求
+    ///
+    /// # 返回值
+    /// 返回一个 `Result` 类型，包含估算的gas；交易执行失败时返回错误
+    pub async fn estimate_gas(&self, request: TransactionRequest) -> Result<U256> {
+        // 将交易请求转换为 RPC 调用所需的值类型
+        let request = to_value(&request)?;
+        // 构造 RPC 调用参数
+        let params = rpc_params![request];
+        // 发送 RPC 调用并等待响应
+        let response = self.send_rpc("eth_estimateGas", params).await?;
+        // 解析十六进制的gas值
+        let gas = serde_json::from_value(response)?;
+
+        Ok(gas)
+    }
+
     /// 异步获取交易收据
     ///
     /// 本函数通过RPC调用以太坊节点获取指定交易哈希的交易收据
@@ -87,14 +171,38 @@ impl Web3 {
         // 返回解析后的交易收据
         Ok(receipt)
     }
+
+    /// 异步按过滤条件查询日志
+    ///
+    /// 将过滤条件序列化为`eth_getLogs`的过滤对象，返回所有满足条件的日志
+    ///
+    /// # 参数
+    /// * `filter` - 日志过滤条件，包含区块范围、合约地址和主题
+    ///
+    /// # 返回值
+    /// 返回一个 `Result` 类型，包含满足条件的日志列表
+    pub async fn get_logs(&self, filter: LogFilter) -> Result<Vec<Log>> {
+        // 将过滤条件转换为 RPC 调用所需的值类型
+        let filter = to_value(&filter)?;
+        // 构造 RPC 调用参数
+        let params = rpc_params![filter];
+        // 发送 RPC 调用并等待响应
+        let response = self.send_rpc("eth_getLogs", params).await?;
+        // 解析响应数据为日志列表
+        let logs = serde_json::from_value(response)?;
+
+        Ok(logs)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::helpers::tests::{increment_account_1_nonce, web3, ACCOUNT_1, ACCOUNT_2};
-    use ethereum_types::U256;
-    use types::transaction::Transaction;
+    use crate::helpers::tests::{
+        increment_account_1_nonce, wait_for_receipt, web3, ACCOUNT_1, ACCOUNT_2,
+    };
+    use types::transaction::{Transaction, DEFAULT_GAS, DEFAULT_GAS_PRICE};
+    use utils::crypto::{keypair, public_key_address};
 
     async fn transaction() -> Transaction {
         let nonce = increment_account_1_nonce().await;
@@ -112,4 +220,122 @@ pub mod tests {
         let transaction_request: TransactionRequest = transaction().await.into();
         web3().send(transaction_request).await
     }
+
+    #[tokio::test]
+    async fn it_sends_a_signed_transaction() {
+        // 新生成的密钥对应的账户在节点上不存在，节点解析并验证签名后因找不到发送者而拒绝交易
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+        let transaction = Transaction::new(
+            from,
+            Some(*ACCOUNT_2),
+            U256::from(10),
+            Some(U256::one()),
+            None,
+        )
+        .unwrap();
+        let web3 = web3();
+        let signed = web3.sign_transaction(transaction, secret_key).unwrap();
+        let response = web3.send_signed(signed).await;
+
+        assert!(
+            matches!(response, Err(Web3Error::RpcErrorObject { .. })),
+            "expected the node to reject the unknown sender, got {:?}",
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn it_rejects_a_signed_transaction_without_a_nonce() {
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+        let transaction =
+            Transaction::new(from, Some(*ACCOUNT_2), U256::from(10), None, None).unwrap();
+        let web3 = web3();
+        let signed = web3.sign_transaction(transaction, secret_key).unwrap();
+        let response = web3.send_signed(signed).await;
+
+        assert!(
+            matches!(response, Err(Web3Error::TransactionBuildError(_))),
+            "expected the nonce-less transaction to be rejected, got {:?}",
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn it_builds_signs_and_sends_a_transfer() {
+        let web3 = web3();
+        let (secret_key, public_key) = keypair();
+        let from = public_key_address(&public_key);
+
+        // 先为新账户转入余额，使其可以支付转账和gas费用
+        let nonce = increment_account_1_nonce().await;
+        let funding =
+            Transaction::new(*ACCOUNT_1, Some(from), U256::from(1_000), Some(nonce), None).unwrap();
+        wait_for_receipt(web3.send(funding.into()).await.unwrap()).await;
+
+        let tx_hash = web3
+            .build_sign_send(secret_key, Some(*ACCOUNT_2), U256::from(10), None)
+            .await
+            .unwrap();
+        let receipt = wait_for_receipt(tx_hash).await;
+
+        assert_eq!(receipt.transaction_hash, tx_hash);
+        // gas上限低于转账的固有消耗，gas费用全部被扣除
+        let fee = U256::from(DEFAULT_GAS * DEFAULT_GAS_PRICE);
+        assert_eq!(web3.get_balance(from).await.unwrap(), U256::from(990) - fee);
+    }
+
+    #[tokio::test]
+    async fn it_estimates_gas_for_a_transfer() {
+        // 估算不会消耗nonce，由节点使用账户当前的nonce
+        let transaction =
+            Transaction::new(*ACCOUNT_1, Some(*ACCOUNT_2), U256::from(10), None, None).unwrap();
+        let gas = web3().estimate_gas(transaction.into()).await.unwrap();
+
+        assert_eq!(gas, U256::from(21_000));
+    }
+
+    #[tokio::test]
+    async fn it_gets_logs_by_address() {
+        let web3 = web3();
+        let nonce = increment_account_1_nonce().await;
+        let tx_hash = web3
+            .deploy(*ACCOUNT_1, b"erc20", Some(nonce))
+            .await
+            .unwrap();
+        let receipt = wait_for_receipt(tx_hash).await;
+        let contract_address = receipt.contract_address.unwrap();
+
+        // 铸币时erc20合约发出一条Transfer日志
+        let account = Account::random();
+        let nonce = increment_account_1_nonce().await;
+        let data = format!("mint,String,{:?},U64,10", account);
+        let mint = Transaction::new(
+            *ACCOUNT_1,
+            Some(contract_address),
+            U256::zero(),
+            Some(nonce),
+            Some(data.into()),
+        )
+        .unwrap();
+        let mint_hash = web3.send(mint.into()).await.unwrap();
+        wait_for_receipt(mint_hash).await;
+
+        let filter = LogFilter {
+            address: Some(contract_address),
+            ..Default::default()
+        };
+        let logs = web3.get_logs(filter).await.unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].address, contract_address);
+        assert_eq!(logs[0].transaction_hash, Some(mint_hash));
+
+        let unrelated = LogFilter {
+            address: Some(Account::random()),
+            ..Default::default()
+        };
+        assert!(web3.get_logs(unrelated).await.unwrap().is_empty());
+    }
 }